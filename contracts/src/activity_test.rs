@@ -0,0 +1,50 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_last_activity_updates_after_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 50);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(client.get_last_activity(&stream_id), 50);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&stream_id, &receiver);
+
+    assert_eq!(client.get_last_activity(&stream_id), 150);
+}
@@ -134,6 +134,7 @@ fn test_create_stream_with_allowed_token() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -177,6 +178,7 @@ fn test_create_stream_with_disallowed_token() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -209,9 +211,58 @@ fn test_create_stream_allowlist_disabled() {
         &1000,
         &100,
         &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_blocked_token_rejected_while_allowlist_disabled() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (blocked_token, _) = create_token_contract(&env, &admin);
+    let (other_token, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &blocked_token).mint(&sender, &1000);
+    StellarAssetClient::new(&env, &other_token).mint(&sender, &1000);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    client.block_token(&admin, &blocked_token);
+    assert!(!client.is_allowlist_enabled());
+    assert!(!client.is_token_allowed(&blocked_token));
+
+    let result = client.try_create_stream(
+        &sender,
+        &receiver,
+        &blocked_token,
+        &1000,
+        &100,
+        &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
+    assert_eq!(result.err(), Some(Ok(crate::errors::Error::TokenBlocked)));
 
+    let result = client.try_create_stream(
+        &sender,
+        &receiver,
+        &other_token,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
     assert!(result.is_ok());
 }
@@ -0,0 +1,176 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, BytesN, Env, Symbol, TryFromVal,
+};
+
+use crate::errors::Error;
+use crate::types::{CurveType, StreamMetadataUpdatedEvent, StreamRescheduledEvent};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+fn setup_stream<'a>(env: &Env) -> (StellarStreamContractClient<'a>, u64, Address, Address) {
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    (client, stream_id, sender, receiver)
+}
+
+#[test]
+fn test_amend_untouched_stream_with_receiver_consent() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 50);
+
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+
+    client.amend_stream(&sender, &stream_id, &100, &400, &CurveType::Exponential);
+
+    // Both parties must have authorized the amendment
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == sender));
+    assert!(auths.iter().any(|(addr, _)| *addr == receiver));
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.start_time, 100);
+    assert_eq!(stream.end_time, 400);
+    assert_eq!(stream.curve_type, CurveType::Exponential);
+    assert_eq!(stream.total_amount, 1000);
+}
+
+#[test]
+fn test_amend_rejected_after_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 150);
+
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    client.withdraw(&stream_id, &receiver);
+
+    let result = client.try_amend_stream(&sender, &stream_id, &100, &400, &CurveType::Linear);
+    assert_eq!(result, Err(Ok(Error::StreamAlreadyClaimed)));
+}
+
+#[test]
+fn test_amend_rejects_invalid_time_range() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (client, stream_id, sender, _) = setup_stream(&env);
+
+    let result = client.try_amend_stream(&sender, &stream_id, &400, &100, &CurveType::Linear);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+}
+
+#[test]
+fn test_reschedule_lengthens_stream() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 150);
+
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+
+    client.reschedule_stream(&stream_id, &sender, &300);
+    let event = env
+        .events()
+        .all()
+        .iter()
+        .find_map(|(_, topics, data)| {
+            if Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("resched")) {
+                StreamRescheduledEvent::try_from_val(&env, &data).ok()
+            } else {
+                None
+            }
+        })
+        .unwrap();
+    assert_eq!((event.old_end_time, event.new_end_time), (200, 300));
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.end_time, 300);
+    assert_eq!(stream.total_amount, 1000);
+
+    // A quarter of the stretched schedule has elapsed
+    assert_eq!(client.withdraw(&stream_id, &receiver), 250);
+}
+
+#[test]
+fn test_reschedule_shortens_stream_but_not_below_withdrawn() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 150);
+
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    client.withdraw(&stream_id, &receiver);
+
+    // Stretching to 300 would unlock only 250 of the 500 already paid
+    let result = client.try_reschedule_stream(&stream_id, &sender, &300);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+
+    let result = client.try_reschedule_stream(&stream_id, &sender, &150);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+
+    client.reschedule_stream(&stream_id, &sender, &175);
+    assert_eq!(client.get_stream(&stream_id).end_time, 175);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 166);
+
+    client.cancel(&stream_id, &sender);
+    let result = client.try_reschedule_stream(&stream_id, &sender, &400);
+    assert_eq!(result, Err(Ok(Error::AlreadyCancelled)));
+}
+
+#[test]
+fn test_sender_updates_stream_metadata() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    assert_eq!(client.get_stream(&stream_id).metadata, None);
+
+    let description = BytesN::from_array(&env, &[7; 32]);
+    client.set_stream_metadata(&stream_id, &sender, &Some(description.clone()));
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("metadata"))
+        })
+        .unwrap();
+    let event = StreamMetadataUpdatedEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.old_metadata, None);
+    assert_eq!(event.new_metadata, Some(description.clone()));
+    assert_eq!(client.get_stream(&stream_id).metadata, Some(description));
+
+    let result = client.try_set_stream_metadata(&stream_id, &receiver, &None);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    client.cancel(&stream_id, &sender);
+    let result = client.try_set_stream_metadata(&stream_id, &sender, &None);
+    assert_eq!(result, Err(Ok(Error::AlreadyCancelled)));
+}
@@ -0,0 +1,527 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::types::{CurveType, Role};
+
+// Condition oracle reporting whether a delivery was attested
+#[contract]
+pub struct MockConditionOracle;
+
+#[contractimpl]
+impl MockConditionOracle {
+    pub fn set_delivered(env: Env, delivered: bool) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("delivered"), &delivered);
+    }
+
+    pub fn condition(env: Env, _stream_id: u64) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("delivered"))
+            .unwrap_or(false)
+    }
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_preview_cancel_matches_cancellation() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 130);
+    client.withdraw(&stream_id, &receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 175);
+    let (to_receiver, to_sender) = client.preview_cancel(&stream_id);
+    assert_eq!((to_receiver, to_sender), (450, 250));
+
+    let receiver_before = token.balance(&receiver);
+    client.cancel(&stream_id, &sender);
+
+    assert_eq!(token.balance(&receiver) - receiver_before, to_receiver);
+    assert_eq!(token.balance(&sender), to_sender);
+
+    let result = client.try_preview_cancel(&stream_id);
+    assert_eq!(result, Err(Ok(Error::AlreadyCancelled)));
+}
+
+#[test]
+fn test_refundable_to_sender_tracks_cancellation() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    for (timestamp, expected) in [(50, 1000), (100, 1000), (125, 750)] {
+        env.ledger().with_mut(|li| li.timestamp = timestamp);
+        assert_eq!(client.refundable_to_sender(&stream_id), expected);
+        assert_eq!(client.preview_cancel(&stream_id).1, expected);
+    }
+
+    // Withdrawals don't change what the sender is owed
+    env.ledger().with_mut(|li| li.timestamp = 140);
+    client.withdraw(&stream_id, &receiver);
+    assert_eq!(client.refundable_to_sender(&stream_id), 600);
+
+    env.ledger().with_mut(|li| li.timestamp = 160);
+    assert_eq!(client.refundable_to_sender(&stream_id), 400);
+    client.cancel(&stream_id, &sender);
+    assert_eq!(token.balance(&sender), 400);
+    assert_eq!(client.refundable_to_sender(&stream_id), 0);
+    assert_eq!(client.refundable_to_sender(&(stream_id + 1)), 0);
+}
+
+#[test]
+fn test_cancel_partial_keeps_flow_rate() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let result = client.try_cancel_partial(&stream_id, &sender, &600);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+    let result = client.try_cancel_partial(&stream_id, &receiver, &300);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    client.cancel_partial(&stream_id, &sender, &300);
+    assert_eq!(token.balance(&sender), 300);
+
+    let stream = client.get_stream(&stream_id);
+    assert!(!stream.cancelled);
+    assert_eq!(stream.total_amount, 700);
+    assert_eq!(stream.end_time, 170);
+
+    // Still 10 tokens per second, so nothing vested is lost
+    assert_eq!(client.withdraw(&stream_id, &receiver), 500);
+    env.ledger().with_mut(|li| li.timestamp = 170);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 200);
+
+    client.pause_stream(&stream_id, &sender);
+    let result = client.try_cancel_partial(&stream_id, &sender, &1);
+    assert_eq!(result, Err(Ok(Error::StreamPaused)));
+}
+
+#[test]
+fn test_purge_cancelled_after_retention() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_purge_retention(&admin, &1000);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    let result = client.try_purge_cancelled(&admin, &stream_id);
+    assert_eq!(result, Err(Ok(Error::StreamNotCancelled)));
+
+    // Settings that must not outlive the stream
+    let other = Address::generate(&env);
+    client.set_withdraw_destination(&stream_id, &receiver, &other);
+    client.approve_reduction(&receiver, &stream_id, &500);
+    client.set_fallback(&stream_id, &receiver, &other, &1000);
+    client.approve_receipt(&stream_id, &receiver, &other);
+    client.set_auto_cancel(&stream_id, &sender, &Some(100), &false);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.cancel(&stream_id, &sender);
+
+    env.ledger().with_mut(|li| li.timestamp = 1149);
+    let result = client.try_purge_cancelled(&admin, &stream_id);
+    assert_eq!(result, Err(Ok(Error::RetentionNotElapsed)));
+    assert!(client.get_stream(&stream_id).cancelled);
+
+    env.ledger().with_mut(|li| li.timestamp = 1150);
+    client.purge_cancelled(&admin, &stream_id);
+
+    assert!(matches!(
+        client.try_get_stream(&stream_id),
+        Err(Ok(Error::StreamNotFound))
+    ));
+    assert_eq!(client.get_withdraw_destination(&stream_id), None);
+    assert_eq!(client.get_reduction_floor(&stream_id), None);
+    assert_eq!(client.get_fallback(&stream_id), None);
+    assert_eq!(client.get_receipt_approval(&stream_id), None);
+    assert_eq!(client.get_auto_cancel(&stream_id), None);
+}
+
+#[test]
+fn test_conditional_cancel_only_when_oracle_reports_failure() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let oracle_id = env.register(MockConditionOracle, ());
+    let oracle = MockConditionOracleClient::new(&env, &oracle_id);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    oracle.set_delivered(&true);
+    let result = client.try_conditional_cancel(&stream_id, &sender, &oracle_id);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+    assert!(!client.get_stream(&stream_id).cancelled);
+
+    // Delivery attested as failed: the escrow can be unwound
+    oracle.set_delivered(&false);
+    client.conditional_cancel(&stream_id, &sender, &oracle_id);
+    assert!(client.get_stream(&stream_id).cancelled);
+    assert_eq!(token.balance(&receiver), 500);
+    assert_eq!(token.balance(&sender), 500);
+}
+
+#[test]
+fn test_auto_settle_pays_untouched_stream_after_grace() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    // Not opted in yet
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    let result = client.try_auto_settle(&stream_id);
+    assert_eq!(result, Err(Ok(Error::AutoSettleUnavailable)));
+
+    let outsider = Address::generate(&env);
+    let result = client.try_set_auto_cancel(&stream_id, &outsider, &Some(500), &false);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    client.set_auto_cancel(&stream_id, &sender, &Some(500), &false);
+    assert_eq!(client.get_auto_cancel(&stream_id), Some((500, false)));
+
+    // Still inside the grace period after end_time
+    env.ledger().with_mut(|li| li.timestamp = 699);
+    let result = client.try_auto_settle(&stream_id);
+    assert_eq!(result, Err(Ok(Error::AutoSettleUnavailable)));
+
+    env.ledger().with_mut(|li| li.timestamp = 700);
+    assert_eq!(client.auto_settle(&stream_id), 1000);
+    assert!(client.get_stream(&stream_id).cancelled);
+    assert_eq!(token.balance(&receiver), 1000);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(client.get_auto_cancel(&stream_id), None);
+
+    let result = client.try_auto_settle(&stream_id);
+    assert_eq!(result, Err(Ok(Error::AlreadyCancelled)));
+}
+
+#[test]
+fn test_auto_settle_skips_withdrawn_and_can_refund_sender() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &2000);
+
+    let touched = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    let untouched = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    client.set_auto_cancel(&touched, &sender, &Some(100), &true);
+    client.set_auto_cancel(&untouched, &sender, &Some(100), &true);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&touched, &receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    let result = client.try_auto_settle(&touched);
+    assert_eq!(result, Err(Ok(Error::AutoSettleUnavailable)));
+
+    assert_eq!(client.auto_settle(&untouched), 1000);
+    assert_eq!(token.balance(&sender), 1000);
+    assert_eq!(token.balance(&receiver), 500);
+}
+
+#[test]
+fn test_auto_settle_refuses_restricted_recipient() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let officer = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+    client.initialize(&admin);
+    client.grant_role(&admin, &officer, &Role::ComplianceOfficer);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    client.set_auto_cancel(&stream_id, &sender, &Some(100), &false);
+    client.restrict_address(&officer, &receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    let result = client.try_auto_settle(&stream_id);
+    assert_eq!(result, Err(Ok(Error::AutoSettleUnavailable)));
+    assert!(!client.get_stream(&stream_id).cancelled);
+    assert_eq!(token.balance(&receiver), 0);
+    assert_eq!(token.balance(&contract_id), 1000);
+}
+
+#[test]
+fn test_reduce_stream_amount_with_receiver_consent() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    // No consent yet
+    env.ledger().with_mut(|li| li.timestamp = 120);
+    let result = client.try_reduce_stream_amount(&sender, &stream_id, &800);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    let result = client.try_approve_reduction(&sender, &stream_id, &600);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    client.approve_reduction(&receiver, &stream_id, &600);
+    assert_eq!(client.get_reduction_floor(&stream_id), Some(600));
+
+    client.reduce_stream_amount(&sender, &stream_id, &800);
+    assert_eq!(token.balance(&sender), 200);
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.total_amount, 800);
+    assert_eq!(stream.end_time, 180);
+
+    // The consent stands until revoked
+    client.reduce_stream_amount(&sender, &stream_id, &700);
+    assert_eq!(token.balance(&sender), 300);
+
+    client.revoke_reduction(&receiver, &stream_id);
+    let result = client.try_reduce_stream_amount(&sender, &stream_id, &650);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_reduce_stream_amount_rejects_below_floor_or_vested() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    client.approve_reduction(&receiver, &stream_id, &600);
+
+    env.ledger().with_mut(|li| li.timestamp = 120);
+    let result = client.try_reduce_stream_amount(&sender, &stream_id, &599);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+    // 700 has already vested, so that is the real floor
+    env.ledger().with_mut(|li| li.timestamp = 170);
+    let result = client.try_reduce_stream_amount(&sender, &stream_id, &650);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.total_amount, 1000);
+    assert_eq!(token.balance(&sender), 0);
+}
@@ -6,6 +6,9 @@ use soroban_sdk::{
     Address, Env,
 };
 
+use crate::errors::Error;
+use crate::types::Stream;
+
 fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
     let contract_id = env
         .register_stellar_asset_contract_v2(admin.clone())
@@ -22,8 +25,17 @@ fn set_compliance_officer_role(env: &Env, contract_id: &Address, officer: &Addre
     });
 }
 
+fn enable_clawback(env: &Env, contract_id: &Address, stream_id: u64) {
+    env.as_contract(contract_id, || {
+        let key = (crate::storage::STREAM_COUNT, stream_id);
+        let mut stream: Stream = env.storage().instance().get(&key).unwrap();
+        stream.clawback_enabled = true;
+        env.storage().instance().set(&key, &stream);
+    });
+}
+
 #[test]
-fn test_governance_clawback() {
+fn test_clawback_stream() {
     let env = Env::default();
     env.mock_all_auths();
 
@@ -31,7 +43,6 @@ fn test_governance_clawback() {
     let sender = Address::generate(&env);
     let receiver = Address::generate(&env);
     let officer = Address::generate(&env);
-    let issuer = Address::generate(&env);
 
     let (token_address, _token_client) = create_token_contract(&env, &admin);
     let token_admin_client = StellarAssetClient::new(&env, &token_address);
@@ -52,6 +63,7 @@ fn test_governance_clawback() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -69,16 +81,17 @@ fn test_governance_clawback() {
     });
 
     // Execute clawback
-    client.governance_clawback(&stream_id, &officer, &issuer, &None);
+    enable_clawback(&env, &contract_id, stream_id);
+    assert_eq!(client.clawback_stream(&officer, &stream_id), 1000);
 
     // Verify stream is cancelled
     let stream = client.get_stream(&stream_id);
     assert!(stream.cancelled);
 
-    // Verify issuer received the funds
+    // Verify the sender got everything back, vested or not
     let token_client = TokenClient::new(&env, &token_address);
-    let issuer_balance = token_client.balance(&issuer);
-    assert_eq!(issuer_balance, 1000); // All funds clawed back
+    assert_eq!(token_client.balance(&sender), 1000);
+    assert_eq!(token_client.balance(&receiver), 0);
 }
 
 #[test]
@@ -91,7 +104,6 @@ fn test_clawback_requires_compliance_officer_role() {
     let sender = Address::generate(&env);
     let receiver = Address::generate(&env);
     let non_officer = Address::generate(&env);
-    let issuer = Address::generate(&env);
 
     let (token_address, _token_client) = create_token_contract(&env, &admin);
     let token_admin_client = StellarAssetClient::new(&env, &token_address);
@@ -108,12 +120,14 @@ fn test_clawback_requires_compliance_officer_role() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
 
     // Non-officer tries to clawback - should panic
-    client.governance_clawback(&stream_id, &non_officer, &issuer, &None);
+    enable_clawback(&env, &contract_id, stream_id);
+    client.clawback_stream(&non_officer, &stream_id);
 }
 
 #[test]
@@ -125,7 +139,6 @@ fn test_clawback_after_partial_withdrawal() {
     let sender = Address::generate(&env);
     let receiver = Address::generate(&env);
     let officer = Address::generate(&env);
-    let issuer = Address::generate(&env);
 
     let (token_address, _token_client) = create_token_contract(&env, &admin);
     let token_admin_client = StellarAssetClient::new(&env, &token_address);
@@ -144,6 +157,7 @@ fn test_clawback_after_partial_withdrawal() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -164,10 +178,85 @@ fn test_clawback_after_partial_withdrawal() {
     client.withdraw(&stream_id, &receiver);
 
     // Clawback remaining 500
-    client.governance_clawback(&stream_id, &officer, &issuer, &None);
+    enable_clawback(&env, &contract_id, stream_id);
+    assert_eq!(client.clawback_stream(&officer, &stream_id), 500);
 
-    // Issuer should receive remaining 500
+    // Sender should receive only the remaining 500
     let token_client = TokenClient::new(&env, &token_address);
-    let issuer_balance = token_client.balance(&issuer);
-    assert_eq!(issuer_balance, 500);
+    assert_eq!(token_client.balance(&sender), 500);
+    assert_eq!(token_client.balance(&receiver), 500);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_clawback_requires_enabled_stream() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let officer = Address::generate(&env);
+
+    let (token_address, _token_client) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_address);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    set_compliance_officer_role(&env, &contract_id, &officer);
+
+    token_admin_client.mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+
+    let result = client.try_clawback_stream(&officer, &stream_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(!client.get_stream(&stream_id).cancelled);
+}
+
+#[test]
+fn test_clawback_enabled_at_creation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let officer = Address::generate(&env);
+
+    let (token_address, token_client) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_address).mint(&sender, &1000);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    set_compliance_officer_role(&env, &contract_id, &officer);
+
+    let stream_id = client.create_stream_with_arbiter(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &100,
+        &200,
+        &crate::types::CurveType::Linear,
+        &false,
+        &None,
+        &true,
+    );
+    assert!(client.get_stream(&stream_id).clawback_enabled);
+
+    assert_eq!(client.clawback_stream(&officer, &stream_id), 1000);
+    assert_eq!(token_client.balance(&sender), 1000);
 }
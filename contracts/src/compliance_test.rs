@@ -0,0 +1,306 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::MAX_BATCH;
+use crate::types::{AddressStatus, CurveType, Role};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct ComplianceContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    token: TokenClient<'a>,
+    token_id: Address,
+    admin: Address,
+    officer: Address,
+    sender: Address,
+    receiver: Address,
+    stream_id: u64,
+}
+
+fn setup(env: &Env) -> ComplianceContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let officer = Address::generate(env);
+    client.initialize(&admin);
+    client.grant_role(&admin, &officer, &Role::ComplianceOfficer);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let (token_id, token) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    ComplianceContext {
+        client,
+        token,
+        token_id,
+        admin,
+        officer,
+        sender,
+        receiver,
+        stream_id,
+    }
+}
+
+#[test]
+fn test_restricted_receiver_cannot_withdraw_until_unrestricted() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    ctx.client.withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 500);
+
+    // Receiver becomes restricted mid-stream
+    ctx.client.restrict_address(&ctx.officer, &ctx.receiver);
+    env.ledger().with_mut(|li| li.timestamp = 175);
+    let result = ctx.client.try_withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+
+    // Funds stay claimable once the restriction is lifted
+    ctx.client.unrestrict_address(&ctx.officer, &ctx.receiver);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    ctx.client.withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 1000);
+}
+
+#[test]
+fn test_restricted_receiver_blocks_new_streams_and_proposals() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let restricted = Address::generate(&env);
+    ctx.client.restrict_address(&ctx.officer, &restricted);
+
+    let result = ctx.client.try_create_stream(
+        &ctx.sender,
+        &restricted,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+
+    let result = ctx.client.try_create_proposal(
+        &ctx.sender,
+        &restricted,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &1,
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+}
+
+#[test]
+fn test_receipt_cannot_move_to_restricted_receiver() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let restricted = Address::generate(&env);
+    ctx.client.restrict_address(&ctx.officer, &restricted);
+
+    let result = ctx
+        .client
+        .try_transfer_receiver(&ctx.stream_id, &ctx.sender, &restricted);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).receiver, ctx.receiver);
+
+    // Restricted after the change was requested: the receiver can't approve it
+    let stream_id = ctx.client.create_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    let new_receiver = Address::generate(&env);
+    ctx.client
+        .set_require_receiver_consent(&stream_id, &ctx.receiver, &true);
+    ctx.client
+        .transfer_receiver(&stream_id, &ctx.sender, &new_receiver);
+    ctx.client.restrict_address(&ctx.officer, &new_receiver);
+    let result = ctx
+        .client
+        .try_approve_receiver_change(&stream_id, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+    assert_eq!(ctx.client.get_stream(&stream_id).receiver, ctx.receiver);
+}
+
+#[test]
+fn test_non_officer_cannot_restrict_address() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_restrict_address(&outsider, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(!ctx.client.is_address_restricted(&ctx.receiver));
+}
+
+#[test]
+fn test_restrictions_are_separate_from_admin_duties() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    // A plain Admin can't touch the restricted list...
+    let result = ctx.client.try_restrict_address(&ctx.admin, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    let mut addresses = Vec::new(&env);
+    addresses.push_back(ctx.receiver.clone());
+    let result = ctx.client.try_restrict_addresses(&ctx.admin, &addresses);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client.restrict_address(&ctx.officer, &ctx.receiver);
+    let result = ctx.client.try_unrestrict_address(&ctx.admin, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(ctx.client.is_address_restricted(&ctx.receiver));
+
+    // ...and a ComplianceOfficer can't manage roles
+    let outsider = Address::generate(&env);
+    let result = ctx
+        .client
+        .try_grant_role(&ctx.officer, &outsider, &Role::ComplianceOfficer);
+    assert!(result.is_err());
+    assert!(ctx.client.get_address_roles(&outsider).is_empty());
+}
+
+#[test]
+fn test_bulk_restrict_addresses() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let other = Address::generate(&env);
+    let mut addresses = Vec::new(&env);
+    addresses.push_back(ctx.receiver.clone());
+    addresses.push_back(other.clone());
+    ctx.client.restrict_addresses(&ctx.officer, &addresses);
+
+    assert!(ctx.client.is_address_restricted(&ctx.receiver));
+    assert!(ctx.client.is_address_restricted(&other));
+}
+
+#[test]
+fn test_bulk_restrict_rejects_oversized_batch() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let mut addresses = Vec::new(&env);
+    for _ in 0..=MAX_BATCH {
+        addresses.push_back(Address::generate(&env));
+    }
+
+    let result = ctx.client.try_restrict_addresses(&ctx.officer, &addresses);
+    assert_eq!(result, Err(Ok(Error::BatchTooLarge)));
+    assert!(ctx.client.get_restricted_addresses().is_empty());
+}
+
+#[test]
+fn test_address_status_for_admin() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    assert_eq!(
+        ctx.client.get_address_status(&ctx.admin),
+        AddressStatus {
+            roles: Vec::from_array(&env, [Role::Admin, Role::Pauser, Role::TreasuryManager]),
+            restricted: false,
+            sending_streams: 0,
+            receiving_streams: 0,
+        }
+    );
+}
+
+#[test]
+fn test_get_address_roles_lists_every_role_held() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let operator = Address::generate(&env);
+    assert!(ctx.client.get_address_roles(&operator).is_empty());
+
+    ctx.client
+        .grant_role(&ctx.admin, &operator, &Role::ComplianceOfficer);
+    ctx.client
+        .grant_role(&ctx.admin, &operator, &Role::Guardian);
+    assert_eq!(
+        ctx.client.get_address_roles(&operator),
+        Vec::from_array(&env, [Role::ComplianceOfficer, Role::Guardian])
+    );
+}
+
+#[test]
+fn test_address_status_for_restricted_address() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client.restrict_address(&ctx.officer, &ctx.sender);
+
+    let status = ctx.client.get_address_status(&ctx.sender);
+    assert!(status.restricted);
+    assert!(status.roles.is_empty());
+    assert_eq!(status.sending_streams, 1);
+    assert_eq!(status.receiving_streams, 0);
+}
+
+#[test]
+fn test_address_status_for_receiver() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    assert_eq!(
+        ctx.client.get_address_status(&ctx.receiver),
+        AddressStatus {
+            roles: Vec::new(&env),
+            restricted: false,
+            sending_streams: 0,
+            receiving_streams: 1,
+        }
+    );
+
+    // Cancelled streams drop out of both counts
+    ctx.client.cancel(&ctx.stream_id, &ctx.sender);
+    let receiver_status = ctx.client.get_address_status(&ctx.receiver);
+    let sender_status = ctx.client.get_address_status(&ctx.sender);
+    assert_eq!(receiver_status.receiving_streams, 0);
+    assert_eq!(sender_status.sending_streams, 0);
+}
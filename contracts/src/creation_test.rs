@@ -0,0 +1,280 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::{DEFAULT_MAX_BACKDATING, DEFAULT_PURGE_RETENTION, SECONDS_PER_MONTH};
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct CreationContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    token_id: Address,
+    sender: Address,
+    receiver: Address,
+}
+
+fn setup(env: &Env) -> CreationContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    CreationContext {
+        client,
+        token_id,
+        sender,
+        receiver,
+    }
+}
+
+fn create_stream(ctx: &CreationContext) -> u64 {
+    ctx.client.create_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    )
+}
+
+#[test]
+fn test_peek_next_stream_id_matches_created_id() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    for _ in 0..3 {
+        let expected = ctx.client.peek_next_stream_id();
+        assert_eq!(create_stream(&ctx), expected);
+    }
+    assert_eq!(ctx.client.peek_next_stream_id(), 3);
+}
+
+fn create_streams(ctx: &CreationContext, count: i128) {
+    // Amounts 100, 200, ... tell the streams apart
+    for i in 1..=count {
+        ctx.client.create_stream(
+            &ctx.sender,
+            &ctx.receiver,
+            &ctx.token_id,
+            &(100 * i),
+            &100,
+            &200,
+            &100,
+            &CurveType::Linear,
+            &false,
+        );
+    }
+}
+
+fn page_amounts(env: &Env, ctx: &CreationContext, start_id: u64, limit: u32) -> Vec<i128> {
+    let mut amounts = Vec::new(env);
+    for stream in ctx.client.get_streams_page(&start_id, &limit).iter() {
+        amounts.push_back(stream.total_amount);
+    }
+    amounts
+}
+
+#[test]
+fn test_streams_page_walks_all_streams() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    create_streams(&ctx, 5);
+    assert_eq!(ctx.client.get_stream_count(), 5);
+
+    assert_eq!(page_amounts(&env, &ctx, 0, 2), vec![&env, 100, 200]);
+    assert_eq!(page_amounts(&env, &ctx, 2, 2), vec![&env, 300, 400]);
+    assert_eq!(page_amounts(&env, &ctx, 4, 2), vec![&env, 500]);
+    assert!(page_amounts(&env, &ctx, 6, 2).is_empty());
+    assert_eq!(page_amounts(&env, &ctx, 0, u32::MAX).len(), 5);
+}
+
+#[test]
+fn test_streams_page_skips_purged_streams() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+
+    create_streams(&ctx, 3);
+    ctx.client.cancel(&1, &ctx.sender);
+    env.ledger()
+        .with_mut(|li| li.timestamp = DEFAULT_PURGE_RETENTION);
+    ctx.client.purge_cancelled(&admin, &1);
+
+    assert_eq!(page_amounts(&env, &ctx, 0, 3), vec![&env, 100, 300]);
+    assert_eq!(ctx.client.get_stream_count(), 3);
+}
+
+#[test]
+fn test_stream_records_creation_ledger_and_time() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 12;
+        li.timestamp = 50;
+    });
+    let stream_id = create_stream(&ctx);
+
+    // Later activity doesn't move the creation context
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 20;
+        li.timestamp = 150;
+    });
+    ctx.client.withdraw(&stream_id, &ctx.receiver);
+
+    let stream = ctx.client.get_stream(&stream_id);
+    assert_eq!(stream.created_ledger, 12);
+    assert_eq!(stream.created_at, 50);
+    assert_eq!(stream.last_activity, 150);
+}
+
+#[test]
+fn test_monthly_stream_vests_one_month_per_thirty_days() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    let stream_id =
+        ctx.client
+            .create_stream_monthly(&ctx.sender, &ctx.receiver, &ctx.token_id, &3000, &3);
+
+    let stream = ctx.client.get_stream(&stream_id);
+    assert_eq!(stream.total_amount, 9000);
+    assert_eq!(stream.start_time, 1000);
+    assert_eq!(stream.end_time, 1000 + 3 * SECONDS_PER_MONTH);
+
+    for month in 1..=3 {
+        env.ledger()
+            .with_mut(|li| li.timestamp = 1000 + month * SECONDS_PER_MONTH);
+        assert_eq!(ctx.client.withdraw(&stream_id, &ctx.receiver), 3000);
+    }
+}
+
+#[test]
+fn test_monthly_stream_rejects_zero_months() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let result =
+        ctx.client
+            .try_create_stream_monthly(&ctx.sender, &ctx.receiver, &ctx.token_id, &3000, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+}
+
+fn create_backdated(ctx: &CreationContext, start_time: u64) -> Result<u64, Error> {
+    ctx.client
+        .try_create_stream(
+            &ctx.sender,
+            &ctx.receiver,
+            &ctx.token_id,
+            &1000,
+            &start_time,
+            &(start_time + 10_000),
+            &start_time,
+            &CurveType::Linear,
+            &false,
+        )
+        .map(|id| id.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_backdating_within_window_vests_immediately() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+    ctx.client.set_backdating_policy(&admin, &5_000, &false);
+
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+    let stream_id = create_backdated(&ctx, 5_000).unwrap();
+    assert_eq!(ctx.client.get_withdrawable(&stream_id), 500);
+
+    assert_eq!(create_backdated(&ctx, 4_999), Err(Error::InvalidTimeRange));
+}
+
+#[test]
+fn test_backdating_beyond_window_needs_explicit_allow() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+    assert_eq!(
+        ctx.client.get_backdating_policy(),
+        (DEFAULT_MAX_BACKDATING, false)
+    );
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = DEFAULT_MAX_BACKDATING + 1);
+    assert_eq!(create_backdated(&ctx, 0), Err(Error::InvalidTimeRange));
+
+    let result = ctx.client.try_set_backdating_policy(&ctx.sender, &0, &true);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client
+        .set_backdating_policy(&admin, &DEFAULT_MAX_BACKDATING, &true);
+    assert!(create_backdated(&ctx, 0).is_ok());
+}
+
+#[test]
+fn test_stream_funded_by_separate_account() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let token = TokenClient::new(&env, &ctx.token_id);
+
+    let custodian = Address::generate(&env);
+    StellarAssetClient::new(&env, &ctx.token_id).mint(&custodian, &5000);
+
+    let stream_id = ctx.client.create_stream_with_funder(
+        &ctx.sender,
+        &custodian,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(token.balance(&custodian), 4000);
+    assert_eq!(token.balance(&ctx.sender), 10000);
+    assert_eq!(ctx.client.get_stream(&stream_id).sender, ctx.sender);
+
+    // The funder has no control over the stream; the sender does
+    let result = ctx.client.try_pause_stream(&stream_id, &custodian);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    let result = ctx.client.try_cancel(&stream_id, &custodian);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    env.ledger().with_mut(|li| li.timestamp = 40);
+    ctx.client.pause_stream(&stream_id, &ctx.sender);
+    ctx.client.unpause_stream(&stream_id, &ctx.sender);
+    ctx.client.cancel(&stream_id, &ctx.sender);
+    assert_eq!(token.balance(&ctx.receiver), 400);
+    assert_eq!(token.balance(&ctx.sender), 10600);
+}
@@ -0,0 +1,467 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::MAX_BATCH;
+use crate::types::{CurveParams, CurveType, Milestone, MilestoneStreamParams};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+fn setup(env: &Env) -> (StellarStreamContractClient<'_>, Address, Address, Address) {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    (client, sender, receiver, token_id)
+}
+
+fn milestones(env: &Env, points: &[(u64, u32)]) -> Vec<Milestone> {
+    let mut milestones = Vec::new(env);
+    for (timestamp, percentage) in points {
+        milestones.push_back(Milestone {
+            timestamp: *timestamp,
+            percentage: *percentage,
+        });
+    }
+    milestones
+}
+
+#[test]
+fn test_piecewise_linear_interpolates_between_milestones() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    // 30% at 25% of the time, 90% at 75% of the time
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &milestones(&env, &[(100, 30), (300, 90)]),
+        &CurveType::PiecewiseLinear,
+        &false,
+        &None,
+    );
+
+    let schedule = client.get_unlock_schedule(&stream_id, &9);
+    let expected = [0, 150, 300, 450, 600, 750, 900, 950, 1000];
+    for (i, amount) in expected.iter().enumerate() {
+        let (_, unlocked) = schedule.get(i as u32).unwrap();
+        assert_eq!(unlocked, *amount);
+    }
+}
+
+#[test]
+fn test_piecewise_linear_rejects_unordered_milestones() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let result = client.try_create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &milestones(&env, &[(300, 30), (100, 90)]),
+        &CurveType::PiecewiseLinear,
+        &false,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+}
+
+#[test]
+fn test_validate_milestones_accepts_valid_schedule() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+
+    let schedule = milestones(&env, &[(100, 30), (300, 90), (350, 100)]);
+    assert_eq!(client.try_validate_milestones(&1000, &schedule), Ok(Ok(())));
+    assert_eq!(
+        client.try_validate_milestones(&1000, &Vec::new(&env)),
+        Ok(Ok(()))
+    );
+}
+
+#[test]
+fn test_validate_milestones_matches_creation_errors() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let mut too_many = Vec::new(&env);
+    for i in 0..=MAX_BATCH {
+        too_many.push_back(Milestone {
+            timestamp: 1 + i as u64,
+            percentage: 1,
+        });
+    }
+    let cases = [
+        (
+            milestones(&env, &[(300, 30), (100, 90)]),
+            Error::InvalidTimeRange,
+        ),
+        (
+            milestones(&env, &[(100, 30), (100, 90)]),
+            Error::InvalidTimeRange,
+        ),
+        (
+            milestones(&env, &[(100, 90), (300, 30)]),
+            Error::InvalidAmount,
+        ),
+        (
+            milestones(&env, &[(100, 30), (300, 101)]),
+            Error::InvalidAmount,
+        ),
+        (too_many, Error::BatchTooLarge),
+    ];
+
+    for (schedule, error) in cases {
+        let result = client.try_validate_milestones(&1000, &schedule);
+        assert_eq!(result, Err(Ok(error)));
+
+        let result = client.try_create_stream_with_milestones(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &0,
+            &400,
+            &schedule,
+            &CurveType::PiecewiseLinear,
+            &false,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(error)));
+    }
+
+    let valid = milestones(&env, &[(100, 30)]);
+    let result = client.try_validate_milestones(&0, &valid);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_logarithmic_stream_front_loads_unlocks() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &0,
+        &CurveType::Logarithmic,
+        &false,
+    );
+
+    // Half the grant is claimable after a quarter of the duration
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 500);
+
+    env.ledger().with_mut(|li| li.timestamp = 400);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 500);
+}
+
+#[test]
+fn test_cliff_blocks_withdrawal() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 99);
+    let result = client.try_withdraw(&stream_id, &receiver);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+}
+
+#[test]
+fn test_cliff_defaults_to_start_time() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &50,
+        &150,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &None,
+    );
+    assert_eq!(client.get_stream(&stream_id).cliff_time, 50);
+
+    env.ledger().with_mut(|li| li.timestamp = 60);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 100);
+}
+
+#[test]
+fn test_milestone_stream_with_cliff() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_milestone_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &MilestoneStreamParams {
+            start_time: 0,
+            end_time: 400,
+            cliff_time: 150,
+            milestones: milestones(&env, &[(100, 30), (300, 90)]),
+            curve_type: CurveType::PiecewiseLinear,
+            is_soulbound: false,
+            vault_address: None,
+        },
+    );
+    assert_eq!(client.get_stream(&stream_id).cliff_time, 150);
+
+    env.ledger().with_mut(|li| li.timestamp = 149);
+    let result = client.try_withdraw(&stream_id, &receiver);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 450);
+}
+
+#[test]
+fn test_cliff_unlocks_at_cliff_time() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &100,
+        &CurveType::Exponential,
+        &false,
+    );
+
+    // Everything vested up to the cliff becomes claimable at once
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert!(client.withdraw(&stream_id, &receiver) > 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 400);
+    client.withdraw(&stream_id, &receiver);
+    assert_eq!(client.get_stream(&stream_id).withdrawn_amount, 1000);
+}
+
+#[test]
+fn test_cliff_outside_stream_rejected() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    for cliff in [50, 500] {
+        let result = client.try_create_stream(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &400,
+            &cliff,
+            &CurveType::Linear,
+            &false,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+    }
+}
+
+#[test]
+fn test_step_stream_releases_whole_tranches() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    // Thirds every 100 seconds; the last tranche picks up the rounding
+    let stream_id = client.create_step_stream(&sender, &receiver, &token_id, &1000, &0, &100, &3);
+    assert_eq!(client.get_stream(&stream_id).end_time, 300);
+
+    let schedule = client.get_unlock_schedule(&stream_id, &7);
+    let expected = [0, 0, 333, 333, 666, 666, 1000];
+    for (i, amount) in expected.iter().enumerate() {
+        let (_, unlocked) = schedule.get(i as u32).unwrap();
+        assert_eq!(unlocked, *amount);
+    }
+
+    env.ledger().with_mut(|li| li.timestamp = 199);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 333);
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 667);
+}
+
+#[test]
+fn test_step_stream_pause_delays_next_tranche() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_step_stream(&sender, &receiver, &token_id, &1000, &0, &100, &4);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.pause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.unpause_stream(&stream_id, &sender);
+
+    // 50 seconds paused, so the second tranche lands at 250 instead of 200
+    env.ledger().with_mut(|li| li.timestamp = 249);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 250);
+    env.ledger().with_mut(|li| li.timestamp = 250);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 250);
+}
+
+#[test]
+fn test_step_curve_requires_step_stream() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let result = client.try_create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &0,
+        &CurveType::Step,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::StepConfigMissing)));
+
+    let result = client.try_create_step_stream(&sender, &receiver, &token_id, &1000, &0, &0, &4);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+}
+
+#[test]
+fn test_get_withdrawable_matches_withdraw_along_curves() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let piecewise = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &milestones(&env, &[(100, 30), (300, 90)]),
+        &CurveType::PiecewiseLinear,
+        &false,
+        &None,
+    );
+    let exponential = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &50,
+        &CurveType::Exponential,
+        &false,
+    );
+    let step = client.create_step_stream(&sender, &receiver, &token_id, &1000, &0, &100, &4);
+
+    for timestamp in [30, 75, 150, 260, 399, 450] {
+        env.ledger().with_mut(|li| li.timestamp = timestamp);
+        for stream_id in [piecewise, exponential, step] {
+            let quoted = client.get_withdrawable(&stream_id);
+            let paid = if quoted > 0 {
+                client.withdraw(&stream_id, &receiver)
+            } else {
+                0
+            };
+            assert_eq!(quoted, paid);
+        }
+    }
+}
+
+/// Off-chain replica of the contract's linear unlock
+fn linear_unlocked(params: &CurveParams, t: u64) -> i128 {
+    if t <= params.start_time || t < params.cliff_time {
+        return 0;
+    }
+    let ongoing = params
+        .paused_since
+        .map_or(0, |since| t.saturating_sub(since));
+    let paused = params.total_paused_duration + ongoing;
+    let duration = params.end_time - params.start_time;
+    let elapsed = (t - params.start_time).saturating_sub(paused).min(duration);
+    params.total_amount * elapsed as i128 / duration as i128
+}
+
+#[test]
+fn test_curve_params_reproduce_unlock_schedule() {
+    let env = Env::default();
+    let (client, sender, receiver, token_id) = setup(&env);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &400,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    // One finished pause and one still running
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    client.pause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 110);
+    client.unpause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 250);
+    client.pause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 300);
+
+    let params = client.get_curve_params(&stream_id);
+    assert_eq!(params.curve_type, CurveType::Linear);
+    assert_eq!(params.cliff_time, 100);
+    assert_eq!(params.total_paused_duration, 60);
+    assert_eq!(params.paused_since, Some(250));
+
+    let schedule = client.get_unlock_schedule(&stream_id, &9);
+    for (timestamp, unlocked) in schedule.iter() {
+        assert_eq!(linear_unlocked(&params, timestamp), unlocked);
+    }
+}
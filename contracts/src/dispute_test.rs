@@ -6,6 +6,8 @@ use soroban_sdk::{
     Address, Env, Vec,
 };
 
+use crate::errors::Error;
+
 fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
     let contract_id = env
         .register_stellar_asset_contract_v2(admin.clone())
@@ -53,7 +55,7 @@ fn test_freeze_stream() {
 }
 
 #[test]
-#[should_panic(expected = "Error(Contract, #22)")]
+#[should_panic(expected = "Error(Contract, #23)")]
 fn test_withdraw_from_frozen_stream_fails() {
     let env = Env::default();
     env.mock_all_auths();
@@ -135,7 +137,8 @@ fn test_resolve_dispute() {
     );
 
     client.set_arbiter(&stream_id, &sender, &arbiter);
-    client.resolve_dispute(&stream_id, &arbiter, &6000);
+    client.raise_dispute(&stream_id, &receiver);
+    client.resolve_dispute(&stream_id, &arbiter, &600, &400);
 
     let stream = client.get_stream(&stream_id);
     assert!(stream.cancelled);
@@ -182,3 +185,419 @@ fn test_non_arbiter_cannot_freeze() {
     client.set_arbiter(&stream_id, &sender, &arbiter);
     client.freeze_stream(&stream_id, &non_arbiter);
 }
+
+#[test]
+fn test_default_arbiter_applies_to_new_streams() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+
+    let (token_address, token_client) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_address);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    client.initialize(&admin);
+    client.set_default_arbiter(&admin, &arbiter);
+    token_admin_client.mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+    assert_eq!(client.get_stream(&stream_id).arbiter, Some(arbiter.clone()));
+
+    client.raise_dispute(&stream_id, &sender);
+    assert!(client.get_stream(&stream_id).is_frozen);
+
+    client.resolve_dispute(&stream_id, &arbiter, &250, &750);
+    assert_eq!(token_client.balance(&receiver), 250);
+    assert_eq!(token_client.balance(&sender), 750);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_per_stream_arbiter_overrides_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let default_arbiter = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+
+    let (token_address, _token_client) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_address);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    client.initialize(&admin);
+    client.set_default_arbiter(&admin, &default_arbiter);
+    token_admin_client.mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+
+    client.set_arbiter(&stream_id, &sender, &arbiter);
+    client.freeze_stream(&stream_id, &default_arbiter);
+}
+
+struct DisputeContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    token: TokenClient<'a>,
+    sender: Address,
+    receiver: Address,
+    arbiter: Address,
+    stream_id: u64,
+}
+
+fn setup_dispute(env: &Env) -> DisputeContext<'_> {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let admin = Address::generate(env);
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let arbiter = Address::generate(env);
+
+    let (token_address, token) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_address).mint(&sender, &1000);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+    client.set_arbiter(&stream_id, &sender, &arbiter);
+
+    DisputeContext {
+        client,
+        token,
+        sender,
+        receiver,
+        arbiter,
+        stream_id,
+    }
+}
+
+#[test]
+fn test_raise_and_resolve_dispute() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 20);
+    ctx.client.withdraw(&ctx.stream_id, &ctx.receiver);
+
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.receiver);
+    assert!(ctx.client.get_stream(&ctx.stream_id).is_frozen);
+
+    ctx.client
+        .resolve_dispute(&ctx.stream_id, &ctx.arbiter, &500, &300);
+
+    let stream = ctx.client.get_stream(&ctx.stream_id);
+    assert!(stream.cancelled);
+    assert!(!stream.is_frozen);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 700);
+    assert_eq!(ctx.token.balance(&ctx.sender), 300);
+}
+
+#[test]
+fn test_raise_dispute_requires_party_and_arbiter() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_raise_dispute(&ctx.stream_id, &outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    // Streams without an arbiter cannot be disputed
+    StellarAssetClient::new(&env, &ctx.token.address).mint(&ctx.sender, &1000);
+    let other = ctx.client.create_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token.address,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+    let result = ctx.client.try_raise_dispute(&other, &ctx.sender);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_resolve_dispute_rejects_non_arbiter() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.sender);
+
+    let result = ctx
+        .client
+        .try_resolve_dispute(&ctx.stream_id, &ctx.sender, &1000, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(ctx.client.get_stream(&ctx.stream_id).is_frozen);
+}
+
+#[test]
+fn test_resolve_dispute_requires_raised_dispute() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    let result = ctx
+        .client
+        .try_resolve_dispute(&ctx.stream_id, &ctx.arbiter, &1000, &0);
+    assert_eq!(result, Err(Ok(Error::NoPendingChange)));
+    assert!(!ctx.client.get_stream(&ctx.stream_id).cancelled);
+}
+
+#[test]
+fn test_freeze_alone_does_not_open_a_ruling() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    ctx.client.freeze_stream(&ctx.stream_id, &ctx.arbiter);
+    let result = ctx
+        .client
+        .try_resolve_dispute(&ctx.stream_id, &ctx.arbiter, &0, &1000);
+    assert_eq!(result, Err(Ok(Error::NoPendingChange)));
+    let result = ctx
+        .client
+        .try_rule_on_dispute(&ctx.stream_id, &ctx.arbiter, &false);
+    assert_eq!(result, Err(Ok(Error::NoPendingChange)));
+
+    // A ruling consumes the dispute, so it cannot be reused later
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.receiver);
+    let result = ctx.client.try_raise_dispute(&ctx.stream_id, &ctx.sender);
+    assert_eq!(result, Err(Ok(Error::StreamFrozen)));
+    ctx.client
+        .rule_on_dispute(&ctx.stream_id, &ctx.arbiter, &true);
+    ctx.client.freeze_stream(&ctx.stream_id, &ctx.arbiter);
+    let result = ctx
+        .client
+        .try_resolve_dispute(&ctx.stream_id, &ctx.arbiter, &0, &1000);
+    assert_eq!(result, Err(Ok(Error::NoPendingChange)));
+    assert!(!ctx.client.get_stream(&ctx.stream_id).cancelled);
+}
+
+#[test]
+fn test_resolve_dispute_rejects_mismatched_split() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.sender);
+
+    let result = ctx
+        .client
+        .try_resolve_dispute(&ctx.stream_id, &ctx.arbiter, &600, &300);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    let result = ctx
+        .client
+        .try_resolve_dispute(&ctx.stream_id, &ctx.arbiter, &1100, &-100);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_rule_on_dispute_lets_stream_continue() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 20);
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.sender);
+    ctx.client
+        .rule_on_dispute(&ctx.stream_id, &ctx.arbiter, &true);
+
+    let stream = ctx.client.get_stream(&ctx.stream_id);
+    assert!(!stream.is_frozen);
+    assert!(!stream.cancelled);
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(ctx.client.withdraw(&ctx.stream_id, &ctx.receiver), 500);
+}
+
+#[test]
+fn test_rule_on_dispute_settles_like_cancel() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 40);
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.receiver);
+    ctx.client
+        .rule_on_dispute(&ctx.stream_id, &ctx.arbiter, &false);
+
+    assert!(ctx.client.get_stream(&ctx.stream_id).cancelled);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 400);
+    assert_eq!(ctx.token.balance(&ctx.sender), 600);
+}
+
+#[test]
+fn test_rule_on_dispute_requires_arbiter_and_dispute() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    let result = ctx
+        .client
+        .try_rule_on_dispute(&ctx.stream_id, &ctx.arbiter, &true);
+    assert_eq!(result, Err(Ok(Error::NoPendingChange)));
+
+    ctx.client.raise_dispute(&ctx.stream_id, &ctx.sender);
+    let result = ctx
+        .client
+        .try_rule_on_dispute(&ctx.stream_id, &ctx.receiver, &true);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(ctx.client.get_stream(&ctx.stream_id).is_frozen);
+}
+
+#[test]
+fn test_create_stream_with_arbiter() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+
+    let (token_address, token_client) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_address).mint(&sender, &2000);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let stream_id = client.create_stream_with_arbiter(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &0,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+        &Some(arbiter.clone()),
+        &false,
+    );
+    assert_eq!(client.get_stream(&stream_id).arbiter, Some(arbiter.clone()));
+
+    env.ledger().with_mut(|li| li.timestamp = 30);
+    client.raise_dispute(&stream_id, &receiver);
+    client.resolve_dispute(&stream_id, &arbiter, &300, &700);
+
+    assert!(client.get_stream(&stream_id).cancelled);
+    assert_eq!(token_client.balance(&receiver), 300);
+    assert_eq!(token_client.balance(&sender), 1700);
+
+    // Without an arbiter the stream can't be disputed
+    let other = client.create_stream_with_arbiter(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &30,
+        &130,
+        &crate::types::CurveType::Linear,
+        &false,
+        &None,
+        &false,
+    );
+    assert_eq!(client.get_stream(&other).arbiter, None);
+    let result = client.try_raise_dispute(&other, &receiver);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(!client.get_stream(&other).is_frozen);
+}
+
+#[test]
+fn test_create_stream_with_arbiter_rejects_party_as_arbiter() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+
+    let (token_address, _token_client) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_address).mint(&sender, &1000);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    for arbiter in [&sender, &receiver] {
+        let result = client.try_create_stream_with_arbiter(
+            &sender,
+            &receiver,
+            &token_address,
+            &1000,
+            &0,
+            &100,
+            &crate::types::CurveType::Linear,
+            &false,
+            &Some(arbiter.clone()),
+            &false,
+        );
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    }
+}
+
+#[test]
+fn test_set_arbiter_needs_receiver_consent() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    let arbiter = Address::generate(&env);
+    ctx.client
+        .set_arbiter(&ctx.stream_id, &ctx.sender, &arbiter);
+
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == ctx.sender));
+    assert!(auths.iter().any(|(addr, _)| *addr == ctx.receiver));
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).arbiter, Some(arbiter));
+}
+
+#[test]
+fn test_set_arbiter_rejects_party_as_arbiter() {
+    let env = Env::default();
+    let ctx = setup_dispute(&env);
+
+    for arbiter in [&ctx.sender, &ctx.receiver] {
+        let result = ctx
+            .client
+            .try_set_arbiter(&ctx.stream_id, &ctx.sender, arbiter);
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    }
+    assert_eq!(
+        ctx.client.get_stream(&ctx.stream_id).arbiter,
+        Some(ctx.arbiter)
+    );
+}
@@ -0,0 +1,417 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::EMERGENCY_DRAIN_DELAY;
+use crate::types::{CurveType, Role};
+use crate::vault_interest_test::{AppreciatingVault, AppreciatingVaultClient};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct DrainContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    contract_id: Address,
+    token: TokenClient<'a>,
+    token_id: Address,
+    sender: Address,
+    admin: Address,
+    guardian: Address,
+}
+
+fn setup(env: &Env) -> DrainContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let guardian = Address::generate(env);
+    client.initialize(&admin);
+    client.grant_role(&admin, &guardian, &Role::Guardian);
+
+    let sender = Address::generate(env);
+    let (token_id, token) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    DrainContext {
+        client,
+        contract_id,
+        token,
+        token_id,
+        sender,
+        admin,
+        guardian,
+    }
+}
+
+#[test]
+fn test_emergency_drain_settles_active_streams() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let receiver_a = Address::generate(&env);
+    let receiver_b = Address::generate(&env);
+    let end = 2 * EMERGENCY_DRAIN_DELAY;
+    ctx.client.create_stream(
+        &ctx.sender,
+        &receiver_a,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &end,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+    ctx.client.create_stream(
+        &ctx.sender,
+        &receiver_b,
+        &ctx.token_id,
+        &2000,
+        &0,
+        &(2 * end),
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+
+    let executable_at = ctx.client.schedule_emergency_drain(&ctx.guardian);
+    let result = ctx.client.try_emergency_drain(&ctx.guardian);
+    assert_eq!(result, Err(Ok(Error::TimelockNotElapsed)));
+
+    // Stream A is half vested, stream B a quarter vested
+    env.ledger().with_mut(|li| li.timestamp = executable_at);
+    assert!(ctx.client.emergency_drain(&ctx.guardian));
+
+    assert_eq!(ctx.token.balance(&receiver_a), 500);
+    assert_eq!(ctx.token.balance(&receiver_b), 500);
+    assert_eq!(ctx.token.balance(&ctx.sender), 7000 + 500 + 1500);
+    assert_eq!(ctx.token.balance(&ctx.contract_id), 0);
+    assert!(ctx.client.is_disabled());
+
+    let result = ctx.client.try_create_stream(
+        &ctx.sender,
+        &receiver_a,
+        &ctx.token_id,
+        &1000,
+        &executable_at,
+        &(executable_at + 100),
+        &executable_at,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::ContractDisabled)));
+}
+
+fn create_vault_stream(env: &Env, ctx: &DrainContext, receiver: &Address, failing: bool) -> u64 {
+    let vault = env.register(AppreciatingVault, ());
+    let vault_client = AppreciatingVaultClient::new(env, &vault);
+    vault_client.init(&ctx.token_id);
+    vault_client.set_failing(&failing);
+    ctx.client.approve_vault(&ctx.admin, &vault);
+
+    ctx.client.create_stream_with_milestones(
+        &ctx.sender,
+        receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &(2 * EMERGENCY_DRAIN_DELAY),
+        &Vec::new(env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault),
+    )
+}
+
+#[test]
+fn test_emergency_drain_settles_vault_streams_and_skips_failures() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let receiver_a = Address::generate(&env);
+    let receiver_b = Address::generate(&env);
+    create_vault_stream(&env, &ctx, &receiver_a, false);
+    let stuck_id = create_vault_stream(&env, &ctx, &receiver_b, true);
+
+    let executable_at = ctx.client.schedule_emergency_drain(&ctx.guardian);
+    env.ledger().with_mut(|li| li.timestamp = executable_at);
+    assert!(ctx.client.emergency_drain(&ctx.guardian));
+    assert!(ctx.client.is_disabled());
+
+    // The working vault pays out the half-vested split
+    assert_eq!(ctx.token.balance(&receiver_a), 500);
+    assert_eq!(ctx.token.balance(&ctx.sender), 8000 + 500);
+
+    // The stream whose vault won't redeem is left open and recorded
+    assert_eq!(ctx.client.get_drain_skipped(), vec![&env, stuck_id]);
+    let stuck = ctx.client.get_stream(&stuck_id);
+    assert!(!stuck.cancelled);
+    assert_eq!(stuck.withdrawn_amount, 0);
+    assert_eq!(ctx.token.balance(&receiver_b), 0);
+}
+
+#[test]
+fn test_emergency_drain_requires_guardian_and_schedule() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let result = ctx.client.try_emergency_drain(&ctx.guardian);
+    assert_eq!(result, Err(Ok(Error::DrainNotScheduled)));
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_schedule_emergency_drain(&outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client.schedule_emergency_drain(&ctx.guardian);
+    ctx.client.cancel_emergency_drain(&ctx.guardian);
+    assert_eq!(ctx.client.get_drain_scheduled_at(), None);
+}
+
+#[test]
+fn test_withdraw_only_blocks_new_funds() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let receiver = Address::generate(&env);
+    let stream_id = ctx.client.create_stream(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+
+    ctx.client.set_withdraw_only(&ctx.guardian, &true);
+    assert!(ctx.client.is_withdraw_only());
+
+    let result = ctx.client.try_create_stream(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::WithdrawOnly)));
+
+    let result =
+        ctx.client
+            .try_create_stream_monthly(&ctx.sender, &receiver, &ctx.token_id, &100, &3);
+    assert_eq!(result, Err(Ok(Error::WithdrawOnly)));
+
+    let result = ctx.client.try_top_up_stream(&stream_id, &ctx.sender, &500);
+    assert_eq!(result, Err(Ok(Error::WithdrawOnly)));
+
+    let result = ctx.client.try_create_proposal(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &1,
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+    assert_eq!(result, Err(Ok(Error::WithdrawOnly)));
+}
+
+#[test]
+fn test_withdraw_only_allows_withdraw_and_cancel() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let receiver = Address::generate(&env);
+    let stream_id = ctx.client.create_stream(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+
+    ctx.client.set_withdraw_only(&ctx.guardian, &true);
+
+    env.ledger().with_mut(|li| li.timestamp = 40);
+    assert_eq!(ctx.client.withdraw(&stream_id, &receiver), 400);
+
+    env.ledger().with_mut(|li| li.timestamp = 60);
+    ctx.client.cancel(&stream_id, &ctx.sender);
+    assert_eq!(ctx.token.balance(&receiver), 600);
+    assert_eq!(ctx.token.balance(&ctx.sender), 9400);
+    assert_eq!(ctx.token.balance(&ctx.contract_id), 0);
+
+    // Lifting the mode lets new streams through again
+    ctx.client.set_withdraw_only(&ctx.guardian, &false);
+    assert!(!ctx.client.is_withdraw_only());
+    ctx.client.create_stream(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &60,
+        &160,
+        &60,
+        &CurveType::Linear,
+        &false,
+    );
+}
+
+#[test]
+fn test_withdraw_only_requires_guardian() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_set_withdraw_only(&outsider, &true);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(!ctx.client.is_withdraw_only());
+}
+
+#[test]
+fn test_global_pause_blocks_stream_operations() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let receiver = Address::generate(&env);
+    let stream_id = ctx.client.create_stream(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+
+    ctx.client.set_global_pause(&ctx.guardian, &true);
+    assert!(ctx.client.is_globally_paused());
+
+    env.ledger().with_mut(|li| li.timestamp = 40);
+    let result = ctx.client.try_withdraw(&stream_id, &receiver);
+    assert_eq!(result, Err(Ok(Error::StreamPaused)));
+    let result = ctx.client.try_cancel(&stream_id, &ctx.sender);
+    assert_eq!(result, Err(Ok(Error::StreamPaused)));
+    let result = ctx.client.try_create_stream(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &40,
+        &140,
+        &40,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::StreamPaused)));
+
+    // Unpausing releases everything vested in the meantime
+    ctx.client.set_global_pause(&ctx.guardian, &false);
+    assert!(!ctx.client.is_globally_paused());
+    assert_eq!(ctx.client.withdraw(&stream_id, &receiver), 400);
+    assert_eq!(ctx.token.balance(&receiver), 400);
+}
+
+#[test]
+fn test_global_pause_requires_guardian() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_set_global_pause(&outsider, &true);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(!ctx.client.is_globally_paused());
+}
+
+#[test]
+fn test_operational_state_reflects_pause_and_freeze() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    assert_eq!(ctx.client.get_operational_state(), (false, false));
+
+    ctx.client.set_global_pause(&ctx.guardian, &true);
+    assert_eq!(ctx.client.get_operational_state(), (true, false));
+
+    ctx.client.set_withdraw_only(&ctx.guardian, &true);
+    assert_eq!(ctx.client.get_operational_state(), (true, true));
+
+    ctx.client.set_global_pause(&ctx.guardian, &false);
+    assert_eq!(ctx.client.get_operational_state(), (false, true));
+
+    ctx.client.set_withdraw_only(&ctx.guardian, &false);
+    assert_eq!(ctx.client.get_operational_state(), (false, false));
+
+    // A drained contract stays frozen
+    let executable_at = ctx.client.schedule_emergency_drain(&ctx.guardian);
+    env.ledger().with_mut(|li| li.timestamp = executable_at);
+    ctx.client.emergency_drain(&ctx.guardian);
+    assert_eq!(ctx.client.get_operational_state(), (false, true));
+}
+
+#[test]
+fn test_stream_proposal_cannot_execute_while_not_accepting_funds() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let receiver = Address::generate(&env);
+    let approver = Address::generate(&env);
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &receiver,
+        &ctx.token_id,
+        &1000,
+        &0,
+        &100,
+        &1,
+        &(10 * EMERGENCY_DRAIN_DELAY),
+        &true,
+        &Vec::new(&env),
+    );
+
+    ctx.client.set_withdraw_only(&ctx.guardian, &true);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &approver);
+    assert_eq!(result, Err(Ok(Error::WithdrawOnly)));
+    ctx.client.set_withdraw_only(&ctx.guardian, &false);
+
+    ctx.client.set_global_pause(&ctx.guardian, &true);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &approver);
+    assert_eq!(result, Err(Ok(Error::StreamPaused)));
+    ctx.client.set_global_pause(&ctx.guardian, &false);
+
+    let executable_at = ctx.client.schedule_emergency_drain(&ctx.guardian);
+    env.ledger().with_mut(|li| li.timestamp = executable_at);
+    ctx.client.emergency_drain(&ctx.guardian);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &approver);
+    assert_eq!(result, Err(Ok(Error::ContractDisabled)));
+
+    // The escrow is still recoverable
+    ctx.client.cancel_proposal(&proposal_id, &ctx.sender);
+    assert_eq!(ctx.token.balance(&ctx.sender), 10000);
+    assert_eq!(ctx.client.get_stream_count(), 0);
+}
@@ -27,4 +27,59 @@ pub enum Error {
     StreamIsSoulbound = 21,
     /// Address is restricted by OFAC compliance
     AddressRestricted = 22,
+    /// Stream is frozen pending dispute resolution
+    StreamFrozen = 23,
+    /// Operation is only allowed before any funds are withdrawn
+    StreamAlreadyClaimed = 24,
+    /// Stream has used up its cumulative pause allowance
+    PauseBudgetExceeded = 25,
+    /// Proposal was cancelled by its sender
+    ProposalCancelled = 26,
+    /// Nothing is pending to act on (receiver change to approve, dispute to rule on)
+    NoPendingChange = 27,
+    /// Too many distinct tokens are already tracked by the contract
+    TooManyTokens = 28,
+    /// Action cannot be proposed through this entry point
+    InvalidProposalAction = 29,
+    /// Deposit would push a vault past its principal cap
+    VaultCapExceeded = 30,
+    /// Receiver already has the maximum number of active streams
+    ReceiverStreamLimitReached = 31,
+    /// Proposal already holds the maximum number of approvals
+    ApproverLimitReached = 32,
+    /// No emergency drain has been scheduled
+    DrainNotScheduled = 33,
+    /// Timelock (emergency drain, fallback inactivity window) has not elapsed yet
+    TimelockNotElapsed = 34,
+    /// Contract was shut down by an emergency drain
+    ContractDisabled = 35,
+    /// Batch input exceeds MAX_BATCH items
+    BatchTooLarge = 36,
+    /// Operation requires a cancelled stream
+    StreamNotCancelled = 37,
+    /// Cancelled stream is still within its retention period
+    RetentionNotElapsed = 38,
+    /// Stream is not stuck (stuck-funds reclaim, redirect of a receiver that
+    /// still accepts transfers)
+    StreamNotStuck = 39,
+    /// Token is not on the allowlist
+    TokenNotAllowed = 40,
+    /// Token is on the deny-list
+    TokenBlocked = 41,
+    /// Activity for this token is paused
+    TokenPaused = 42,
+    /// Router is not on the approved settlement router list
+    RouterNotApproved = 43,
+    /// Settlement swap failed or returned less than the minimum
+    SwapFailed = 44,
+    /// Step curves need a StepConfig and can only come from create_step_stream
+    StepConfigMissing = 45,
+    /// Stream can't change receiver until its transfer lock expires
+    TransferLocked = 46,
+    /// Contract is in withdraw-only mode; no new funds are accepted
+    WithdrawOnly = 47,
+    /// Condition oracle does not permit this cancellation
+    ConditionNotMet = 48,
+    /// Stream has no auto-cancel set, was withdrawn from, or is still in grace
+    AutoSettleUnavailable = 49,
 }
@@ -0,0 +1,199 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env, Symbol, TryFromVal, Vec,
+};
+
+use crate::errors::Error;
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct FeeContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    token: TokenClient<'a>,
+    token_id: Address,
+    admin: Address,
+    sender: Address,
+    receiver: Address,
+}
+
+fn setup(env: &Env) -> FeeContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let (token_id, token) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    FeeContext {
+        client,
+        token,
+        token_id,
+        admin,
+        sender,
+        receiver,
+    }
+}
+
+fn create_stream(ctx: &FeeContext) -> u64 {
+    ctx.client.create_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    )
+}
+
+fn fee_event_count(env: &Env) -> usize {
+    env.events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| {
+            Symbol::try_from_val(env, &topics.get(0).unwrap()) == Ok(symbol_short!("fee"))
+        })
+        .count()
+}
+
+#[test]
+fn test_unconfigured_fee_deducts_nothing() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    assert!(!ctx.client.is_fee_configured());
+
+    let stream_id = create_stream(&ctx);
+    assert_eq!(fee_event_count(&env), 0);
+    assert_eq!(ctx.client.get_stream(&stream_id).total_amount, 1000);
+}
+
+#[test]
+fn test_configured_zero_fee_emits_event_without_deduction() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let treasury = Address::generate(&env);
+
+    ctx.client.set_fee_config(&ctx.admin, &0, &treasury);
+    assert!(ctx.client.is_fee_configured());
+
+    let stream_id = create_stream(&ctx);
+    assert_eq!(fee_event_count(&env), 1);
+    assert_eq!(ctx.client.get_stream(&stream_id).total_amount, 1000);
+    assert_eq!(ctx.token.balance(&treasury), 0);
+}
+
+#[test]
+fn test_configured_fee_is_sent_to_treasury() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let treasury = Address::generate(&env);
+
+    // 2.5% fee
+    ctx.client.set_fee_config(&ctx.admin, &250, &treasury);
+
+    let stream_id = create_stream(&ctx);
+    assert_eq!(ctx.client.get_stream(&stream_id).total_amount, 975);
+    assert_eq!(ctx.token.balance(&treasury), 25);
+    assert_eq!(ctx.token.balance(&ctx.sender), 9000);
+}
+
+#[test]
+fn test_only_treasury_manager_can_set_fee() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let treasury = Address::generate(&env);
+
+    let result = ctx.client.try_set_fee_config(&ctx.sender, &100, &treasury);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert!(!ctx.client.is_fee_configured());
+}
+
+#[test]
+fn test_set_fee_bps_and_treasury() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let treasury = Address::generate(&env);
+
+    let result = ctx.client.try_set_fee_bps(&ctx.admin, &100);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+    // A treasury alone configures an explicit 0 bps fee
+    ctx.client.set_treasury(&ctx.admin, &treasury);
+    assert_eq!(ctx.client.get_fee_config().unwrap().fee_bps, 0);
+
+    let result = ctx.client.try_set_fee_bps(&ctx.admin, &1001);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    let result = ctx.client.try_set_fee_bps(&ctx.sender, &100);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    // 10% is the ceiling
+    ctx.client.set_fee_bps(&ctx.admin, &1000);
+    let stream_id = create_stream(&ctx);
+    assert_eq!(ctx.client.get_stream(&stream_id).total_amount, 900);
+    assert_eq!(ctx.token.balance(&treasury), 100);
+
+    // Moving the treasury keeps the rate
+    let new_treasury = Address::generate(&env);
+    ctx.client.set_treasury(&ctx.admin, &new_treasury);
+    create_stream(&ctx);
+    assert_eq!(ctx.token.balance(&new_treasury), 100);
+}
+
+#[test]
+fn test_fee_config_capped_at_max() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let treasury = Address::generate(&env);
+
+    let result = ctx.client.try_set_fee_config(&ctx.admin, &1001, &treasury);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    assert!(!ctx.client.is_fee_configured());
+}
+
+#[test]
+fn test_executed_proposal_pays_fee_to_treasury() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let treasury = Address::generate(&env);
+    ctx.client.set_fee_config(&ctx.admin, &500, &treasury);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &1,
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+    let approver = Address::generate(&env);
+    ctx.client.approve_proposal(&proposal_id, &approver);
+
+    let stream_id = 0;
+    assert_eq!(ctx.client.get_stream(&stream_id).total_amount, 950);
+    assert_eq!(ctx.token.balance(&treasury), 50);
+    assert_eq!(ctx.token.balance(&ctx.sender), 9000);
+}
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use crate::types::{
     InterestDistribution, INTEREST_TO_PROTOCOL, INTEREST_TO_RECEIVER, INTEREST_TO_SENDER,
 };
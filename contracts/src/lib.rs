@@ -7,25 +7,74 @@ mod interest;
 mod math;
 mod oracle;
 mod rbac;
+mod router;
 mod storage;
 mod types;
 mod vault;
 mod voting;
 
+#[cfg(test)]
+mod activity_test;
 #[cfg(test)]
 mod allowlist_test;
 #[cfg(test)]
+mod amend_test;
+#[cfg(test)]
+mod cancel_test;
+#[cfg(test)]
 mod clawback_test;
 #[cfg(test)]
+mod compliance_test;
+#[cfg(test)]
+mod creation_test;
+#[cfg(test)]
+mod curve_test;
+#[cfg(test)]
 mod dispute_test;
 #[cfg(test)]
+mod drain_test;
+#[cfg(test)]
+mod fee_test;
+#[cfg(test)]
+mod loyalty_test;
+#[cfg(test)]
+mod outstanding_test;
+#[cfg(test)]
+mod pause_test;
+#[cfg(test)]
+mod proposal_test;
+#[cfg(test)]
+mod receiver_index_test;
+#[cfg(test)]
+mod receiver_transfer_test;
+#[cfg(test)]
+mod reentrancy_test;
+#[cfg(test)]
+mod schedule_test;
+#[cfg(test)]
+mod settlement_test;
+#[cfg(test)]
 mod soulbound_test;
 #[cfg(test)]
+mod state_test;
+#[cfg(test)]
 mod topup_test;
 #[cfg(test)]
+mod tranche_test;
+#[cfg(test)]
+mod usd_peg_test;
+#[cfg(test)]
+mod validation_test;
+#[cfg(test)]
+mod vault_interest_test;
+#[cfg(test)]
+mod vault_registry_test;
+#[cfg(test)]
 mod vault_test;
 #[cfg(test)]
 mod voting_test;
+#[cfg(test)]
+mod withdraw_test;
 
 // #[cfg(test)]
 // mod interest_test;
@@ -40,20 +89,28 @@ mod voting_test;
 mod ttl_stress_test;
 
 use errors::Error;
-use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, Vec};
-use storage::{PROPOSAL_COUNT, RECEIPT, RESTRICTED_ADDRESSES, STREAM_COUNT};
-use types::{
-    ContributorRequest, CurveType, DataKey, Milestone, ProposalApprovedEvent, ProposalCreatedEvent,
-    ReceiptMetadata, ReceiptTransferredEvent, RequestCreatedEvent, RequestExecutedEvent,
-    RequestKey, RequestStatus, Role, Stream, StreamCancelledEvent, StreamClaimEvent,
-    StreamCreatedEvent, StreamPausedEvent, StreamProposal, StreamReceipt, StreamUnpausedEvent,
-use storage::{PROPOSAL_COUNT, RECEIPT, STREAM_COUNT};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, xdr::ToXdr, Address, BytesN, Env, Symbol, Vec,
+};
+use storage::{
+    BACKDATE, DEFAULT_MAX_BACKDATING, DEFAULT_PURGE_RETENTION, DISPUTE_RAISED, DRAIN_SKIPPED,
+    EMERGENCY_DRAIN_DELAY, FALLBACK, LOYALTY_MIN_IDLE, LOYALTY_PERIOD, MAX_BATCH, MAX_FEE_BPS,
+    MAX_LOYALTY_RATE_BPS, MAX_PAGE_SIZE, MAX_PROPOSAL_APPROVERS, MAX_PROPOSAL_EXTENSION,
+    MAX_TRACKED_TOKENS, MAX_WITHDRAW_PRICES, MIN_UPGRADE_APPROVALS, PROPOSAL_COUNT, RECEIPT,
+    RECEIPT_APPROVAL, RECEIVER_HISTORY, REDIRECT_GRACE, RESTRICTED_ADDRESSES, SECONDS_PER_MONTH,
+    STREAM_COUNT, STUCK_RECLAIM_GRACE, WD_FAIL, WITHDRAW_QUEUE_CURSOR,
+};
 use types::{
-    ClawbackEvent, ContributorRequest, CurveType, DataKey, Milestone, ProposalApprovedEvent,
-    ProposalCreatedEvent, ReceiptMetadata, ReceiptTransferredEvent, RequestCreatedEvent,
-    RequestExecutedEvent, RequestKey, RequestStatus, Role, Stream, StreamCancelledEvent,
-    StreamClaimEvent, StreamCreatedEvent, StreamPausedEvent, StreamProposal, StreamReceipt,
-    StreamUnpausedEvent,
+    ActionProposalCreatedEvent, AddressStatus, BatchOperationEvent, ClawbackEvent,
+    ContributorRequest, CurveParams, CurveType, DataKey, DisputeRaisedEvent, DisputeResolvedEvent,
+    EmergencyDrainEvent, FeeCollectedEvent, FeeConfig, GlobalPauseEvent, Milestone,
+    MilestoneStreamParams, Proposal, ProposalAction, ProposalApprovedEvent, ProposalCreatedEvent,
+    ProposedStream, ReceiptMetadata, ReceiptTransferredEvent, RequestCreatedEvent,
+    RequestExecutedEvent, RequestKey, RequestStatus, Role, SettlementEvent, StepConfig, Stream,
+    StreamActivatedEvent, StreamAutoSettledEvent, StreamCancelledEvent, StreamClaimEvent,
+    StreamCreatedEvent, StreamFrozenEvent, StreamMetadataUpdatedEvent, StreamPurgedEvent,
+    StreamReceipt, StreamReducedEvent, StreamRescheduledEvent, VotingPowerMode, WithdrawOnlyEvent,
+    INTEREST_TO_PROTOCOL, INTEREST_TO_RECEIVER, INTEREST_TO_SENDER,
 };
 
 #[contract]
@@ -61,6 +118,9 @@ pub struct StellarStreamContract;
 
 #[contractimpl]
 impl StellarStreamContract {
+    /// Propose a stream for multisig approval. A non-empty
+    /// `authorized_approvers` limits who may approve, and must hold at least
+    /// `required_approvals` distinct addresses.
     pub fn create_proposal(
         env: Env,
         sender: Address,
@@ -71,43 +131,49 @@ impl StellarStreamContract {
         end_time: u64,
         required_approvals: u32,
         deadline: u64,
+        escrow: bool,
+        authorized_approvers: Vec<Address>,
     ) -> Result<u64, Error> {
         sender.require_auth();
 
-        // Validate time range
-        if start_time >= end_time {
-            return Err(Error::InvalidTimeRange);
-        }
-        if total_amount <= 0 {
-            return Err(Error::InvalidAmount);
+        Self::require_accepting_funds(&env)?;
+        let stream = ProposedStream {
+            receiver: receiver.clone(),
+            token: token.clone(),
+            total_amount,
+            start_time,
+            end_time,
+        };
+        Self::check_proposed_stream(&env, &stream)?;
+        if required_approvals == 0 || required_approvals > MAX_PROPOSAL_APPROVERS {
+            return Err(Error::InvalidApprovalThreshold);
         }
-        if required_approvals == 0 {
+        // With an approver allowlist the threshold must be reachable by it
+        if !authorized_approvers.is_empty()
+            && required_approvals > Self::count_distinct(&authorized_approvers)
+        {
             return Err(Error::InvalidApprovalThreshold);
         }
         if deadline <= env.ledger().timestamp() {
             return Err(Error::ProposalExpired);
         }
 
-        let proposal_id: u64 = env.storage().instance().get(&PROPOSAL_COUNT).unwrap_or(0);
-        let next_id = proposal_id + 1;
+        // In escrow mode the funds are locked now so execution can't fail later
+        if escrow {
+            let token_client = token::Client::new(&env, &token);
+            token_client.transfer(&sender, &env.current_contract_address(), &total_amount);
+        }
 
-        let proposal = StreamProposal {
-            sender: sender.clone(),
-            receiver: receiver.clone(),
-            token: token.clone(),
-            total_amount,
-            start_time,
-            end_time,
-            approvers: Vec::new(&env),
+        let action = ProposalAction::CreateStream(stream);
+        let proposal_id = Self::store_proposal(
+            &env,
+            &sender,
+            action,
             required_approvals,
             deadline,
-            executed: false,
-        };
-
-        env.storage()
-            .instance()
-            .set(&(PROPOSAL_COUNT, proposal_id), &proposal);
-        env.storage().instance().set(&PROPOSAL_COUNT, &next_id);
+            escrow,
+            authorized_approvers,
+        );
 
         // Emit ProposalCreatedEvent
         env.events().publish(
@@ -129,11 +195,125 @@ impl StellarStreamContract {
         Ok(proposal_id)
     }
 
+    /// Propose a privileged action (fee change, address restriction) for multisig approval
+    ///
+    /// The proposer and every approver must hold the role the action would
+    /// normally require. Stream proposals go through `create_proposal`.
+    pub fn create_action_proposal(
+        env: Env,
+        sender: Address,
+        action: ProposalAction,
+        required_approvals: u32,
+        deadline: u64,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        Self::require_enabled(&env)?;
+        let role = Self::required_role(&action).ok_or(Error::InvalidProposalAction)?;
+        if !Self::has_role(&env, &sender, role) {
+            return Err(Error::Unauthorized);
+        }
+        if let ProposalAction::SetFee(fee_bps, _) = action {
+            if fee_bps > MAX_FEE_BPS {
+                return Err(Error::InvalidAmount);
+            }
+        }
+        if required_approvals == 0 || required_approvals > MAX_PROPOSAL_APPROVERS {
+            return Err(Error::InvalidApprovalThreshold);
+        }
+        if matches!(action, ProposalAction::Upgrade(_))
+            && required_approvals < MIN_UPGRADE_APPROVALS
+        {
+            return Err(Error::InvalidApprovalThreshold);
+        }
+        if deadline <= env.ledger().timestamp() {
+            return Err(Error::ProposalExpired);
+        }
+
+        let proposal_id = Self::store_proposal(
+            &env,
+            &sender,
+            action.clone(),
+            required_approvals,
+            deadline,
+            false,
+            Vec::new(&env),
+        );
+
+        env.events().publish(
+            (symbol_short!("propose"), sender.clone()),
+            ActionProposalCreatedEvent {
+                proposal_id,
+                sender,
+                action,
+                required_approvals,
+                deadline,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(proposal_id)
+    }
+
+    fn store_proposal(
+        env: &Env,
+        sender: &Address,
+        action: ProposalAction,
+        required_approvals: u32,
+        deadline: u64,
+        escrowed: bool,
+        authorized_approvers: Vec<Address>,
+    ) -> u64 {
+        let proposal_id: u64 = env.storage().instance().get(&PROPOSAL_COUNT).unwrap_or(0);
+        let next_id = proposal_id + 1;
+
+        let proposal = Proposal {
+            sender: sender.clone(),
+            action,
+            approvers: Vec::new(env),
+            authorized_approvers,
+            required_approvals,
+            deadline,
+            original_deadline: deadline,
+            executed: false,
+            escrowed,
+            cancelled: false,
+        };
+
+        env.storage()
+            .instance()
+            .set(&(PROPOSAL_COUNT, proposal_id), &proposal);
+        env.storage().instance().set(&PROPOSAL_COUNT, &next_id);
+
+        proposal_id
+    }
+
+    fn count_distinct(addresses: &Vec<Address>) -> u32 {
+        let mut distinct: Vec<Address> = Vec::new(addresses.env());
+        for address in addresses.iter() {
+            if !distinct.contains(&address) {
+                distinct.push_back(address);
+            }
+        }
+        distinct.len()
+    }
+
+    /// Role needed to propose or approve an action; None for stream creation,
+    /// which anyone may approve unless the proposal names its approvers.
+    fn required_role(action: &ProposalAction) -> Option<Role> {
+        match action {
+            ProposalAction::CreateStream(_) => None,
+            ProposalAction::SetFee(_, _) => Some(Role::TreasuryManager),
+            ProposalAction::RestrictAddress(_) => Some(Role::Admin),
+            ProposalAction::Upgrade(_) => Some(Role::Admin),
+        }
+    }
+
     pub fn approve_proposal(env: Env, proposal_id: u64, approver: Address) -> Result<(), Error> {
         approver.require_auth();
 
         let key = (PROPOSAL_COUNT, proposal_id);
-        let mut proposal: StreamProposal = env
+        let mut proposal: Proposal = env
             .storage()
             .instance()
             .get(&key)
@@ -142,15 +322,31 @@ impl StellarStreamContract {
         if proposal.executed {
             return Err(Error::ProposalAlreadyExecuted);
         }
+        if proposal.cancelled {
+            return Err(Error::ProposalCancelled);
+        }
         if env.ledger().timestamp() > proposal.deadline {
             return Err(Error::ProposalExpired);
         }
+        if let Some(role) = Self::required_role(&proposal.action) {
+            if !Self::has_role(&env, &approver, role) {
+                return Err(Error::Unauthorized);
+            }
+        }
+        if !proposal.authorized_approvers.is_empty()
+            && !proposal.authorized_approvers.contains(&approver)
+        {
+            return Err(Error::Unauthorized);
+        }
 
         for existing_approver in proposal.approvers.iter() {
             if existing_approver == approver {
                 return Err(Error::AlreadyApproved);
             }
         }
+        if proposal.approvers.len() >= MAX_PROPOSAL_APPROVERS {
+            return Err(Error::ApproverLimitReached);
+        }
 
         proposal.approvers.push_back(approver.clone());
         let approval_count = proposal.approvers.len();
@@ -178,30 +374,172 @@ impl StellarStreamContract {
         Ok(())
     }
 
-    fn execute_proposal(env: &Env, proposal: StreamProposal) -> Result<u64, Error> {
-        // Transfer tokens from proposer to contract
-        let token_client = token::Client::new(env, &proposal.token);
-        token_client.transfer(
-            &proposal.sender,
-            &env.current_contract_address(),
-            &proposal.total_amount,
+    /// Cancel a pending proposal, refunding any escrowed funds to the sender
+    ///
+    /// Works both before and after the deadline, so an expired proposal's
+    /// escrow can always be recovered.
+    pub fn cancel_proposal(env: Env, proposal_id: u64, sender: Address) -> Result<(), Error> {
+        sender.require_auth();
+
+        let key = (PROPOSAL_COUNT, proposal_id);
+        let mut proposal: Proposal = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::ProposalNotFound)?;
+
+        if proposal.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if proposal.executed {
+            return Err(Error::ProposalAlreadyExecuted);
+        }
+        if proposal.cancelled {
+            return Err(Error::ProposalCancelled);
+        }
+
+        proposal.cancelled = true;
+        env.storage().instance().set(&key, &proposal);
+
+        let refunded = match proposal.action {
+            ProposalAction::CreateStream(ref stream) if proposal.escrowed => {
+                let token_client = token::Client::new(&env, &stream.token);
+                token_client.transfer(
+                    &env.current_contract_address(),
+                    &proposal.sender,
+                    &stream.total_amount,
+                );
+                stream.total_amount
+            }
+            _ => 0,
+        };
+
+        env.events().publish(
+            (symbol_short!("cancel"), proposal_id),
+            types::ProposalCancelledEvent {
+                proposal_id,
+                sender,
+                refunded,
+                timestamp: env.ledger().timestamp(),
+            },
         );
 
+        Ok(())
+    }
+
+    /// Give approvers more time on a live proposal (proposer only). The
+    /// deadline can't move more than MAX_PROPOSAL_EXTENSION past the one the
+    /// proposal was created with, and an expired proposal can't be revived.
+    pub fn extend_proposal_deadline(
+        env: Env,
+        proposal_id: u64,
+        sender: Address,
+        new_deadline: u64,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        let key = (PROPOSAL_COUNT, proposal_id);
+        let mut proposal: Proposal = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::ProposalNotFound)?;
+
+        if proposal.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if proposal.executed {
+            return Err(Error::ProposalAlreadyExecuted);
+        }
+        if proposal.cancelled {
+            return Err(Error::ProposalCancelled);
+        }
+        let now = env.ledger().timestamp();
+        if now > proposal.deadline {
+            return Err(Error::ProposalExpired);
+        }
+        if new_deadline <= now
+            || new_deadline <= proposal.deadline
+            || new_deadline > proposal.original_deadline + MAX_PROPOSAL_EXTENSION
+        {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        proposal.deadline = new_deadline;
+        env.storage().instance().set(&key, &proposal);
+
+        Ok(())
+    }
+
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Result<Proposal, Error> {
+        env.storage()
+            .instance()
+            .get(&(PROPOSAL_COUNT, proposal_id))
+            .ok_or(Error::ProposalNotFound)
+    }
+
+    fn execute_proposal(env: &Env, proposal: Proposal) -> Result<(), Error> {
+        match proposal.action {
+            ProposalAction::CreateStream(stream) => {
+                Self::execute_stream_proposal(env, &proposal.sender, stream, proposal.escrowed)?;
+            }
+            ProposalAction::SetFee(fee_bps, treasury) => {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::FeeConfig, &FeeConfig { fee_bps, treasury });
+            }
+            ProposalAction::RestrictAddress(address) => {
+                Self::add_restricted_address(env, address);
+            }
+            ProposalAction::Upgrade(new_wasm_hash) => {
+                Self::apply_upgrade(env, new_wasm_hash);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_stream_proposal(
+        env: &Env,
+        sender: &Address,
+        proposal: ProposedStream,
+        escrowed: bool,
+    ) -> Result<u64, Error> {
+        // The proposal may have been created before a drain, pause or
+        // withdraw-only mode; escrowed funds stay recoverable via cancel_proposal
+        Self::require_accepting_funds(env)?;
+        // Token, receiver and backdating rules may have changed since proposal
+        Self::check_proposed_stream(env, &proposal)?;
+
+        // Transfer tokens from proposer to contract unless already escrowed
+        if !escrowed {
+            let token_client = token::Client::new(env, &proposal.token);
+            token_client.transfer(
+                sender,
+                &env.current_contract_address(),
+                &proposal.total_amount,
+            );
+        }
+
         // Allocate next stream id
         let stream_id: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
         let next_id = stream_id + 1;
 
+        // Protocol fee comes out of the deposit; the rest is streamed
+        let total_amount =
+            Self::collect_fee(env, stream_id, &proposal.token, proposal.total_amount);
+
         let stream = Stream {
-            sender: proposal.sender.clone(),
+            sender: sender.clone(),
             receiver: proposal.receiver.clone(),
             token: proposal.token.clone(),
-            total_amount: proposal.total_amount,
+            total_amount,
             start_time: proposal.start_time,
             end_time: proposal.end_time,
+            cliff_time: proposal.start_time,
             withdrawn_amount: 0,
             interest_strategy: 0,
             vault_address: None,
-            deposited_principal: proposal.total_amount,
+            deposited_principal: total_amount,
             metadata: None,
             withdrawn: 0,
             cancelled: false,
@@ -213,30 +551,46 @@ impl StellarStreamContract {
             curve_type: CurveType::Linear,
             is_usd_pegged: false,
             usd_amount: 0,
-            oracle_address: proposal.sender.clone(),
+            oracle_address: sender.clone(),
             oracle_max_staleness: 0,
             price_min: 0,
             price_max: 0,
             is_soulbound: false,     // Proposals default to non-soulbound
             clawback_enabled: false, // Check at runtime if needed
-            arbiter: None,
+            arbiter: Self::get_default_arbiter(env.clone()),
             is_frozen: false,
+            pause_budget: 0,
+            require_receiver_consent: false,
+            last_activity: env.ledger().timestamp(),
+            created_ledger: env.ledger().sequence(),
+            created_at: env.ledger().timestamp(),
+            settlement_token: None,
+            router: None,
+            settlement_min_rate: 0,
+            step_config: StepConfig {
+                interval: 0,
+                tranches: 0,
+            },
+            transfer_locked_until: 0,
         };
 
         env.storage()
             .instance()
             .set(&(STREAM_COUNT, stream_id), &stream);
         env.storage().instance().set(&STREAM_COUNT, &next_id);
+        Self::adjust_outstanding(env, &proposal.token, total_amount)?;
+        Self::index_receiver_stream(env, &proposal.receiver, stream_id)?;
+        Self::index_sender_stream(env, sender, stream_id);
 
         // Emit StreamCreatedEvent
         env.events().publish(
-            (symbol_short!("create"), proposal.sender.clone()),
+            (symbol_short!("create"), sender.clone()),
             StreamCreatedEvent {
                 stream_id,
-                sender: proposal.sender.clone(),
+                sender: sender.clone(),
                 receiver: proposal.receiver.clone(),
                 token: proposal.token,
-                total_amount: proposal.total_amount,
+                total_amount,
                 start_time: proposal.start_time,
                 end_time: proposal.end_time,
                 timestamp: env.ledger().timestamp(),
@@ -250,6 +604,7 @@ impl StellarStreamContract {
     /// Create a new stream with optional soulbound locking
     ///
     /// # Parameters
+    /// - `cliff_time`: Nothing unlocks before this time. Pass `start_time` for no cliff.
     /// - `is_soulbound`: Set to true to permanently bind this stream to the receiver's address.
     ///   Cannot be changed after stream creation. Irreversible.
     pub fn create_stream(
@@ -260,11 +615,14 @@ impl StellarStreamContract {
         total_amount: i128,
         start_time: u64,
         end_time: u64,
+        cliff_time: u64,
         curve_type: CurveType,
         is_soulbound: bool,
     ) -> Result<u64, Error> {
+        sender.require_auth();
+
         let milestones = Vec::new(&env);
-        Self::create_stream_with_milestones(
+        Self::create_stream_unchecked(
             env,
             sender,
             receiver,
@@ -272,6 +630,7 @@ impl StellarStreamContract {
             total_amount,
             start_time,
             end_time,
+            cliff_time,
             milestones,
             curve_type,
             is_soulbound,
@@ -279,12 +638,10 @@ impl StellarStreamContract {
         )
     }
 
-    /// Create a new stream with milestones and optional soulbound locking
-    ///
-    /// # Parameters
-    /// - `is_soulbound`: Set to true to permanently bind this stream to the receiver's address.
-    ///   Cannot be changed after stream creation. Irreversible.
-    pub fn create_stream_with_milestones(
+    /// Same as `create_stream` (cliff at start_time), with `arbiter`, if given,
+    /// handling disputes instead of the default arbiter and clawback opted
+    /// into up front. The arbiter can't be the sender or receiver.
+    pub fn create_stream_with_arbiter(
         env: Env,
         sender: Address,
         receiver: Address,
@@ -292,52 +649,341 @@ impl StellarStreamContract {
         total_amount: i128,
         start_time: u64,
         end_time: u64,
-        milestones: Vec<Milestone>,
         curve_type: CurveType,
         is_soulbound: bool,
-        vault_address: Option<Address>,
+        arbiter: Option<Address>,
+        clawback_enabled: bool,
     ) -> Result<u64, Error> {
         sender.require_auth();
 
-        // Validate time range
-        if start_time >= end_time {
-            return Err(Error::InvalidTimeRange);
-        }
-        if total_amount <= 0 {
-            return Err(Error::InvalidAmount);
+        if arbiter == Some(sender.clone()) || arbiter == Some(receiver.clone()) {
+            return Err(Error::Unauthorized);
         }
 
-        // Validate vault if provided
-        let vault_shares = if let Some(ref vault) = vault_address {
-            // Check if vault is approved
-            if !Self::is_vault_approved(env.clone(), vault.clone()) {
-                return Err(Error::Unauthorized);
-            }
-
-            // Transfer tokens to contract first
-            let token_client = token::Client::new(&env, &token);
-            token_client.transfer(&sender, &env.current_contract_address(), &total_amount);
-
-            // Deposit to vault and get shares
-            vault::deposit_to_vault(&env, vault, &token, total_amount)
-                .map_err(|_| Error::InvalidAmount)?
-        } else {
-            // Standard stream without vault
-            let token_client = token::Client::new(&env, &token);
-            token_client.transfer(&sender, &env.current_contract_address(), &total_amount);
-            0
-        };
-
-        let stream_id: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
-        let next_id = stream_id + 1;
-
-        let stream = Stream {
-            sender: sender.clone(),
-            receiver: receiver.clone(),
-            token: token.clone(),
+        let stream_id = Self::create_stream_unchecked(
+            env.clone(),
+            sender,
+            receiver,
+            token,
             total_amount,
             start_time,
             end_time,
+            start_time,
+            Vec::new(&env),
+            curve_type,
+            is_soulbound,
+            None, // No vault
+        )?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env.storage().instance().get(&key).unwrap();
+        if arbiter.is_some() {
+            stream.arbiter = arbiter;
+        }
+        stream.clawback_enabled = clawback_enabled;
+        env.storage().instance().set(&key, &stream);
+
+        Ok(stream_id)
+    }
+
+    /// Create a linear stream starting now that pays `amount_per_month` for
+    /// `months` months
+    ///
+    /// A month is `SECONDS_PER_MONTH` (30 days), so the stream vests
+    /// `amount_per_month / 2_592_000` tokens per second and is funded with
+    /// `amount_per_month * months` up front.
+    pub fn create_stream_monthly(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        amount_per_month: i128,
+        months: u32,
+    ) -> Result<u64, Error> {
+        if months == 0 {
+            return Err(Error::InvalidTimeRange);
+        }
+        let total_amount = amount_per_month
+            .checked_mul(months as i128)
+            .ok_or(Error::InvalidAmount)?;
+        let start_time = env.ledger().timestamp();
+        let end_time = start_time + months as u64 * SECONDS_PER_MONTH;
+
+        Self::create_stream(
+            env,
+            sender,
+            receiver,
+            token,
+            total_amount,
+            start_time,
+            end_time,
+            start_time,
+            CurveType::Linear,
+            false,
+        )
+    }
+
+    /// Create one linked stream per tranche of `(amount, start, end, curve)`
+    /// and return the ID of the group tying them together
+    pub fn create_tranched_stream(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        tranches: Vec<(i128, u64, u64, CurveType)>,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        if tranches.is_empty() {
+            return Err(Error::InvalidAmount);
+        }
+        Self::check_batch_size(tranches.len())?;
+
+        let mut stream_ids = Vec::new(&env);
+        for (amount, start_time, end_time, curve_type) in tranches.iter() {
+            let stream_id = Self::create_stream_unchecked(
+                env.clone(),
+                sender.clone(),
+                receiver.clone(),
+                token.clone(),
+                amount,
+                start_time,
+                end_time,
+                start_time,
+                Vec::new(&env),
+                curve_type,
+                false,
+                None,
+            )?;
+            stream_ids.push_back(stream_id);
+        }
+
+        let group_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TrancheGroupCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TrancheGroup(group_id), &stream_ids);
+        env.storage()
+            .instance()
+            .set(&DataKey::TrancheGroupCount, &(group_id + 1));
+
+        Ok(group_id)
+    }
+
+    /// Stream IDs belonging to a tranche group, in tranche order
+    pub fn get_tranche_group(env: Env, group_id: u64) -> Result<Vec<u64>, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::TrancheGroup(group_id))
+            .ok_or(Error::StreamNotFound)
+    }
+
+    /// Create a new stream with milestones and optional soulbound locking
+    ///
+    /// The cliff defaults to `start_time`; use `create_milestone_stream` to
+    /// set one, since Soroban caps contract functions at ten parameters.
+    ///
+    /// # Parameters
+    /// - `is_soulbound`: Set to true to permanently bind this stream to the receiver's address.
+    ///   Cannot be changed after stream creation. Irreversible.
+    pub fn create_stream_with_milestones(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        milestones: Vec<Milestone>,
+        curve_type: CurveType,
+        is_soulbound: bool,
+        vault_address: Option<Address>,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        Self::create_stream_unchecked(
+            env,
+            sender,
+            receiver,
+            token,
+            total_amount,
+            start_time,
+            end_time,
+            start_time,
+            milestones,
+            curve_type,
+            is_soulbound,
+            vault_address,
+        )
+    }
+
+    /// Create a milestone stream with a cliff. Same as
+    /// `create_stream_with_milestones`, with the schedule in `params`.
+    pub fn create_milestone_stream(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        params: MilestoneStreamParams,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        Self::create_stream_unchecked(
+            env,
+            sender,
+            receiver,
+            token,
+            total_amount,
+            params.start_time,
+            params.end_time,
+            params.cliff_time,
+            params.milestones,
+            params.curve_type,
+            params.is_soulbound,
+            params.vault_address,
+        )
+    }
+
+    /// Create a milestone stream whose deposit is pulled from `funder`
+    /// rather than `sender`, for custody setups where the controlling
+    /// account holds no tokens. `sender` keeps cancel/pause rights and gets
+    /// any refund; the funder has no say over the stream once created.
+    ///
+    /// Same as `create_stream_with_milestones` without the vault, which the
+    /// ten-parameter cap leaves no room for.
+    pub fn create_stream_with_funder(
+        env: Env,
+        sender: Address,
+        funder: Address,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        milestones: Vec<Milestone>,
+        curve_type: CurveType,
+        is_soulbound: bool,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+        funder.require_auth();
+
+        Self::create_stream_funded(
+            env,
+            sender,
+            funder,
+            receiver,
+            token,
+            total_amount,
+            start_time,
+            end_time,
+            start_time,
+            milestones,
+            curve_type,
+            is_soulbound,
+            None, // No vault
+        )
+    }
+
+    /// Body of `create_stream_with_milestones` for callers that have already
+    /// required the sender's authorization
+    fn create_stream_unchecked(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        cliff_time: u64,
+        milestones: Vec<Milestone>,
+        curve_type: CurveType,
+        is_soulbound: bool,
+        vault_address: Option<Address>,
+    ) -> Result<u64, Error> {
+        Self::create_stream_funded(
+            env,
+            sender.clone(),
+            sender,
+            receiver,
+            token,
+            total_amount,
+            start_time,
+            end_time,
+            cliff_time,
+            milestones,
+            curve_type,
+            is_soulbound,
+            vault_address,
+        )
+    }
+
+    /// `create_stream_unchecked` with the deposit taken from `funder`, whose
+    /// authorization the caller must also have required
+    fn create_stream_funded(
+        env: Env,
+        sender: Address,
+        funder: Address,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        cliff_time: u64,
+        milestones: Vec<Milestone>,
+        curve_type: CurveType,
+        is_soulbound: bool,
+        vault_address: Option<Address>,
+    ) -> Result<u64, Error> {
+        Self::require_accepting_funds(&env)?;
+        let errors = Self::collect_stream_errors(
+            &env,
+            &receiver,
+            &token,
+            total_amount,
+            start_time,
+            end_time,
+            cliff_time,
+            &milestones,
+            &curve_type,
+            &vault_address,
+        );
+        if let Some(error) = errors.first() {
+            return Err(error);
+        }
+
+        // Transfer tokens to contract first
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&funder, &env.current_contract_address(), &total_amount);
+
+        let stream_id: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
+        let next_id = stream_id + 1;
+
+        // Protocol fee comes out of the deposit; the rest is streamed
+        let total_amount = Self::collect_fee(&env, stream_id, &token, total_amount);
+
+        let vault_shares = if let Some(ref vault) = vault_address {
+            Self::adjust_vault_deposited(&env, vault, total_amount)?;
+
+            // Deposit to vault and get shares
+            vault::deposit_to_vault(&env, vault, &token, total_amount)
+                .map_err(|_| Error::InvalidAmount)?
+        } else {
+            // Standard stream without vault
+            0
+        };
+
+        let stream = Stream {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            token: token.clone(),
+            total_amount,
+            start_time,
+            end_time,
+            cliff_time,
             withdrawn_amount: 0,
             interest_strategy: 0,
             vault_address: vault_address.clone(),
@@ -359,19 +1005,34 @@ impl StellarStreamContract {
             price_max: 0,
             is_soulbound,
             clawback_enabled: false, // TODO: Check token flags
-            arbiter: None,
+            arbiter: Self::get_default_arbiter(env.clone()),
             is_frozen: false,
+            pause_budget: 0,
+            require_receiver_consent: false,
+            last_activity: env.ledger().timestamp(),
+            created_ledger: env.ledger().sequence(),
+            created_at: env.ledger().timestamp(),
+            settlement_token: None,
+            router: None,
+            settlement_min_rate: 0,
+            step_config: StepConfig {
+                interval: 0,
+                tranches: 0,
+            },
+            transfer_locked_until: 0,
         };
 
         let stream_key = (STREAM_COUNT, stream_id);
-        
+
         // Extend contract instance TTL to ensure long-term accessibility
-        Self::extend_contract_ttl(&env);
-        
-        env.storage()
-            .instance()
-            .set(&stream_key, &stream);
+        let max_ttl = env.storage().max_ttl();
+        env.storage().instance().extend_ttl(max_ttl / 2, max_ttl);
+
+        env.storage().instance().set(&stream_key, &stream);
         env.storage().instance().set(&STREAM_COUNT, &next_id);
+        Self::adjust_outstanding(&env, &token, total_amount)?;
+        Self::index_receiver_stream(&env, &receiver, stream_id)?;
+        Self::index_sender_stream(&env, &sender, stream_id);
 
         // Store vault shares if vault is used
         if vault_shares > 0 {
@@ -380,177 +1041,4243 @@ impl StellarStreamContract {
                 .set(&DataKey::VaultShares(stream_id), &vault_shares);
         }
 
-        // If soulbound, emit event and add to index
-        if is_soulbound {
-            env.events().publish(
-                (symbol_short!("soulbound"), symbol_short!("locked")),
-                (stream_id, receiver.clone()),
-            );
+        // If soulbound, emit event and add to index
+        if is_soulbound {
+            Self::index_soulbound(&env, stream_id, &receiver);
+        }
+
+        env.events().publish(
+            (symbol_short!("create"), sender.clone()),
+            StreamCreatedEvent {
+                stream_id,
+                sender: sender.clone(),
+                receiver: receiver.clone(),
+                token,
+                total_amount,
+                start_time,
+                end_time,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        Self::mint_receipt(&env, stream_id, &receiver);
+
+        Ok(stream_id)
+    }
+
+    /// Create a stream that releases `total_amount` in `tranches` equal parts,
+    /// one every `interval` seconds from `start_time`
+    pub fn create_step_stream(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        start_time: u64,
+        interval: u64,
+        tranches: u32,
+    ) -> Result<u64, Error> {
+        sender.require_auth();
+
+        if interval == 0 || tranches == 0 {
+            return Err(Error::InvalidTimeRange);
+        }
+        let end_time = interval
+            .checked_mul(tranches as u64)
+            .and_then(|duration| start_time.checked_add(duration))
+            .ok_or(Error::InvalidTimeRange)?;
+
+        let stream_id = Self::create_stream_unchecked(
+            env.clone(),
+            sender,
+            receiver,
+            token,
+            total_amount,
+            start_time,
+            end_time,
+            start_time,
+            Vec::new(&env),
+            CurveType::Linear,
+            false,
+            None,
+        )?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+        stream.curve_type = CurveType::Step;
+        stream.step_config = StepConfig { interval, tranches };
+        env.storage().instance().set(&key, &stream);
+
+        Ok(stream_id)
+    }
+
+    /// Create a stream that pays out a fixed USD value
+    ///
+    /// The initial token deposit is derived from the oracle price at creation.
+    /// The price must be fresh and within `[min_price, max_price]`, otherwise
+    /// no stream is funded.
+    pub fn create_usd_pegged_stream(
+        env: Env,
+        sender: Address,
+        receiver: Address,
+        token: Address,
+        usd_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        oracle_address: Address,
+        max_staleness: u64,
+        min_price: i128,
+        max_price: i128,
+    ) -> Result<u64, Error> {
+        if usd_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if min_price <= 0 || min_price > max_price {
+            return Err(Error::InvalidAmount);
+        }
+
+        let price = oracle::get_price(&env, &oracle_address, max_staleness)
+            .map_err(|_| Error::OracleFailed)?;
+        if price < min_price || price > max_price {
+            return Err(Error::PriceOutOfBounds);
+        }
+
+        let initial_amount =
+            oracle::calculate_token_amount(usd_amount, price).map_err(|_| Error::OracleFailed)?;
+        if initial_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let stream_id = Self::create_stream_with_milestones(
+            env.clone(),
+            sender,
+            receiver,
+            token,
+            initial_amount,
+            start_time,
+            end_time,
+            Vec::new(&env),
+            CurveType::Linear,
+            false,
+            None,
+        )?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+        stream.is_usd_pegged = true;
+        // The protocol fee takes the same share of the USD value as of the tokens
+        stream.usd_amount = usd_amount
+            .checked_mul(stream.total_amount)
+            .ok_or(Error::InvalidAmount)?
+            / initial_amount;
+        stream.oracle_address = oracle_address;
+        stream.oracle_max_staleness = max_staleness;
+        stream.price_min = min_price;
+        stream.price_max = max_price;
+        env.storage().instance().set(&key, &stream);
+
+        Ok(stream_id)
+    }
+
+    pub fn initialize(env: Env, admin: Address) {
+        admin.require_auth();
+
+        // Set admin role
+        env.storage().instance().set(&DataKey::Admin, &admin);
+
+        // Grant all roles to admin
+        env.storage()
+            .instance()
+            .set(&DataKey::Role(admin.clone(), Role::Admin), &true);
+        env.storage()
+            .instance()
+            .set(&DataKey::Role(admin.clone(), Role::Pauser), &true);
+        env.storage()
+            .instance()
+            .set(&DataKey::Role(admin.clone(), Role::TreasuryManager), &true);
+    }
+
+    fn mint_receipt(env: &Env, stream_id: u64, owner: &Address) {
+        let receipt = StreamReceipt {
+            stream_id,
+            owner: owner.clone(),
+            minted_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .instance()
+            .set(&(RECEIPT, stream_id), &receipt);
+    }
+
+    pub fn get_stream(env: Env, stream_id: u64) -> Result<Stream, Error> {
+        env.storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)
+    }
+
+    /// ID the next created stream will get. Only a hint: any creation that
+    /// lands first, including in the same ledger, takes it.
+    pub fn peek_next_stream_id(env: Env) -> u64 {
+        env.storage().instance().get(&STREAM_COUNT).unwrap_or(0)
+    }
+
+    /// Number of streams ever created, purged ones included. IDs run from 0
+    /// to this count.
+    pub fn get_stream_count(env: Env) -> u64 {
+        env.storage().instance().get(&STREAM_COUNT).unwrap_or(0)
+    }
+
+    /// Streams with IDs in `start_id..start_id + limit`, skipping purged ones,
+    /// for indexers to page through. `limit` is capped at MAX_PAGE_SIZE; the
+    /// next page starts at `start_id + limit`.
+    pub fn get_streams_page(env: Env, start_id: u64, limit: u32) -> Vec<Stream> {
+        let end_id = start_id
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(Self::get_stream_count(env.clone()));
+
+        let mut page = Vec::new(&env);
+        for stream_id in start_id..end_id {
+            if let Some(stream) = env.storage().instance().get(&(STREAM_COUNT, stream_id)) {
+                page.push_back(stream);
+            }
+        }
+        page
+    }
+
+    /// Get the real completion time of a stream, shifted by every pause.
+    /// If the stream is currently paused, the ongoing pause is included too.
+    pub fn get_effective_end_time(env: Env, stream_id: u64) -> Result<u64, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        let current_time = env.ledger().timestamp();
+        Ok(stream.end_time
+            + stream.total_paused_duration
+            + Self::ongoing_pause(&stream, current_time))
+    }
+
+    /// Timestamp of the last state-changing operation on a stream.
+    /// Monitoring can use this to spot idle streams at risk of TTL expiry.
+    pub fn get_last_activity(env: Env, stream_id: u64) -> Result<u64, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        Ok(stream.last_activity)
+    }
+
+    /// Oracle prices used by past withdrawals of a USD-pegged stream, as
+    /// (timestamp, price) pairs, oldest first
+    pub fn get_withdraw_prices(env: Env, stream_id: u64) -> Vec<(u64, i128)> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WithdrawPrices(stream_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Earliest timestamp (no earlier than now) at which at least
+    /// `min_amount` will be claimable given what has already been withdrawn
+    pub fn next_claimable_time(env: Env, stream_id: u64, min_amount: i128) -> Result<u64, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if min_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if min_amount > stream.total_amount - stream.withdrawn_amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let claimable_at =
+            |t: u64| Self::calculate_unlocked(&stream, t) - stream.withdrawn_amount >= min_amount;
+
+        let now = env.ledger().timestamp();
+        if claimable_at(now) {
+            return Ok(now);
+        }
+        // A paused stream may never unlock more
+        if stream.is_paused {
+            return Err(Error::StreamPaused);
+        }
+
+        // Unlocking is monotonic in time, so binary search up to the fully
+        // vested point for the first timestamp that satisfies min_amount
+        let mut low = now;
+        let mut high = now.max(stream.end_time + stream.total_paused_duration);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if claimable_at(mid) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+
+        Ok(high)
+    }
+
+    /// Amount that vested between `since_timestamp` and now, following the
+    /// stream's curve and pauses, so clients can poll incremental accrual
+    pub fn accrued_since(env: Env, stream_id: u64, since_timestamp: u64) -> Result<i128, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        let now = env.ledger().timestamp();
+        if since_timestamp > now {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        let accrued = Self::calculate_unlocked(&stream, now)
+            - Self::calculate_unlocked(&stream, since_timestamp);
+        Ok(accrued.max(0))
+    }
+
+    /// Parameters of the stream's unlock curve in one struct. Unlocked at
+    /// `t` is the curve evaluated at `t - start_time` less pauses, see
+    /// `CurveParams`.
+    pub fn get_curve_params(env: Env, stream_id: u64) -> Result<CurveParams, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        Ok(CurveParams {
+            curve_type: stream.curve_type,
+            total_amount: stream.total_amount,
+            start_time: stream.start_time,
+            end_time: stream.end_time,
+            cliff_time: stream.cliff_time,
+            milestones: stream.milestones,
+            step_config: stream.step_config,
+            total_paused_duration: stream.total_paused_duration,
+            paused_since: stream.is_paused.then_some(stream.paused_time),
+            pause_budget: stream.pause_budget,
+        })
+    }
+
+    /// Sample the unlock curve at `points` evenly spaced timestamps between
+    /// start_time and end_time (inclusive). Returns (timestamp, unlocked) pairs.
+    /// `points` must be between 2 and MAX_PAGE_SIZE.
+    pub fn get_unlock_schedule(
+        env: Env,
+        stream_id: u64,
+        points: u32,
+    ) -> Result<Vec<(u64, i128)>, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        if !(2..=MAX_PAGE_SIZE).contains(&points) {
+            return Err(Error::InvalidAmount);
+        }
+
+        // u128 so `duration * i` cannot overflow on very long streams
+        let duration = (stream.end_time - stream.start_time) as u128;
+        let mut schedule = Vec::new(&env);
+        for i in 0..points {
+            let offset = duration * i as u128 / (points - 1) as u128;
+            let timestamp = stream.start_time + offset as u64;
+            schedule.push_back((timestamp, Self::calculate_unlocked(&stream, timestamp)));
+        }
+
+        Ok(schedule)
+    }
+
+    /// Same samples as `get_unlock_schedule`, with the current withdrawn amount
+    /// attached to each point so UIs can draw "claimed vs available" charts.
+    pub fn get_vested_withdrawn_schedule(
+        env: Env,
+        stream_id: u64,
+        points: u32,
+    ) -> Result<Vec<(u64, i128, i128)>, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        let unlock_schedule = Self::get_unlock_schedule(env.clone(), stream_id, points)?;
+        let mut schedule = Vec::new(&env);
+        for (timestamp, vested) in unlock_schedule.iter() {
+            schedule.push_back((timestamp, vested, stream.withdrawn_amount));
+        }
+
+        Ok(schedule)
+    }
+
+    /// Lock a stream to its current receiver after creation
+    ///
+    /// Callable by the sender (turning a grant non-transferable) or by the
+    /// receipt owner (voluntarily committing to it). It is a one-way latch:
+    /// once set, `is_soulbound` can never be cleared.
+    pub fn make_soulbound(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+
+        let receipt: StreamReceipt = env
+            .storage()
+            .instance()
+            .get(&(RECEIPT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.sender != caller && receipt.owner != caller {
+            return Err(Error::Unauthorized);
+        }
+
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+
+        stream.is_soulbound = true;
+        env.storage().instance().set(&stream_key, &stream);
+
+        // A pending receiver change can no longer be applied
+        env.storage()
+            .instance()
+            .remove(&DataKey::PendingReceiver(stream_id));
+
+        Self::index_soulbound(&env, stream_id, &stream.receiver);
+
+        Ok(())
+    }
+
+    /// Emit the soulbound event and add the stream to the soulbound index
+    fn index_soulbound(env: &Env, stream_id: u64, receiver: &Address) {
+        env.events().publish(
+            (symbol_short!("soulbound"), symbol_short!("locked")),
+            (stream_id, receiver.clone()),
+        );
+
+        let mut soulbound_streams: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SoulboundStreams)
+            .unwrap_or(Vec::new(env));
+        soulbound_streams.push_back(stream_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::SoulboundStreams, &soulbound_streams);
+    }
+
+    pub fn get_soulbound_streams(env: Env) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SoulboundStreams)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Rebuild the soulbound index from each stream's `is_soulbound` flag
+    /// (Admin only). Scans at most MAX_BATCH stream IDs per call and returns
+    /// true once the index has been replaced; keep calling until it does.
+    pub fn rebuild_soulbound_index(env: Env, admin: Address) -> Result<bool, Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let (cursor, mut found): (u64, Vec<u64>) = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SoulboundRebuild)
+            .unwrap_or((0, Vec::new(&env)));
+        let stream_count: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
+        let batch_end = (cursor + MAX_BATCH as u64).min(stream_count);
+
+        for stream_id in cursor..batch_end {
+            let stream: Option<Stream> = env.storage().instance().get(&(STREAM_COUNT, stream_id));
+            if let Some(stream) = stream {
+                if stream.is_soulbound {
+                    found.push_back(stream_id);
+                }
+            }
+        }
+
+        // The live index is only swapped once the scan is complete
+        let completed = batch_end >= stream_count;
+        if completed {
+            env.storage()
+                .persistent()
+                .set(&DataKey::SoulboundStreams, &found);
+            env.storage()
+                .persistent()
+                .remove(&DataKey::SoulboundRebuild);
+        } else {
+            env.storage()
+                .persistent()
+                .set(&DataKey::SoulboundRebuild, &(batch_end, found));
+        }
+
+        Ok(completed)
+    }
+
+    /// Active (non-cancelled) stream IDs currently paying `receiver`
+    pub fn get_receiver_streams(env: Env, receiver: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ReceiverIndex(receiver))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Every stream ID paying `receiver`, cancelled ones included until they
+    /// are purged. Use `get_stream` to filter by state.
+    pub fn get_streams_by_receiver(env: Env, receiver: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&(RECEIVER_HISTORY, receiver))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Active (non-cancelled) stream IDs funded by `sender`
+    pub fn get_sender_streams(env: Env, sender: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SenderIndex(sender))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Cap how many active streams a single receiver can have (Admin only).
+    /// 0 means unlimited, which is the default.
+    pub fn set_max_streams_per_receiver(
+        env: Env,
+        admin: Address,
+        max_streams: u32,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxStreamsPerReceiver, &max_streams);
+
+        Ok(())
+    }
+
+    pub fn get_max_streams_per_receiver(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MaxStreamsPerReceiver)
+            .unwrap_or(0)
+    }
+
+    /// Limit how far before creation a new stream's start_time may lie, or
+    /// lift the limit with `allow_backdating` (Admin only)
+    pub fn set_backdating_policy(
+        env: Env,
+        admin: Address,
+        max_backdating: u64,
+        allow_backdating: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&BACKDATE, &(max_backdating, allow_backdating));
+        Ok(())
+    }
+
+    /// (max backdating in seconds, backdating unrestricted); defaults to
+    /// DEFAULT_MAX_BACKDATING and restricted
+    pub fn get_backdating_policy(env: Env) -> (u64, bool) {
+        env.storage()
+            .instance()
+            .get(&BACKDATE)
+            .unwrap_or((DEFAULT_MAX_BACKDATING, false))
+    }
+
+    fn index_receiver_stream(env: &Env, receiver: &Address, stream_id: u64) -> Result<(), Error> {
+        let mut streams = Self::get_receiver_streams(env.clone(), receiver.clone());
+
+        let max_streams = Self::get_max_streams_per_receiver(env.clone());
+        if max_streams > 0 && streams.len() >= max_streams {
+            return Err(Error::ReceiverStreamLimitReached);
+        }
+
+        streams.push_back(stream_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ReceiverIndex(receiver.clone()), &streams);
+
+        let mut history = Self::get_streams_by_receiver(env.clone(), receiver.clone());
+        history.push_back(stream_id);
+        env.storage()
+            .persistent()
+            .set(&(RECEIVER_HISTORY, receiver.clone()), &history);
+        Ok(())
+    }
+
+    fn unindex_receiver_stream(env: &Env, receiver: &Address, stream_id: u64) {
+        let mut streams = Self::get_receiver_streams(env.clone(), receiver.clone());
+        if let Some(index) = streams.first_index_of(stream_id) {
+            streams.remove(index);
+            env.storage()
+                .persistent()
+                .set(&DataKey::ReceiverIndex(receiver.clone()), &streams);
+        }
+    }
+
+    /// Drop a stream from `receiver`'s history, which `unindex_receiver_stream`
+    /// leaves alone so cancelled streams stay listed
+    fn unindex_receiver_history(env: &Env, receiver: &Address, stream_id: u64) {
+        let mut history = Self::get_streams_by_receiver(env.clone(), receiver.clone());
+        if let Some(index) = history.first_index_of(stream_id) {
+            history.remove(index);
+            env.storage()
+                .persistent()
+                .set(&(RECEIVER_HISTORY, receiver.clone()), &history);
+        }
+    }
+
+    fn index_sender_stream(env: &Env, sender: &Address, stream_id: u64) {
+        let mut streams = Self::get_sender_streams(env.clone(), sender.clone());
+        streams.push_back(stream_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::SenderIndex(sender.clone()), &streams);
+    }
+
+    fn unindex_sender_stream(env: &Env, sender: &Address, stream_id: u64) {
+        let mut streams = Self::get_sender_streams(env.clone(), sender.clone());
+        if let Some(index) = streams.first_index_of(stream_id) {
+            streams.remove(index);
+            env.storage()
+                .persistent()
+                .set(&DataKey::SenderIndex(sender.clone()), &streams);
+        }
+    }
+
+    /// Tokens still owed to streams (deposited but not yet paid out), per token
+    pub fn get_total_outstanding(env: Env) -> Vec<(Address, i128)> {
+        let tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::OutstandingTokens)
+            .unwrap_or(Vec::new(&env));
+
+        let mut totals = Vec::new(&env);
+        for token in tokens.iter() {
+            let amount: i128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::Outstanding(token.clone()))
+                .unwrap_or(0);
+            totals.push_back((token, amount));
+        }
+        totals
+    }
+
+    /// Recompute the outstanding counter for `token` from every live stream,
+    /// repairing any drift (Admin only). Scans the whole stream table, so it
+    /// is meant for rare maintenance rather than routine use.
+    pub fn rebuild_outstanding(env: Env, admin: Address, token: Address) -> Result<i128, Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let stream_count: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
+        let mut outstanding: i128 = 0;
+        for stream_id in 0..stream_count {
+            let stream: Option<Stream> = env.storage().instance().get(&(STREAM_COUNT, stream_id));
+            if let Some(stream) = stream {
+                if stream.token == token && !stream.cancelled {
+                    outstanding += stream.total_amount - stream.withdrawn_amount;
+                }
+            }
+        }
+
+        let current: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Outstanding(token.clone()))
+            .unwrap_or(0);
+        Self::adjust_outstanding(&env, &token, outstanding - current)?;
+
+        Ok(outstanding)
+    }
+
+    /// Set the order a stream is served in by `process_withdraw_queue`;
+    /// higher goes first (Admin only). Streams default to priority 0.
+    pub fn set_stream_priority(
+        env: Env,
+        admin: Address,
+        stream_id: u64,
+        priority: u32,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+        if !env.storage().instance().has(&(STREAM_COUNT, stream_id)) {
+            return Err(Error::StreamNotFound);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::StreamPriority(stream_id), &priority);
+
+        Ok(())
+    }
+
+    pub fn get_stream_priority(env: Env, stream_id: u64) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::StreamPriority(stream_id))
+            .unwrap_or(0)
+    }
+
+    /// Pay out withdrawable balances of `token` streams from the liquidity the
+    /// contract holds right now (Admin only). Each call scans the next
+    /// MAX_BATCH stream IDs and serves them highest priority first, by stream
+    /// ID within a priority; the last stream served may be paid in part.
+    /// Vault-backed and router-settled streams are skipped since their
+    /// payouts depend on outside contracts, as are streams whose withdrawal
+    /// fails. Returns the principal paid and whether the scan reached the
+    /// last stream, after which the next call starts over.
+    pub fn process_withdraw_queue(
+        env: Env,
+        admin: Address,
+        token: Address,
+    ) -> Result<(i128, bool), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_not_globally_paused(&env)?;
+
+        let cursor_key = (WITHDRAW_QUEUE_CURSOR, token.clone());
+        let cursor: u64 = env.storage().instance().get(&cursor_key).unwrap_or(0);
+        let stream_count: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
+        let batch_end = (cursor + MAX_BATCH as u64).min(stream_count);
+
+        // (priority, stream_id, withdrawable), highest priority first
+        let current_time = env.ledger().timestamp();
+        let mut queue: Vec<(u32, u64, i128)> = Vec::new(&env);
+        for stream_id in cursor..batch_end {
+            let stream: Option<Stream> = env.storage().instance().get(&(STREAM_COUNT, stream_id));
+            let Some(stream) = stream else { continue };
+            if stream.token != token || stream.vault_address.is_some() || stream.router.is_some() {
+                continue;
+            }
+            if Self::validate_receiver(&env, &stream.receiver).is_err() {
+                continue;
+            }
+            let withdrawable =
+                match Self::compute_withdrawable(&env, stream_id, &stream, current_time) {
+                    Ok((amount, _)) if amount > 0 => amount,
+                    _ => continue,
+                };
+
+            let priority = Self::get_stream_priority(env.clone(), stream_id);
+            let mut at = queue.len();
+            for (i, (queued_priority, _, _)) in queue.iter().enumerate() {
+                if queued_priority < priority {
+                    at = i as u32;
+                    break;
+                }
+            }
+            queue.insert(at, (priority, stream_id, withdrawable));
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let mut total_paid: i128 = 0;
+        for (_, stream_id, withdrawable) in queue.iter() {
+            let available = token_client.balance(&env.current_contract_address());
+            let amount = withdrawable.min(available);
+            if amount <= 0 {
+                break;
+            }
+
+            let stream: Stream = env
+                .storage()
+                .instance()
+                .get(&(STREAM_COUNT, stream_id))
+                .ok_or(Error::StreamNotFound)?;
+            // A destination set before it was restricted must not be paid
+            let destination = Self::withdraw_destination(&env, stream_id, &stream.receiver);
+            if Self::validate_receiver(&env, &destination).is_err() {
+                continue;
+            }
+            // Pay before anything is written, so a token that rejects the
+            // transfer leaves the stream as it was and the batch moves on
+            let sent =
+                token_client.try_transfer(&env.current_contract_address(), &destination, &amount);
+            if !matches!(sent, Ok(Ok(()))) {
+                continue;
+            }
+            Self::withdraw_to_destination(
+                env.clone(),
+                stream_id,
+                stream.receiver,
+                destination,
+                None,
+                Some(amount),
+                amount,
+                true,
+            )?;
+            total_paid += amount;
+        }
+
+        let completed = batch_end >= stream_count;
+        if completed {
+            env.storage().instance().remove(&cursor_key);
+        } else {
+            env.storage().instance().set(&cursor_key, &batch_end);
+        }
+
+        Ok((total_paid, completed))
+    }
+
+    /// Apply `delta` to the running outstanding counter for `token`.
+    /// New tokens are added to the tracked set, which is capped at MAX_TRACKED_TOKENS.
+    fn adjust_outstanding(env: &Env, token: &Address, delta: i128) -> Result<(), Error> {
+        let key = DataKey::Outstanding(token.clone());
+        let current: Option<i128> = env.storage().instance().get(&key);
+
+        if current.is_none() {
+            let mut tokens: Vec<Address> = env
+                .storage()
+                .instance()
+                .get(&DataKey::OutstandingTokens)
+                .unwrap_or(Vec::new(env));
+            if tokens.len() >= MAX_TRACKED_TOKENS {
+                return Err(Error::TooManyTokens);
+            }
+            tokens.push_back(token.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::OutstandingTokens, &tokens);
+        }
+
+        env.storage()
+            .instance()
+            .set(&key, &(current.unwrap_or(0) + delta));
+        Ok(())
+    }
+
+    /// Shared state gate for operations that change a stream. Cancellation is
+    /// terminal; paused and frozen streams are rejected unless the operation
+    /// says it can run in that state.
+    fn require_operable(
+        stream: &Stream,
+        allowed_while_paused: bool,
+        allowed_while_frozen: bool,
+    ) -> Result<(), Error> {
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if stream.is_frozen && !allowed_while_frozen {
+            return Err(Error::StreamFrozen);
+        }
+        if stream.is_paused && !allowed_while_paused {
+            return Err(Error::StreamPaused);
+        }
+        Ok(())
+    }
+
+    pub fn transfer_receiver(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        new_receiver: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+
+        // SOULBOUND CHECK FIRST
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+
+        // Authorization check: only sender can transfer receiver
+        if stream.sender != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+        if env.ledger().timestamp() < stream.transfer_locked_until {
+            return Err(Error::TransferLocked);
+        }
+        Self::validate_receiver(&env, &new_receiver)?;
+
+        // Receiver opted in to consent: record the change until they approve it
+        if stream.require_receiver_consent {
+            env.storage()
+                .instance()
+                .set(&DataKey::PendingReceiver(stream_id), &new_receiver);
+            env.events().publish(
+                (symbol_short!("rcv_req"), stream_id),
+                types::ReceiverChangeRequestedEvent {
+                    stream_id,
+                    current_receiver: stream.receiver,
+                    new_receiver,
+                    timestamp: env.ledger().timestamp(),
+                },
+            );
+            return Ok(());
+        }
+
+        // Update receiver
+        Self::reassign_receiver(&env, stream_id, &mut stream, new_receiver)?;
+        env.storage().instance().set(&stream_key, &stream);
+
+        Ok(())
+    }
+
+    /// Move a stream's receipt, and with it the right to withdraw, from its
+    /// owner to `to`. `from` is either the owner or the spender they approved
+    /// with `approve_receipt`.
+    pub fn transfer_receipt(
+        env: Env,
+        stream_id: u64,
+        from: Address,
+        to: Address,
+    ) -> Result<(), Error> {
+        from.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        let owner = stream.receipt_owner.clone();
+        if from != owner && Self::get_receipt_approval(env.clone(), stream_id) != Some(from) {
+            return Err(Error::NotReceiptOwner);
+        }
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+        Self::require_operable(&stream, true, false)?;
+        if env.ledger().timestamp() < stream.transfer_locked_until {
+            return Err(Error::TransferLocked);
+        }
+        Self::validate_receiver(&env, &to)?;
+
+        if !Self::move_receipt(&env, stream_id, &owner, &to)? {
+            return Err(Error::NotReceiptOwner);
+        }
+
+        Ok(())
+    }
+
+    /// Let `spender` transfer the receipt once, e.g. a marketplace listing it
+    /// (receipt owner only). Replaces any earlier approval; the approval is
+    /// cleared when the receipt moves.
+    pub fn approve_receipt(
+        env: Env,
+        stream_id: u64,
+        owner: Address,
+        spender: Address,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receipt_owner != owner {
+            return Err(Error::NotReceiptOwner);
+        }
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+
+        env.storage()
+            .instance()
+            .set(&(RECEIPT_APPROVAL, stream_id), &spender);
+
+        Ok(())
+    }
+
+    /// Withdraw the approval given by `approve_receipt` (receipt owner only)
+    pub fn revoke_receipt_approval(env: Env, stream_id: u64, owner: Address) -> Result<(), Error> {
+        owner.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receipt_owner != owner {
+            return Err(Error::NotReceiptOwner);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&(RECEIPT_APPROVAL, stream_id));
+
+        Ok(())
+    }
+
+    /// Receipt for a stream: its current owner and mint time
+    pub fn get_receipt(env: Env, stream_id: u64) -> Result<StreamReceipt, Error> {
+        env.storage()
+            .instance()
+            .get(&(RECEIPT, stream_id))
+            .ok_or(Error::StreamNotFound)
+    }
+
+    /// Live balances behind a receipt, for wallets and explorers
+    pub fn get_receipt_metadata(env: Env, stream_id: u64) -> Result<ReceiptMetadata, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        let unlocked = Self::calculate_unlocked(&stream, env.ledger().timestamp());
+        Ok(ReceiptMetadata {
+            stream_id,
+            locked_balance: stream.total_amount - unlocked,
+            unlocked_balance: unlocked,
+            total_amount: stream.total_amount,
+            token: stream.token,
+        })
+    }
+
+    /// Spender approved to transfer a stream's receipt, if any
+    pub fn get_receipt_approval(env: Env, stream_id: u64) -> Option<Address> {
+        env.storage().instance().get(&(RECEIPT_APPROVAL, stream_id))
+    }
+
+    /// Move every stream `from` receives to `to`, e.g. when rotating a
+    /// treasury key. Soulbound, frozen and transfer-locked streams stay with
+    /// `from`. Returns the IDs of the streams that moved.
+    pub fn transfer_all_receipts(env: Env, from: Address, to: Address) -> Result<Vec<u64>, Error> {
+        from.require_auth();
+
+        Self::validate_receiver(&env, &to)?;
+
+        let mut moved = Vec::new(&env);
+        for stream_id in Self::get_receiver_streams(env.clone(), from.clone()).iter() {
+            if Self::move_receipt(&env, stream_id, &from, &to)? {
+                moved.push_back(stream_id);
+            }
+        }
+
+        Ok(moved)
+    }
+
+    /// Move the listed streams `from` receives to `to`, e.g. for an estate
+    /// transfer. Streams `from` doesn't receive, or that are soulbound, frozen
+    /// or transfer-locked, are skipped. Returns the IDs of the streams that moved.
+    pub fn transfer_receipts_batch(
+        env: Env,
+        from: Address,
+        to: Address,
+        stream_ids: Vec<u64>,
+    ) -> Result<Vec<u64>, Error> {
+        from.require_auth();
+
+        Self::check_batch_size(stream_ids.len())?;
+        Self::validate_receiver(&env, &to)?;
+
+        let mut moved = Vec::new(&env);
+        for stream_id in stream_ids.iter() {
+            if Self::move_receipt(&env, stream_id, &from, &to)? {
+                moved.push_back(stream_id);
+            }
+        }
+
+        Ok(moved)
+    }
+
+    /// Reassign one stream from `from` to `to` for the bulk transfers, which
+    /// have already checked `to`. Returns false if the stream can't move.
+    fn move_receipt(
+        env: &Env,
+        stream_id: u64,
+        from: &Address,
+        to: &Address,
+    ) -> Result<bool, Error> {
+        let stream_key = (STREAM_COUNT, stream_id);
+        let stream: Option<Stream> = env.storage().instance().get(&stream_key);
+        let Some(mut stream) = stream else {
+            return Ok(false);
+        };
+        let now = env.ledger().timestamp();
+        if stream.receiver != *from
+            || stream.is_soulbound
+            || Self::require_operable(&stream, true, false).is_err()
+            || now < stream.transfer_locked_until
+        {
+            return Ok(false);
+        }
+
+        Self::reassign_receiver(env, stream_id, &mut stream, to.clone())?;
+        env.storage().instance().set(&stream_key, &stream);
+
+        env.events().publish(
+            (symbol_short!("transfer"), stream_id),
+            ReceiptTransferredEvent {
+                stream_id,
+                from: from.clone(),
+                to: to.clone(),
+                timestamp: now,
+            },
+        );
+        Ok(true)
+    }
+
+    /// Name a fallback who may take over the stream once the receiver has
+    /// gone `inactivity_seconds` without withdrawing, e.g. for inheritance
+    /// (receiver only). Replaces any earlier fallback.
+    pub fn set_fallback(
+        env: Env,
+        stream_id: u64,
+        owner: Address,
+        fallback: Address,
+        inactivity_seconds: u64,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receiver != owner {
+            return Err(Error::Unauthorized);
+        }
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+        Self::require_operable(&stream, true, true)?;
+        if inactivity_seconds == 0 {
+            return Err(Error::InvalidTimeRange);
+        }
+        Self::validate_receiver(&env, &fallback)?;
+
+        env.storage().instance().set(
+            &(FALLBACK, stream_id),
+            &(fallback, inactivity_seconds, env.ledger().timestamp()),
+        );
+
+        Ok(())
+    }
+
+    /// Remove the fallback set by `set_fallback` (receiver only)
+    pub fn clear_fallback(env: Env, stream_id: u64, owner: Address) -> Result<(), Error> {
+        owner.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receiver != owner {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage().instance().remove(&(FALLBACK, stream_id));
+
+        Ok(())
+    }
+
+    /// (fallback, inactivity seconds, set at) for a stream, if one is set
+    pub fn get_fallback(env: Env, stream_id: u64) -> Option<(Address, u64, u64)> {
+        env.storage().instance().get(&(FALLBACK, stream_id))
+    }
+
+    /// Take over the stream and its receipt as its fallback. The inactivity
+    /// window counts from the receiver's last withdrawal, or from when the
+    /// fallback was set if that is later.
+    pub fn claim_as_fallback(env: Env, stream_id: u64, fallback: Address) -> Result<(), Error> {
+        fallback.require_auth();
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+        let (expected, inactivity_seconds, set_at): (Address, u64, u64) = env
+            .storage()
+            .instance()
+            .get(&(FALLBACK, stream_id))
+            .ok_or(Error::Unauthorized)?;
+        if expected != fallback {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+
+        let now = env.ledger().timestamp();
+        if now < stream.transfer_locked_until {
+            return Err(Error::TransferLocked);
+        }
+        // Every withdrawal moves the loyalty checkpoint, so it doubles as the
+        // receiver's last withdrawal time
+        let last_withdrawal = env
+            .storage()
+            .instance()
+            .get::<_, (u64, i128)>(&DataKey::LoyaltyAccrual(stream_id))
+            .map_or(0, |(checkpoint, _)| checkpoint);
+        let inactive_since = last_withdrawal.max(set_at);
+        if now < inactive_since.saturating_add(inactivity_seconds) {
+            return Err(Error::TimelockNotElapsed);
+        }
+        Self::validate_receiver(&env, &fallback)?;
+
+        let from = stream.receiver.clone();
+        Self::reassign_receiver(&env, stream_id, &mut stream, fallback.clone())?;
+        stream.last_activity = now;
+        env.storage().instance().set(&stream_key, &stream);
+
+        env.events().publish(
+            (symbol_short!("transfer"), stream_id),
+            ReceiptTransferredEvent {
+                stream_id,
+                from,
+                to: fallback,
+                timestamp: now,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Move a stream whose receiver rejects token transfers to `fallback`
+    /// (sender only)
+    ///
+    /// Each call probes the receiver by sending it what is currently
+    /// withdrawable. The first failed probe is recorded and `false` returned;
+    /// once probes have kept failing for `REDIRECT_GRACE` with no withdrawal in
+    /// between, the stream is reassigned and `true` returned. Vesting is
+    /// unaffected. Fails with `StreamNotStuck` if the receiver accepts the probe.
+    pub fn redirect_on_failure(
+        env: Env,
+        sender: Address,
+        stream_id: u64,
+        fallback: Address,
+    ) -> Result<bool, Error> {
+        sender.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+        Self::require_operable(&stream, true, false)?;
+        Self::validate_receiver(&env, &fallback)?;
+
+        let now = env.ledger().timestamp();
+        let pending = Self::calculate_unlocked(&stream, now) - stream.withdrawn_amount;
+        if pending <= 0 {
+            return Err(Error::InsufficientBalance);
+        }
+        // A successful probe is rolled back along with the error
+        let token_client = token::Client::new(&env, &stream.token);
+        if let Ok(Ok(())) =
+            token_client.try_transfer(&env.current_contract_address(), &stream.receiver, &pending)
+        {
+            return Err(Error::StreamNotStuck);
+        }
+
+        // A withdrawal since the first failure means the receiver recovered
+        let fail_key = (WD_FAIL, stream_id);
+        let failing_since = match env.storage().instance().get::<_, (u64, i128)>(&fail_key) {
+            Some((since, withdrawn)) if withdrawn == stream.withdrawn_amount => since,
+            _ => {
+                env.storage()
+                    .instance()
+                    .set(&fail_key, &(now, stream.withdrawn_amount));
+                now
+            }
+        };
+        if now < failing_since.saturating_add(REDIRECT_GRACE) {
+            return Ok(false);
+        }
+
+        let from = stream.receiver.clone();
+        Self::reassign_receiver(&env, stream_id, &mut stream, fallback.clone())?;
+        stream.last_activity = now;
+        env.storage().instance().set(&stream_key, &stream);
+
+        env.events().publish(
+            (symbol_short!("transfer"), stream_id),
+            ReceiptTransferredEvent {
+                stream_id,
+                from,
+                to: fallback,
+                timestamp: now,
+            },
+        );
+
+        Ok(true)
+    }
+
+    /// Let the receiver require consent before the sender can reassign the stream
+    pub fn set_require_receiver_consent(
+        env: Env,
+        stream_id: u64,
+        receiver: Address,
+        required: bool,
+    ) -> Result<(), Error> {
+        receiver.require_auth();
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+
+        stream.require_receiver_consent = required;
+        env.storage().instance().set(&stream_key, &stream);
+
+        Ok(())
+    }
+
+    /// Block receiver transfers until `locked_until`, e.g. a vesting cliff.
+    /// The lock can be extended but never shortened.
+    pub fn set_transfer_lock(
+        env: Env,
+        stream_id: u64,
+        sender: Address,
+        locked_until: u64,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if locked_until < stream.transfer_locked_until {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        stream.transfer_locked_until = locked_until;
+        env.storage().instance().set(&stream_key, &stream);
+
+        Ok(())
+    }
+
+    /// Approve a pending receiver change requested by the sender
+    pub fn approve_receiver_change(
+        env: Env,
+        stream_id: u64,
+        receiver: Address,
+    ) -> Result<(), Error> {
+        receiver.require_auth();
+
+        let stream_key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&stream_key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+        if env.ledger().timestamp() < stream.transfer_locked_until {
+            return Err(Error::TransferLocked);
+        }
+
+        let pending_key = DataKey::PendingReceiver(stream_id);
+        let new_receiver: Address = env
+            .storage()
+            .instance()
+            .get(&pending_key)
+            .ok_or(Error::NoPendingChange)?;
+        // The new receiver may have been restricted since the change was requested
+        Self::validate_receiver(&env, &new_receiver)?;
+        env.storage().instance().remove(&pending_key);
+
+        Self::reassign_receiver(&env, stream_id, &mut stream, new_receiver)?;
+        env.storage().instance().set(&stream_key, &stream);
+
+        Ok(())
+    }
+
+    /// Move a stream to a new receiver's index. A withdraw destination set by
+    /// the old receiver doesn't carry over.
+    fn reassign_receiver(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        new_receiver: Address,
+    ) -> Result<(), Error> {
+        Self::unindex_receiver_stream(env, &stream.receiver, stream_id);
+        Self::unindex_receiver_history(env, &stream.receiver, stream_id);
+        Self::index_receiver_stream(env, &new_receiver, stream_id)?;
+        // The receipt follows the receiver
+        stream.receipt_owner = new_receiver.clone();
+        let receipt_key = (RECEIPT, stream_id);
+        let receipt: Option<StreamReceipt> = env.storage().instance().get(&receipt_key);
+        if let Some(mut receipt) = receipt {
+            receipt.owner = new_receiver.clone();
+            env.storage().instance().set(&receipt_key, &receipt);
+        }
+        stream.receiver = new_receiver;
+        // Settings made by the previous receiver don't carry over
+        Self::clear_stream_keys(env, stream_id);
+        Ok(())
+    }
+
+    /// Drop the per-stream settings tied to the current receiver or receipt
+    /// owner, shared by receiver changes and purging
+    fn clear_stream_keys(env: &Env, stream_id: u64) {
+        env.storage()
+            .instance()
+            .remove(&DataKey::WithdrawDestination(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::ReductionFloor(stream_id));
+        env.storage().instance().remove(&(FALLBACK, stream_id));
+        env.storage().instance().remove(&(WD_FAIL, stream_id));
+        env.storage()
+            .instance()
+            .remove(&(RECEIPT_APPROVAL, stream_id));
+    }
+
+    pub fn get_pending_receiver(env: Env, stream_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::PendingReceiver(stream_id))
+    }
+
+    /// Top up an active stream with additional funds
+    pub fn top_up_stream(
+        env: Env,
+        stream_id: u64,
+        sender: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        Self::require_accepting_funds(&env)?;
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+
+        let current_time = env.ledger().timestamp();
+        if current_time >= stream.end_time {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        // Transfer tokens from sender
+        let token_client = token::Client::new(&env, &stream.token);
+        token_client.transfer(&sender, &env.current_contract_address(), &amount);
+
+        // Calculate new end time based on flow rate
+        let total_duration = stream.end_time.saturating_sub(stream.start_time);
+        let flow_rate = stream.total_amount / total_duration as i128;
+
+        let new_total = stream.total_amount + amount;
+        let additional_duration = amount / flow_rate;
+        let new_end_time = stream.end_time + additional_duration as u64;
+
+        stream.total_amount = new_total;
+        stream.end_time = new_end_time;
+        stream.last_activity = current_time;
+        env.storage().instance().set(&key, &stream);
+        Self::adjust_outstanding(&env, &stream.token, amount)?;
+
+        env.events().publish(
+            (symbol_short!("topup"), stream_id),
+            types::StreamToppedUpEvent {
+                stream_id,
+                sender,
+                amount,
+                new_total,
+                new_end_time,
+                timestamp: current_time,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Correct the schedule of a misconfigured stream without moving funds
+    ///
+    /// Only possible before anything has been withdrawn, and requires the
+    /// receiver's consent alongside the sender's.
+    pub fn amend_stream(
+        env: Env,
+        sender: Address,
+        stream_id: u64,
+        new_start: u64,
+        new_end: u64,
+        new_curve: CurveType,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        if new_start >= new_end {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        stream.receiver.require_auth();
+
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if stream.is_soulbound {
+            return Err(Error::StreamIsSoulbound);
+        }
+        if stream.is_frozen {
+            return Err(Error::StreamFrozen);
+        }
+        if stream.withdrawn_amount != 0 {
+            return Err(Error::StreamAlreadyClaimed);
+        }
+        Self::validate_curve(&new_curve, &stream.milestones, new_start, new_end)?;
+
+        stream.cliff_time = stream.cliff_time.clamp(new_start, new_end);
+        stream.start_time = new_start;
+        stream.end_time = new_end;
+        stream.curve_type = new_curve.clone();
+        env.storage().instance().set(&key, &stream);
+
+        env.events().publish(
+            (symbol_short!("amend"), stream_id),
+            types::StreamAmendedEvent {
+                stream_id,
+                start_time: new_start,
+                end_time: new_end,
+                curve_type: new_curve,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Move a stream's end time without changing its principal, e.g. to
+    /// stretch a renegotiated vesting schedule. The receiver must never end up
+    /// having withdrawn more than the new schedule has unlocked.
+    pub fn reschedule_stream(
+        env: Env,
+        stream_id: u64,
+        sender: Address,
+        new_end_time: u64,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+
+        let current_time = env.ledger().timestamp();
+        if new_end_time <= current_time || new_end_time <= stream.start_time {
+            return Err(Error::InvalidTimeRange);
+        }
+        // Step schedules are fixed by their StepConfig
+        if stream.curve_type == CurveType::Step {
+            return Err(Error::InvalidTimeRange);
+        }
+        Self::validate_curve(
+            &stream.curve_type,
+            &stream.milestones,
+            stream.start_time,
+            new_end_time,
+        )?;
+
+        let old_end_time = stream.end_time;
+        stream.end_time = new_end_time;
+        stream.cliff_time = stream.cliff_time.min(new_end_time);
+        if Self::calculate_unlocked(&stream, current_time) < stream.withdrawn_amount {
+            return Err(Error::InvalidTimeRange);
+        }
+        stream.last_activity = current_time;
+        env.storage().instance().set(&key, &stream);
+
+        env.events().publish(
+            (symbol_short!("resched"), stream_id),
+            StreamRescheduledEvent {
+                stream_id,
+                sender,
+                old_end_time,
+                new_end_time,
+                timestamp: current_time,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Point a stream at a new off-chain description, e.g. an IPFS hash
+    /// (sender only)
+    pub fn set_stream_metadata(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        metadata: Option<BytesN<32>>,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != caller {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+
+        let old_metadata = stream.metadata.clone();
+        stream.metadata = metadata.clone();
+        env.storage().instance().set(&key, &stream);
+
+        env.events().publish(
+            (symbol_short!("metadata"), stream_id),
+            StreamMetadataUpdatedEvent {
+                stream_id,
+                old_metadata,
+                new_metadata: metadata,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Cap the cumulative time a stream may spend paused
+    ///
+    /// The budget can only be tightened once set, so receivers can rely on it.
+    pub fn set_pause_budget(
+        env: Env,
+        stream_id: u64,
+        sender: Address,
+        pause_budget: u64,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if pause_budget == 0 || (stream.pause_budget > 0 && pause_budget > stream.pause_budget) {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        stream.pause_budget = pause_budget;
+        env.storage().instance().set(&key, &stream);
+
+        Ok(())
+    }
+
+    pub fn pause_stream(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+        if stream.is_paused {
+            return Ok(());
+        }
+        if stream.pause_budget > 0 && stream.total_paused_duration >= stream.pause_budget {
+            return Err(Error::PauseBudgetExceeded);
+        }
+
+        stream.is_paused = true;
+        stream.paused_time = env.ledger().timestamp();
+        stream.last_activity = stream.paused_time;
+        env.storage().instance().set(&key, &stream);
+
+        Ok(())
+    }
+
+    pub fn unpause_stream(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+        if !stream.is_paused {
+            return Ok(());
+        }
+
+        let current_time = env.ledger().timestamp();
+        stream.total_paused_duration += Self::ongoing_pause(&stream, current_time);
+        stream.is_paused = false;
+        stream.paused_time = 0;
+        stream.last_activity = current_time;
+
+        env.storage().instance().set(&key, &stream);
+
+        Ok(())
+    }
+
+    /// Withdraw to the receiver, or to its withdraw destination if one is set
+    pub fn withdraw(env: Env, stream_id: u64, caller: Address) -> Result<i128, Error> {
+        let destination = Self::withdraw_destination(&env, stream_id, &caller);
+        Self::withdraw_to(env, stream_id, caller, destination)
+    }
+
+    /// Route every withdrawal from this stream to `destination`, e.g. a cold
+    /// wallet, without handing over the stream (receiver only)
+    pub fn set_withdraw_destination(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        destination: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receiver != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::validate_receiver(&env, &destination)?;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::WithdrawDestination(stream_id), &destination);
+
+        Ok(())
+    }
+
+    /// Send withdrawals back to the receiver itself (receiver only)
+    pub fn clear_withdraw_destination(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receiver != caller {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&DataKey::WithdrawDestination(stream_id));
+
+        Ok(())
+    }
+
+    pub fn get_withdraw_destination(env: Env, stream_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::WithdrawDestination(stream_id))
+    }
+
+    fn withdraw_destination(env: &Env, stream_id: u64, caller: &Address) -> Address {
+        Self::get_withdraw_destination(env.clone(), stream_id).unwrap_or(caller.clone())
+    }
+
+    /// Withdraw to another address, e.g. a custody account, without handing
+    /// over the stream. The claim event records the destination.
+    pub fn withdraw_to(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        destination: Address,
+    ) -> Result<i128, Error> {
+        caller.require_auth();
+
+        Self::validate_receiver(&env, &destination)?;
+        Self::withdraw_to_destination(env, stream_id, caller, destination, None, None, 0, true)
+    }
+
+    /// Withdraw to another address, e.g. an exchange deposit address, with a
+    /// memo carried in the claim event so the deposit can be matched off-chain
+    pub fn withdraw_to_with_memo(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        destination: Address,
+        memo: BytesN<32>,
+    ) -> Result<i128, Error> {
+        caller.require_auth();
+
+        Self::validate_receiver(&env, &destination)?;
+        Self::withdraw_to_destination(
+            env,
+            stream_id,
+            caller,
+            destination,
+            Some(memo),
+            None,
+            0,
+            true,
+        )
+    }
+
+    /// Withdraw at most `amount`, leaving anything above it in the stream.
+    /// Returns what was actually paid, which is less than `amount` when less
+    /// has vested.
+    pub fn withdraw_amount(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        caller.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let destination = Self::withdraw_destination(&env, stream_id, &caller);
+        Self::validate_receiver(&env, &destination)?;
+        Self::withdraw_to_destination(
+            env,
+            stream_id,
+            caller,
+            destination,
+            None,
+            Some(amount),
+            0,
+            true,
+        )
+    }
+
+    /// Withdraw from several streams at once, returning the amount paid by
+    /// each. Streams the caller doesn't receive, or that are paused, frozen,
+    /// cancelled or have nothing vested, contribute 0 instead of failing the
+    /// whole batch; any other failure reverts it. At most MAX_BATCH IDs per
+    /// call.
+    ///
+    /// With `summarize` set, the per-stream claim events are replaced by a
+    /// single `BatchOperationEvent` to save on event fees.
+    pub fn withdraw_batch(
+        env: Env,
+        stream_ids: Vec<u64>,
+        caller: Address,
+        summarize: bool,
+    ) -> Result<Vec<i128>, Error> {
+        caller.require_auth();
+        if stream_ids.is_empty() {
+            return Err(Error::InvalidAmount);
+        }
+        Self::check_batch_size(stream_ids.len())?;
+
+        let mut amounts = Vec::new(&env);
+        for stream_id in stream_ids.iter() {
+            let stream: Option<Stream> = env.storage().instance().get(&(STREAM_COUNT, stream_id));
+            let amount = match stream {
+                Some(stream)
+                    if stream.receiver == caller
+                        && Self::require_operable(&stream, false, false).is_ok() =>
+                {
+                    let now = env.ledger().timestamp();
+                    // A paused token or a failing oracle only holds back
+                    // this stream, as does a restricted destination
+                    let withdrawable = Self::compute_withdrawable(&env, stream_id, &stream, now)
+                        .map_or(0, |(withdrawable, _)| withdrawable);
+                    let destination = Self::withdraw_destination(&env, stream_id, &caller);
+                    if withdrawable <= 0 || Self::validate_receiver(&env, &destination).is_err() {
+                        0
+                    } else {
+                        // Failures past this point, e.g. a vault refusing to
+                        // redeem, must revert the batch rather than leave the
+                        // stream marked as paid
+                        Self::withdraw_to_destination(
+                            env.clone(),
+                            stream_id,
+                            caller.clone(),
+                            destination,
+                            None,
+                            None,
+                            0,
+                            !summarize,
+                        )?
+                    }
+                }
+                _ => 0,
+            };
+            amounts.push_back(amount);
+        }
+
+        if summarize {
+            Self::publish_batch_summary(
+                &env,
+                symbol_short!("withdraw"),
+                &caller,
+                &stream_ids,
+                &amounts,
+            );
+        }
+
+        Ok(amounts)
+    }
+
+    /// One event standing in for the per-item events of a batch call. Only
+    /// items that moved funds are counted; the hash covers every requested ID
+    /// so it can be checked against the call arguments.
+    fn publish_batch_summary(
+        env: &Env,
+        operation: Symbol,
+        caller: &Address,
+        stream_ids: &Vec<u64>,
+        amounts: &Vec<i128>,
+    ) {
+        let mut count = 0u32;
+        let mut total_amount = 0i128;
+        for amount in amounts.iter() {
+            if amount > 0 {
+                count += 1;
+                total_amount += amount;
+            }
+        }
+
+        env.events().publish(
+            (symbol_short!("batch"), caller.clone()),
+            BatchOperationEvent {
+                operation,
+                count,
+                total_amount,
+                stream_ids_hash: env.crypto().sha256(&stream_ids.clone().to_xdr(env)).into(),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+    }
+
+    /// Principal a withdrawal would pay right now, without auth, transfers or
+    /// TTL bumps. Fails with the same error `withdraw` would when the stream
+    /// is paused, frozen or cancelled. Vault interest and loyalty bonuses are
+    /// not included.
+    pub fn get_withdrawable(env: Env, stream_id: u64) -> Result<i128, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        let (to_withdraw, _) =
+            Self::compute_withdrawable(&env, stream_id, &stream, env.ledger().timestamp())?;
+        Ok(to_withdraw.max(0))
+    }
+
+    /// Shared by `withdraw` and `get_withdrawable`. For USD-pegged streams
+    /// also returns the (price, USD unlocked) a withdrawal has to record.
+    fn compute_withdrawable(
+        env: &Env,
+        stream_id: u64,
+        stream: &Stream,
+        current_time: u64,
+    ) -> Result<(i128, Option<(i128, i128)>), Error> {
+        Self::require_operable(stream, false, false)?;
+        if Self::is_token_paused(env.clone(), stream.token.clone()) {
+            return Err(Error::TokenPaused);
+        }
+
+        let unlocked = Self::calculate_unlocked(stream, current_time);
+        if !stream.is_usd_pegged {
+            return Ok((unlocked - stream.withdrawn_amount, None));
+        }
+
+        // USD-pegged streams re-check the oracle on every withdrawal and pay
+        // the unlocked USD value at that price
+        let price = oracle::get_price(env, &stream.oracle_address, stream.oracle_max_staleness)
+            .map_err(|_| Error::OracleFailed)?;
+        if price < stream.price_min || price > stream.price_max {
+            return Err(Error::PriceOutOfBounds);
+        }
+        let (tokens, usd_unlocked) = Self::usd_payout(env, stream_id, stream, unlocked, price)?;
+        Ok((tokens, Some((price, usd_unlocked))))
+    }
+
+    fn withdraw_to_destination(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        destination: Address,
+        memo: Option<BytesN<32>>,
+        max_amount: Option<i128>,
+        prepaid: i128,
+        publish_claim: bool,
+    ) -> Result<i128, Error> {
+        Self::require_not_globally_paused(&env)?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.receiver != caller {
+            return Err(Error::Unauthorized);
+        }
+        // Block a receiver restricted after the stream was created
+        Self::validate_receiver(&env, &stream.receiver)?;
+
+        let current_time = env.ledger().timestamp();
+        let (withdrawable, usd_quote) =
+            Self::compute_withdrawable(&env, stream_id, &stream, current_time)?;
+        if withdrawable <= 0 {
+            return Err(Error::InsufficientBalance);
+        }
+        let to_withdraw = withdrawable.min(max_amount.unwrap_or(withdrawable));
+
+        // Keep the oracle price for audits and mark the USD value as paid,
+        // pro rata when only part of the withdrawable amount is taken
+        if let Some((price, usd_unlocked)) = usd_quote {
+            Self::record_withdraw_price(&env, stream_id, current_time, price);
+            let usd_key = DataKey::UsdWithdrawn(stream_id);
+            let usd_withdrawn: i128 = env.storage().instance().get(&usd_key).unwrap_or(0);
+            let usd_paid = (usd_unlocked - usd_withdrawn)
+                .checked_mul(to_withdraw)
+                .ok_or(Error::InvalidAmount)?
+                / withdrawable;
+            env.storage()
+                .instance()
+                .set(&usd_key, &(usd_withdrawn + usd_paid));
+        }
+
+        // Commit all storage effects, including the vault shares being
+        // redeemed, before calling out to the vault or token contract
+        let redeemed_shares = match stream.vault_address {
+            Some(ref vault) => {
+                let remaining_principal = stream.total_amount - stream.withdrawn_amount;
+                Self::adjust_vault_deposited(&env, vault, -to_withdraw)?;
+                Self::take_vault_shares(&env, stream_id, remaining_principal, to_withdraw)
+            }
+            None => 0,
+        };
+        let loyalty_bonus = Self::take_loyalty_bonus(&env, stream_id, &stream, current_time);
+        stream.withdrawn_amount += to_withdraw;
+        stream.last_activity = current_time;
+        env.storage().instance().set(&key, &stream);
+        Self::adjust_outstanding(&env, &stream.token, -to_withdraw)?;
+        // First claim marks the stream as activated for grant tracking
+        let activated_key = DataKey::Activated(stream_id);
+        let first_claim = !env.storage().instance().has(&activated_key);
+        if first_claim {
+            env.storage().instance().set(&activated_key, &true);
+        }
+
+        // Vault-backed principal is redeemed first; any yield comes out as interest
+        let (principal_amount, interest_amount) = match stream.vault_address {
+            Some(ref vault) => Self::redeem_from_vault(&env, vault, redeemed_shares, to_withdraw)?,
+            None => (to_withdraw, 0),
+        };
+        let amount = principal_amount + interest_amount + loyalty_bonus;
+
+        // `prepaid` is principal the caller already sent to `destination`
+        if amount > prepaid {
+            Self::pay_out(&env, stream_id, &stream, &destination, amount - prepaid)?;
+        }
+
+        if first_claim {
+            env.events().publish(
+                (symbol_short!("activate"), stream_id),
+                StreamActivatedEvent {
+                    stream_id,
+                    receiver: stream.receiver.clone(),
+                    timestamp: current_time,
+                },
+            );
+        }
+
+        if publish_claim {
+            env.events().publish(
+                (symbol_short!("claim"), stream_id),
+                StreamClaimEvent {
+                    stream_id,
+                    claimer: caller,
+                    destination,
+                    memo,
+                    amount,
+                    principal_amount,
+                    interest_amount,
+                    loyalty_bonus,
+                    total_claimed: stream.withdrawn_amount,
+                    timestamp: current_time,
+                },
+            );
+        }
+
+        Ok(amount)
+    }
+
+    /// Deduct the vault shares backing `principal` out of a stream's remaining
+    /// principal and return how many shares to redeem
+    fn take_vault_shares(
+        env: &Env,
+        stream_id: u64,
+        remaining_principal: i128,
+        principal: i128,
+    ) -> i128 {
+        let shares_key = DataKey::VaultShares(stream_id);
+        let shares: i128 = env.storage().instance().get(&shares_key).unwrap_or(0);
+        let redeemed_shares = shares * principal / remaining_principal;
+        env.storage()
+            .instance()
+            .set(&shares_key, &(shares - redeemed_shares));
+        redeemed_shares
+    }
+
+    /// Redeem vault shares backing `principal`. Returns the (principal,
+    /// interest) split of what the vault paid.
+    fn redeem_from_vault(
+        env: &Env,
+        vault: &Address,
+        shares: i128,
+        principal: i128,
+    ) -> Result<(i128, i128), Error> {
+        let received = vault::withdraw_from_vault(env, vault, shares)
+            .map_err(|_| Error::InsufficientBalance)?;
+
+        // A vault that lost value pays out less than the principal owed
+        let interest = (received - principal).max(0);
+        Ok((received - interest, interest))
+    }
+
+    /// Convert the USD value unlocked since the last withdrawal into tokens
+    /// at `price`. A token that lost value since creation can ask for more
+    /// than was deposited, so the payout is capped at the tokens the stream
+    /// still holds. Returns the payout and the new USD total unlocked.
+    fn usd_payout(
+        env: &Env,
+        stream_id: u64,
+        stream: &Stream,
+        unlocked: i128,
+        price: i128,
+    ) -> Result<(i128, i128), Error> {
+        let usd_withdrawn: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdWithdrawn(stream_id))
+            .unwrap_or(0);
+        let usd_unlocked = stream
+            .usd_amount
+            .checked_mul(unlocked)
+            .ok_or(Error::InvalidAmount)?
+            / stream.total_amount;
+        let usd_owed = usd_unlocked - usd_withdrawn;
+        if usd_owed <= 0 {
+            return Ok((0, usd_withdrawn));
+        }
+
+        let tokens =
+            oracle::calculate_token_amount(usd_owed, price).map_err(|_| Error::OracleFailed)?;
+        Ok((
+            tokens.min(stream.total_amount - stream.withdrawn_amount),
+            usd_unlocked,
+        ))
+    }
+
+    /// Append to a stream's withdrawal price history, dropping the oldest
+    /// entry once MAX_WITHDRAW_PRICES is reached
+    fn record_withdraw_price(env: &Env, stream_id: u64, timestamp: u64, price: i128) {
+        let key = DataKey::WithdrawPrices(stream_id);
+        let mut prices: Vec<(u64, i128)> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        if prices.len() >= MAX_WITHDRAW_PRICES {
+            prices.pop_front();
+        }
+        prices.push_back((timestamp, price));
+        env.storage().persistent().set(&key, &prices);
+    }
+
+    pub fn cancel(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != caller && stream.receiver != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+
+        Self::settle_cancellation(&env, stream_id, &mut stream)
+    }
+
+    /// Cancel only if `oracle` reports the stream's condition (e.g. a delivery
+    /// attestation) as not met. Lets escrow refunds be gated on an outside
+    /// attestation instead of either party's say-so.
+    pub fn conditional_cancel(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        oracle: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != caller && stream.receiver != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+
+        let condition_holds =
+            oracle::get_condition(&env, &oracle, stream_id).map_err(|_| Error::OracleFailed)?;
+        if condition_holds {
+            return Err(Error::ConditionNotMet);
+        }
+
+        Self::settle_cancellation(&env, stream_id, &mut stream)
+    }
+
+    /// Opt a stream into auto-settlement (sender only). Once it has been fully
+    /// vested for `auto_cancel_after` seconds without a single withdrawal,
+    /// anyone may call `auto_settle`. `None` opts back out.
+    pub fn set_auto_cancel(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        auto_cancel_after: Option<u64>,
+        return_to_sender: bool,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.sender != caller {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+
+        let key = DataKey::AutoCancel(stream_id);
+        match auto_cancel_after {
+            Some(grace) => env
+                .storage()
+                .instance()
+                .set(&key, &(grace, return_to_sender)),
+            None => env.storage().instance().remove(&key),
+        }
+
+        Ok(())
+    }
+
+    /// (grace seconds, refund to sender) if the stream opted into auto-settlement
+    pub fn get_auto_cancel(env: Env, stream_id: u64) -> Option<(u64, bool)> {
+        env.storage()
+            .instance()
+            .get(&DataKey::AutoCancel(stream_id))
+    }
+
+    /// Close an opted-in stream that was never withdrawn from once its grace
+    /// period after `end_time` has passed. Pays the whole balance to the
+    /// receiver, or back to the sender if the stream was set up that way.
+    pub fn auto_settle(env: Env, stream_id: u64) -> Result<i128, Error> {
+        Self::require_not_globally_paused(&env)?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+        Self::require_operable(&stream, false, false)?;
+
+        let (grace, return_to_sender): (u64, bool) = env
+            .storage()
+            .instance()
+            .get(&DataKey::AutoCancel(stream_id))
+            .ok_or(Error::AutoSettleUnavailable)?;
+        // Completed pauses push the vesting end back by the same amount
+        let settle_at = stream
+            .end_time
+            .saturating_add(stream.total_paused_duration)
+            .saturating_add(grace);
+        if stream.withdrawn_amount != 0 || env.ledger().timestamp() < settle_at {
+            return Err(Error::AutoSettleUnavailable);
+        }
+
+        let amount = stream.total_amount;
+        let (to_receiver, to_sender, recipient) = if return_to_sender {
+            (0, amount, stream.sender.clone())
+        } else {
+            (amount, 0, stream.receiver.clone())
+        };
+        // Nobody signs for this call, so a restricted payee just blocks it
+        if Self::validate_receiver(&env, &recipient).is_err() {
+            return Err(Error::AutoSettleUnavailable);
+        }
+        Self::close_stream(&env, stream_id, &mut stream, to_receiver, to_sender)?;
+        env.storage()
+            .instance()
+            .remove(&DataKey::AutoCancel(stream_id));
+
+        env.events().publish(
+            (symbol_short!("autosettl"), stream_id),
+            StreamAutoSettledEvent {
+                stream_id,
+                recipient,
+                amount,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(amount)
+    }
+
+    /// Mark a stream cancelled and pay the receiver its vested balance and the
+    /// sender the unvested remainder
+    fn settle_cancellation(env: &Env, stream_id: u64, stream: &mut Stream) -> Result<(), Error> {
+        let (to_receiver, to_sender) = Self::cancellation_split(env, stream);
+        Self::close_stream(env, stream_id, stream, to_receiver, to_sender)
+    }
+
+    /// (to_receiver, to_sender) amounts a cancellation would pay right now
+    fn cancellation_split(env: &Env, stream: &Stream) -> (i128, i128) {
+        let current_time = env.ledger().timestamp();
+        let unlocked = Self::calculate_unlocked(stream, current_time);
+        // USD-pegged payouts follow the price, so withdrawals may already be
+        // ahead of the token schedule; never split more than is left
+        let remaining = stream.total_amount - stream.withdrawn_amount;
+        let to_receiver = (unlocked - stream.withdrawn_amount).clamp(0, remaining);
+        (to_receiver, remaining - to_receiver)
+    }
+
+    /// Cut `reduce_by` from the unvested part of a stream and refund it to the
+    /// sender, keeping the rest streaming at the same rate. `end_time` moves
+    /// earlier in proportion to the new total.
+    pub fn cancel_partial(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        reduce_by: i128,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != caller {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, false, false)?;
+        // Removing the whole amount is a full cancellation
+        if reduce_by <= 0 || reduce_by >= stream.total_amount {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::reduce_unvested(&env, stream_id, &mut stream, reduce_by)
+    }
+
+    /// Give the sender standing consent to cut this stream's total down to no
+    /// less than `min_total` with `reduce_stream_amount` (receiver only).
+    /// Replaces any earlier consent.
+    pub fn approve_reduction(
+        env: Env,
+        receiver: Address,
+        stream_id: u64,
+        min_total: i128,
+    ) -> Result<(), Error> {
+        receiver.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if min_total < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ReductionFloor(stream_id), &min_total);
+
+        Ok(())
+    }
+
+    /// Withdraw the consent given by `approve_reduction` (receiver only)
+    pub fn revoke_reduction(env: Env, receiver: Address, stream_id: u64) -> Result<(), Error> {
+        receiver.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+        if stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&DataKey::ReductionFloor(stream_id));
+
+        Ok(())
+    }
+
+    pub fn get_reduction_floor(env: Env, stream_id: u64) -> Option<i128> {
+        env.storage()
+            .instance()
+            .get(&DataKey::ReductionFloor(stream_id))
+    }
+
+    /// Lower a stream's total to `new_total` under the receiver's consent,
+    /// refunding the difference to the sender. `new_total` can't go below
+    /// what has already vested or the floor the receiver approved.
+    pub fn reduce_stream_amount(
+        env: Env,
+        sender: Address,
+        stream_id: u64,
+        new_total: i128,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
+
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, false, false)?;
+        let min_total: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ReductionFloor(stream_id))
+            .ok_or(Error::Unauthorized)?;
+        if new_total < min_total || new_total <= 0 || new_total >= stream.total_amount {
+            return Err(Error::InvalidAmount);
+        }
+
+        let reduce_by = stream.total_amount - new_total;
+        Self::reduce_unvested(&env, stream_id, &mut stream, reduce_by)
+    }
+
+    /// Shared body of `cancel_partial` and `reduce_stream_amount`
+    fn reduce_unvested(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        reduce_by: i128,
+    ) -> Result<(), Error> {
+        let current_time = env.ledger().timestamp();
+        let locked = stream.total_amount - Self::calculate_unlocked(stream, current_time);
+        if reduce_by > locked {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let new_total = stream.total_amount - reduce_by;
+        let duration = (stream.end_time - stream.start_time) as i128;
+        let new_end_time = stream.start_time + (duration * new_total / stream.total_amount) as u64;
+
+        // Vault-backed funds come back by redeeming the matching shares, not
+        // out of the contract's balance, which belongs to other streams
+        let redeemed_shares = match stream.vault_address {
+            Some(ref vault) => {
+                let remaining_principal = stream.total_amount - stream.withdrawn_amount;
+                Self::adjust_vault_deposited(env, vault, -reduce_by)?;
+                Self::take_vault_shares(env, stream_id, remaining_principal, reduce_by)
+            }
+            None => 0,
+        };
+
+        stream.total_amount = new_total;
+        stream.end_time = new_end_time;
+        stream.cliff_time = stream.cliff_time.min(new_end_time);
+        stream.last_activity = current_time;
+        env.storage()
+            .instance()
+            .set(&(STREAM_COUNT, stream_id), stream);
+        Self::adjust_outstanding(env, &stream.token, -reduce_by)?;
+
+        // Any yield on the redeemed shares goes back with the principal
+        let refund = match stream.vault_address {
+            Some(ref vault) => {
+                let (principal, interest) =
+                    Self::redeem_from_vault(env, vault, redeemed_shares, reduce_by)?;
+                principal + interest
+            }
+            None => reduce_by,
+        };
+        let token_client = token::Client::new(env, &stream.token);
+        token_client.transfer(&env.current_contract_address(), &stream.sender, &refund);
+
+        env.events().publish(
+            (symbol_short!("reduce"), stream_id),
+            StreamReducedEvent {
+                stream_id,
+                sender: stream.sender.clone(),
+                reduced_by: reduce_by,
+                new_total,
+                new_end_time,
+                timestamp: current_time,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Preview how `cancel` would split the remaining funds at the current
+    /// time, as (to_receiver, to_sender), without changing any state
+    pub fn preview_cancel(env: Env, stream_id: u64) -> Result<(i128, i128), Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+
+        Ok(Self::cancellation_split(&env, &stream))
+    }
+
+    /// What the sender would get back by cancelling now; 0 for cancelled or
+    /// unknown streams. Read-only.
+    pub fn refundable_to_sender(env: Env, stream_id: u64) -> i128 {
+        let stream: Stream = match env.storage().instance().get(&(STREAM_COUNT, stream_id)) {
+            Some(stream) => stream,
+            None => return 0,
+        };
+
+        if stream.cancelled {
+            return 0;
+        }
+
+        let (_, to_sender) = Self::cancellation_split(&env, &stream);
+        to_sender
+    }
+
+    /// Last-resort refund for a USD-pegged stream whose oracle died before the
+    /// receiver could ever withdraw. Returns the full balance to the sender
+    /// once end_time plus STUCK_RECLAIM_GRACE has passed and needs an admin
+    /// co-signature.
+    pub fn sender_reclaim_stuck(
+        env: Env,
+        sender: Address,
+        admin: Address,
+        stream_id: u64,
+    ) -> Result<i128, Error> {
+        sender.require_auth();
+        admin.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if !stream.is_usd_pegged || stream.withdrawn_amount != 0 {
+            return Err(Error::StreamNotStuck);
+        }
+        if env.ledger().timestamp() < stream.end_time + STUCK_RECLAIM_GRACE {
+            return Err(Error::TimelockNotElapsed);
+        }
+
+        let refund = stream.total_amount;
+        Self::close_stream(&env, stream_id, &mut stream, 0, refund)?;
+
+        env.events().publish(
+            (symbol_short!("reclaim"), stream_id),
+            StreamCancelledEvent {
+                stream_id,
+                canceller: sender,
+                to_receiver: 0,
+                to_sender: refund,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(refund)
+    }
+
+    /// Set how long cancelled streams stay queryable before they can be
+    /// purged (Admin only)
+    pub fn set_purge_retention(env: Env, admin: Address, retention: u64) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PurgeRetention, &retention);
+
+        Ok(())
+    }
+
+    pub fn get_purge_retention(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::PurgeRetention)
+            .unwrap_or(DEFAULT_PURGE_RETENTION)
+    }
+
+    /// Delete a cancelled stream's storage once the retention period has
+    /// passed since cancellation (Admin only)
+    pub fn purge_cancelled(env: Env, admin: Address, stream_id: u64) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let key = (STREAM_COUNT, stream_id);
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if !stream.cancelled {
+            return Err(Error::StreamNotCancelled);
+        }
+        // Cancelled streams never change again, so last_activity is the
+        // cancellation time
+        let purgeable_at = stream.last_activity + Self::get_purge_retention(env.clone());
+        if env.ledger().timestamp() < purgeable_at {
+            return Err(Error::RetentionNotElapsed);
+        }
+
+        env.storage().instance().remove(&key);
+        env.storage().instance().remove(&(RECEIPT, stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::VaultShares(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::VotingDelegate(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::PendingReceiver(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::UsdWithdrawn(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::Activated(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::LoyaltyAccrual(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::AutoCancel(stream_id));
+        env.storage()
+            .instance()
+            .remove(&DataKey::StreamPriority(stream_id));
+        env.storage()
+            .instance()
+            .remove(&(DISPUTE_RAISED, stream_id));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::WithdrawPrices(stream_id));
+        Self::clear_stream_keys(&env, stream_id);
+        Self::unindex_receiver_history(&env, &stream.receiver, stream_id);
+
+        if stream.is_soulbound {
+            let mut soulbound_streams = Self::get_soulbound_streams(env.clone());
+            if let Some(index) = soulbound_streams.first_index_of(stream_id) {
+                soulbound_streams.remove(index);
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::SoulboundStreams, &soulbound_streams);
+            }
+        }
+
+        env.events().publish(
+            (symbol_short!("purge"), stream_id),
+            StreamPurgedEvent {
+                stream_id,
+                admin,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Mark a stream cancelled and pay out the given split of its remaining balance
+    fn close_stream(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        to_receiver: i128,
+        to_sender: i128,
+    ) -> Result<(), Error> {
+        // Redeem vault shares before writing anything, so a vault that fails
+        // leaves the stream untouched. The vault can't re-enter this contract.
+        let (pay_receiver, pay_sender) = match stream.vault_address {
+            Some(ref vault) => {
+                Self::redeem_remaining_shares(env, stream_id, vault, to_receiver, to_sender)?
+            }
+            None => (to_receiver, to_sender),
+        };
+
+        stream.cancelled = true;
+        stream.withdrawn_amount += to_receiver;
+        stream.last_activity = env.ledger().timestamp();
+        env.storage()
+            .instance()
+            .set(&(STREAM_COUNT, stream_id), stream);
+        Self::adjust_outstanding(env, &stream.token, -(to_receiver + to_sender))?;
+        Self::unindex_receiver_stream(env, &stream.receiver, stream_id);
+        Self::unindex_sender_stream(env, &stream.sender, stream_id);
+        if let Some(ref vault) = stream.vault_address {
+            Self::adjust_vault_deposited(env, vault, -(to_receiver + to_sender))?;
+        }
+
+        let token_client = token::Client::new(env, &stream.token);
+        if pay_receiver > 0 {
+            token_client.transfer(
+                &env.current_contract_address(),
+                &stream.receiver,
+                &pay_receiver,
+            );
+        }
+        if pay_sender > 0 {
+            token_client.transfer(&env.current_contract_address(), &stream.sender, &pay_sender);
+        }
+
+        Ok(())
+    }
+
+    /// Redeem all of a closing stream's vault shares and split what the vault
+    /// pays pro rata between `to_receiver` and `to_sender`, so both share any
+    /// yield or loss. Returns the amounts to pay each.
+    fn redeem_remaining_shares(
+        env: &Env,
+        stream_id: u64,
+        vault: &Address,
+        to_receiver: i128,
+        to_sender: i128,
+    ) -> Result<(i128, i128), Error> {
+        let shares_key = DataKey::VaultShares(stream_id);
+        let shares: i128 = env.storage().instance().get(&shares_key).unwrap_or(0);
+        if shares <= 0 {
+            return Ok((0, 0));
+        }
+
+        let owed = to_receiver + to_sender;
+        let (principal, interest) = Self::redeem_from_vault(env, vault, shares, owed)?;
+        env.storage().instance().remove(&shares_key);
+        let received = principal + interest;
+        let to_receiver = if owed > 0 {
+            received * to_receiver / owed
+        } else {
+            0
+        };
+        Ok((to_receiver, received - to_receiver))
+    }
+
+    fn calculate_unlocked(stream: &Stream, current_time: u64) -> i128 {
+        if current_time <= stream.start_time || current_time < stream.cliff_time {
+            return 0;
+        }
+
+        // Completed pauses plus the counted part of any ongoing pause. A stream
+        // paused after it had fully vested therefore still reports total_amount.
+        let paused = stream.total_paused_duration + Self::ongoing_pause(stream, current_time);
+        let effective_elapsed = (current_time - stream.start_time).saturating_sub(paused);
+        let duration = stream.end_time - stream.start_time;
+
+        if effective_elapsed >= duration {
+            return stream.total_amount;
+        }
+        if effective_elapsed == 0 {
+            return 0;
+        }
+
+        let effective_elapsed = effective_elapsed as i128;
+        let duration = duration as i128;
+
+        // Calculate base unlocked amount based on curve type
+        let unlocked = match stream.curve_type {
+            CurveType::Linear => (stream.total_amount * effective_elapsed) / duration,
+            CurveType::Exponential => {
+                // Use exponential curve with overflow protection
+                let adjusted_start = stream.start_time;
+                let adjusted_current = stream.start_time + effective_elapsed as u64;
+
+                math::calculate_exponential_unlocked(
+                    stream.total_amount,
+                    adjusted_start,
+                    stream.end_time,
+                    adjusted_current,
+                )
+                .unwrap_or((stream.total_amount * effective_elapsed) / duration)
+            }
+            CurveType::Logarithmic => math::calculate_logarithmic_unlocked(
+                stream.total_amount,
+                stream.start_time,
+                stream.end_time,
+                stream.start_time + effective_elapsed as u64,
+            )
+            .unwrap_or((stream.total_amount * effective_elapsed) / duration),
+            CurveType::PiecewiseLinear => Self::calculate_piecewise_unlocked(
+                stream,
+                stream.start_time + effective_elapsed as u64,
+            ),
+            CurveType::Step => {
+                let step = &stream.step_config;
+                if step.interval == 0 || step.tranches == 0 {
+                    (stream.total_amount * effective_elapsed) / duration
+                } else {
+                    let released =
+                        (effective_elapsed / step.interval as i128).min(step.tranches as i128);
+                    stream.total_amount * released / step.tranches as i128
+                }
+            }
+        };
+
+        // Other curves treat milestones as caps: nothing beyond the highest
+        // percentage reached so far unlocks, however far the curve has run
+        if stream.curve_type == CurveType::PiecewiseLinear || stream.milestones.is_empty() {
+            return unlocked;
+        }
+        let current = stream.start_time + effective_elapsed as u64;
+        let mut cap_percentage: u32 = 0;
+        for milestone in stream.milestones.iter() {
+            if current >= milestone.timestamp {
+                cap_percentage = cap_percentage.max(milestone.percentage);
+            }
+        }
+        unlocked.min(stream.total_amount * cap_percentage as i128 / 100)
+    }
+
+    /// Length of the current pause that freezes vesting. Time paused beyond
+    /// the stream's pause budget keeps vesting and is not counted.
+    fn ongoing_pause(stream: &Stream, current_time: u64) -> u64 {
+        if !stream.is_paused {
+            return 0;
+        }
+
+        let pause_duration = current_time.saturating_sub(stream.paused_time);
+        if stream.pause_budget > 0 {
+            let remaining_budget = stream
+                .pause_budget
+                .saturating_sub(stream.total_paused_duration);
+            return pause_duration.min(remaining_budget);
+        }
+        pause_duration
+    }
+
+    /// Interpolate linearly between consecutive milestone points.
+    /// `current_time` must already be adjusted for pauses.
+    fn calculate_piecewise_unlocked(stream: &Stream, current_time: u64) -> i128 {
+        let mut prev_time = stream.start_time;
+        let mut prev_percentage: u32 = 0;
+
+        let mut points = stream.milestones.clone();
+        points.push_back(Milestone {
+            timestamp: stream.end_time,
+            percentage: 100,
+        });
+
+        for point in points.iter() {
+            if current_time < point.timestamp {
+                let prev_amount = stream.total_amount * prev_percentage as i128 / 100;
+                let next_amount = stream.total_amount * point.percentage as i128 / 100;
+                let elapsed = (current_time - prev_time) as i128;
+                let span = (point.timestamp - prev_time) as i128;
+                return prev_amount + (next_amount - prev_amount) * elapsed / span;
+            }
+            prev_time = point.timestamp;
+            prev_percentage = point.percentage;
+        }
+
+        stream.total_amount
+    }
+
+    /// Check stream creation parameters without creating anything and return
+    /// every validation failure, in the order creation would report them
+    pub fn validate_stream_params(
+        env: Env,
+        receiver: Address,
+        token: Address,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        cliff_time: u64,
+        milestones: Vec<Milestone>,
+        curve_type: CurveType,
+        vault_address: Option<Address>,
+    ) -> Vec<Error> {
+        Self::collect_stream_errors(
+            &env,
+            &receiver,
+            &token,
+            total_amount,
+            start_time,
+            end_time,
+            cliff_time,
+            &milestones,
+            &curve_type,
+            &vault_address,
+        )
+    }
+
+    /// Proposed streams are linear with no cliff, milestones or vault, and
+    /// are checked like any other stream both when proposed and when executed
+    fn check_proposed_stream(env: &Env, stream: &ProposedStream) -> Result<(), Error> {
+        let errors = Self::collect_stream_errors(
+            env,
+            &stream.receiver,
+            &stream.token,
+            stream.total_amount,
+            stream.start_time,
+            stream.end_time,
+            stream.start_time,
+            &Vec::new(env),
+            &CurveType::Linear,
+            &None,
+        );
+        match errors.first() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Validation shared by stream creation and `validate_stream_params`
+    fn collect_stream_errors(
+        env: &Env,
+        receiver: &Address,
+        token: &Address,
+        total_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        cliff_time: u64,
+        milestones: &Vec<Milestone>,
+        curve_type: &CurveType,
+        vault_address: &Option<Address>,
+    ) -> Vec<Error> {
+        let mut errors = Vec::new(env);
+
+        if let Err(error) = Self::validate_receiver(env, receiver) {
+            errors.push_back(error);
+        }
+        if let Err(error) = Self::validate_token(env, token) {
+            errors.push_back(error);
+        }
+        // Backdated streams vest a chunk at once, so only within the policy
+        let (max_backdating, allow_backdating) = Self::get_backdating_policy(env.clone());
+        let too_old = !allow_backdating
+            && start_time < env.ledger().timestamp().saturating_sub(max_backdating);
+        if start_time >= end_time || cliff_time < start_time || cliff_time > end_time || too_old {
+            errors.push_back(Error::InvalidTimeRange);
+        } else if let Err(error) =
+            Self::validate_curve(curve_type, milestones, start_time, end_time)
+        {
+            errors.push_back(error);
+        }
+        if total_amount <= 0 {
+            errors.push_back(Error::InvalidAmount);
+        }
+        if let Some(vault) = vault_address {
+            if !Self::is_vault_approved(env.clone(), vault.clone()) {
+                errors.push_back(Error::Unauthorized);
+            }
+        }
+
+        errors
+    }
+
+    /// Check a milestone schedule the way creation will, for UIs to preflight.
+    /// Times are only checked against each other here; creation also needs
+    /// them strictly inside the stream's (start, end).
+    pub fn validate_milestones(
+        _env: Env,
+        total_amount: i128,
+        milestones: Vec<Milestone>,
+    ) -> Result<(), Error> {
+        Self::check_batch_size(milestones.len())?;
+        Self::check_milestones(&milestones, 0, u64::MAX)?;
+        if total_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        Ok(())
+    }
+
+    /// At most MAX_BATCH milestones; piecewise curves also need them to pass
+    /// `check_milestones`
+    fn validate_curve(
+        curve_type: &CurveType,
+        milestones: &Vec<Milestone>,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<(), Error> {
+        Self::check_batch_size(milestones.len())?;
+        if *curve_type == CurveType::Step {
+            return Err(Error::StepConfigMissing);
+        }
+        if *curve_type != CurveType::PiecewiseLinear {
+            return Ok(());
+        }
+
+        Self::check_milestones(milestones, start_time, end_time)
+    }
+
+    /// Milestones must sit strictly inside (start, end), in time order, with
+    /// non-decreasing percentages of at most 100
+    fn check_milestones(
+        milestones: &Vec<Milestone>,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<(), Error> {
+        let mut prev_time = start_time;
+        let mut prev_percentage: u32 = 0;
+        for milestone in milestones.iter() {
+            if milestone.timestamp <= prev_time || milestone.timestamp >= end_time {
+                return Err(Error::InvalidTimeRange);
+            }
+            if milestone.percentage < prev_percentage || milestone.percentage > 100 {
+                return Err(Error::InvalidAmount);
+            }
+            prev_time = milestone.timestamp;
+            prev_percentage = milestone.percentage;
+        }
+
+        Ok(())
+    }
+
+    // ========== RBAC Functions ==========
+
+    /// Grant a role to an address (Admin only)
+    pub fn grant_role(env: Env, admin: Address, target: Address, role: Role) {
+        admin.require_auth();
+
+        // Check if caller has Admin role
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            panic!("{}", Error::Unauthorized as u32);
+        }
+
+        // Grant the role
+        env.storage()
+            .instance()
+            .set(&DataKey::Role(target.clone(), role.clone()), &true);
+
+        // Emit event
+        env.events().publish((symbol_short!("grant"), target), role);
+    }
+
+    /// Revoke a role from an address (Admin only)
+    pub fn revoke_role(env: Env, admin: Address, target: Address, role: Role) {
+        admin.require_auth();
+
+        // Check if caller has Admin role
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return; // Error::Unauthorized;
+        }
+
+        // Revoke the role
+        env.storage()
+            .instance()
+            .remove(&DataKey::Role(target.clone(), role.clone()));
+
+        // Emit event
+        env.events()
+            .publish((symbol_short!("revoke"), target), role);
+    }
+
+    /// Check if an address has a specific role
+    pub fn check_role(env: Env, address: Address, role: Role) -> bool {
+        Self::has_role(&env, &address, role)
+    }
+
+    /// Internal helper to check if an address has a role
+    fn has_role(env: &Env, address: &Address, role: Role) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Role(address.clone(), role))
+            .unwrap_or(false)
+    }
+
+    /// Reject batch inputs that could exceed transaction resource limits
+    fn check_batch_size(len: u32) -> Result<(), Error> {
+        if len > MAX_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+        Ok(())
+    }
+
+    // ========== Vault Functions ==========
+
+    /// Allow a lending vault to be used for stream principal (Admin only)
+    pub fn approve_vault(env: Env, admin: Address, vault: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut vaults = Self::get_approved_vaults(env.clone());
+        if !vaults.contains(&vault) {
+            vaults.push_back(vault);
+            env.storage()
+                .instance()
+                .set(&DataKey::ApprovedVaults, &vaults);
+        }
+
+        Ok(())
+    }
+
+    /// Remove a vault from the approved list (Admin only).
+    /// Existing streams keep their vault; only new streams are affected.
+    pub fn revoke_vault(env: Env, admin: Address, vault: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut vaults = Self::get_approved_vaults(env.clone());
+        if let Some(index) = vaults.first_index_of(&vault) {
+            vaults.remove(index);
+            env.storage()
+                .instance()
+                .set(&DataKey::ApprovedVaults, &vaults);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_vault_approved(env: Env, vault: Address) -> bool {
+        Self::get_approved_vaults(env).contains(&vault)
+    }
+
+    pub fn get_approved_vaults(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::ApprovedVaults)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Limit the total stream principal a vault may hold (Admin only)
+    pub fn set_vault_cap(env: Env, admin: Address, vault: Address, cap: i128) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+        if cap < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultCap(vault), &cap);
+
+        Ok(())
+    }
+
+    pub fn get_vault_cap(env: Env, vault: Address) -> Option<i128> {
+        env.storage().instance().get(&DataKey::VaultCap(vault))
+    }
+
+    /// Principal currently deposited into a vault across all streams
+    pub fn get_vault_deposited(env: Env, vault: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultTotal(vault))
+            .unwrap_or(0)
+    }
+
+    /// Change how a stream's vault interest is split (INTEREST_TO_* bits).
+    /// Needs both sender and receiver, and only before any withdrawal, since
+    /// every withdrawal from a vault stream can realize interest.
+    pub fn update_interest_strategy(
+        env: Env,
+        stream_id: u64,
+        sender: Address,
+        receiver: Address,
+        new_strategy: u32,
+    ) -> Result<(), Error> {
+        sender.require_auth();
+        receiver.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.sender != sender || stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_operable(&stream, true, false)?;
+        if stream.withdrawn_amount > 0 {
+            return Err(Error::StreamAlreadyClaimed);
+        }
+        let all_bits = INTEREST_TO_SENDER | INTEREST_TO_RECEIVER | INTEREST_TO_PROTOCOL;
+        if new_strategy == 0 || new_strategy & !all_bits != 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        stream.interest_strategy = new_strategy;
+        stream.last_activity = env.ledger().timestamp();
+        env.storage().instance().set(&key, &stream);
+
+        Ok(())
+    }
+
+    /// Apply `delta` to a vault's deposited total, rejecting increases past its cap
+    fn adjust_vault_deposited(env: &Env, vault: &Address, delta: i128) -> Result<(), Error> {
+        let deposited = Self::get_vault_deposited(env.clone(), vault.clone()) + delta;
+
+        if delta > 0 {
+            if let Some(cap) = Self::get_vault_cap(env.clone(), vault.clone()) {
+                if deposited > cap {
+                    return Err(Error::VaultCapExceeded);
+                }
+            }
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultTotal(vault.clone()), &deposited.max(0));
+        Ok(())
+    }
+
+    // ========== Settlement Functions ==========
+
+    /// Allow a DEX router to be used for settlement swaps (Admin only)
+    pub fn approve_router(env: Env, admin: Address, router: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut routers = Self::get_approved_routers(env.clone());
+        if !routers.contains(&router) {
+            routers.push_back(router);
+            env.storage()
+                .instance()
+                .set(&DataKey::ApprovedRouters, &routers);
+        }
+
+        Ok(())
+    }
+
+    /// Remove a router from the approved list (Admin only).
+    /// Streams still settling through it can't withdraw until the receiver
+    /// clears or changes their settlement.
+    pub fn revoke_router(env: Env, admin: Address, router: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut routers = Self::get_approved_routers(env.clone());
+        if let Some(index) = routers.first_index_of(&router) {
+            routers.remove(index);
+            env.storage()
+                .instance()
+                .set(&DataKey::ApprovedRouters, &routers);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_router_approved(env: Env, router: Address) -> bool {
+        Self::get_approved_routers(env).contains(&router)
+    }
+
+    pub fn get_approved_routers(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::ApprovedRouters)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Swap every withdrawal into `settlement_token` through an approved
+    /// router. Each swap must pay at least `min_rate` units of
+    /// `settlement_token` per whole stream token (10^decimals units), so the
+    /// floor is set in the output token whatever the two tokens' prices.
+    pub fn set_settlement(
+        env: Env,
+        stream_id: u64,
+        receiver: Address,
+        settlement_token: Address,
+        router: Address,
+        min_rate: i128,
+    ) -> Result<(), Error> {
+        receiver.require_auth();
+
+        // A zero floor would leave the swap without slippage protection
+        if min_rate <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        if !Self::is_router_approved(env.clone(), router.clone()) {
+            return Err(Error::RouterNotApproved);
+        }
+
+        stream.settlement_token = Some(settlement_token);
+        stream.router = Some(router);
+        stream.settlement_min_rate = min_rate;
+        env.storage().instance().set(&key, &stream);
+
+        Ok(())
+    }
+
+    /// Stop converting withdrawals; the stream token is paid out as-is
+    pub fn clear_settlement(env: Env, stream_id: u64, receiver: Address) -> Result<(), Error> {
+        receiver.require_auth();
+
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.receiver != receiver {
+            return Err(Error::Unauthorized);
+        }
+
+        stream.settlement_token = None;
+        stream.router = None;
+        stream.settlement_min_rate = 0;
+        env.storage().instance().set(&key, &stream);
+
+        Ok(())
+    }
+
+    /// Send `amount` of the stream token to `destination`, swapping it into
+    /// the stream's settlement token first if one is set. A failed swap
+    /// reverts the whole withdrawal.
+    fn pay_out(
+        env: &Env,
+        stream_id: u64,
+        stream: &Stream,
+        destination: &Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        let (token_out, router) = match (&stream.settlement_token, &stream.router) {
+            (Some(token_out), Some(router)) => (token_out, router),
+            _ => {
+                token::Client::new(env, &stream.token).transfer(
+                    &env.current_contract_address(),
+                    destination,
+                    &amount,
+                );
+                return Ok(());
+            }
+        };
+
+        if !Self::is_router_approved(env.clone(), router.clone()) {
+            return Err(Error::RouterNotApproved);
+        }
+
+        let unit = 10i128.pow(token::Client::new(env, &stream.token).decimals());
+        let min_out = amount
+            .checked_mul(stream.settlement_min_rate)
+            .ok_or(Error::InvalidAmount)?
+            / unit;
+        let amount_out =
+            router::swap_via_router(env, router, &stream.token, token_out, amount, min_out)
+                .map_err(|_| Error::SwapFailed)?;
+        token::Client::new(env, token_out).transfer(
+            &env.current_contract_address(),
+            destination,
+            &amount_out,
+        );
+
+        env.events().publish(
+            (symbol_short!("settle"), stream_id),
+            SettlementEvent {
+                stream_id,
+                router: router.clone(),
+                token_in: stream.token.clone(),
+                token_out: token_out.clone(),
+                amount_in: amount,
+                amount_out,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
+    }
+
+    // ========== Voting Functions ==========
+
+    /// Choose which part of a stream's balance counts as voting power (Admin only)
+    pub fn set_voting_power_mode(
+        env: Env,
+        admin: Address,
+        mode: VotingPowerMode,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VotingPowerMode, &mode);
+
+        Ok(())
+    }
+
+    pub fn get_voting_power_mode(env: Env) -> VotingPowerMode {
+        env.storage()
+            .instance()
+            .get(&DataKey::VotingPowerMode)
+            .unwrap_or(VotingPowerMode::Unlocked)
+    }
+
+    /// Voting power of a stream under the contract-wide mode
+    pub fn get_voting_power(env: Env, stream_id: u64) -> Result<i128, Error> {
+        let mode = Self::get_voting_power_mode(env.clone());
+        Self::get_voting_power_with_mode(env, stream_id, mode)
+    }
+
+    /// Voting power of a stream under an explicit mode, for governance
+    /// proposals that weight power differently from the contract default
+    pub fn get_voting_power_with_mode(
+        env: Env,
+        stream_id: u64,
+        mode: VotingPowerMode,
+    ) -> Result<i128, Error> {
+        let stream: Stream = env
+            .storage()
+            .instance()
+            .get(&(STREAM_COUNT, stream_id))
+            .ok_or(Error::StreamNotFound)?;
+
+        Ok(voting::get_voting_power_for_mode(
+            &env,
+            &stream,
+            env.ledger().timestamp(),
+            mode,
+        ))
+    }
+
+    /// Delegate a stream's voting power (receipt owner only)
+    pub fn delegate_voting_power(
+        env: Env,
+        stream_id: u64,
+        caller: Address,
+        delegate: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        if !env.storage().instance().has(&(RECEIPT, stream_id)) {
+            return Err(Error::StreamNotFound);
+        }
+        if !voting::can_delegate(&env, stream_id, &caller) {
+            return Err(Error::NotReceiptOwner);
+        }
+
+        if let Some(previous) = Self::get_voting_delegate(env.clone(), stream_id) {
+            let key = DataKey::Delegations(previous);
+            let mut streams: Vec<u64> = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .unwrap_or(Vec::new(&env));
+            if let Some(index) = streams.first_index_of(stream_id) {
+                streams.remove(index);
+                env.storage().persistent().set(&key, &streams);
+            }
+        }
+
+        let key = DataKey::Delegations(delegate.clone());
+        let mut streams: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(&env));
+        streams.push_back(stream_id);
+        env.storage().persistent().set(&key, &streams);
+        env.storage()
+            .instance()
+            .set(&DataKey::VotingDelegate(stream_id), &delegate);
+
+        Ok(())
+    }
+
+    pub fn get_voting_delegate(env: Env, stream_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::VotingDelegate(stream_id))
+    }
+
+    /// Combined voting power of every stream delegated to `delegate`
+    pub fn get_delegated_voting_power(env: Env, delegate: Address) -> i128 {
+        let streams: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Delegations(delegate))
+            .unwrap_or(Vec::new(&env));
+
+        let mut total: i128 = 0;
+        for stream_id in streams.iter() {
+            total += Self::get_voting_power(env.clone(), stream_id).unwrap_or(0);
+        }
+        total
+    }
+
+    // ========== Fee Functions ==========
+
+    /// Set the protocol fee (TreasuryManager only). Setting 0 bps is an
+    /// explicit "no fee" and differs from leaving fees unconfigured.
+    pub fn set_fee_config(
+        env: Env,
+        manager: Address,
+        fee_bps: u32,
+        treasury: Address,
+    ) -> Result<(), Error> {
+        manager.require_auth();
+
+        if !Self::has_role(&env, &manager, Role::TreasuryManager) {
+            return Err(Error::Unauthorized);
+        }
+        if fee_bps > MAX_FEE_BPS {
+            return Err(Error::InvalidAmount);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeConfig, &FeeConfig { fee_bps, treasury });
+
+        Ok(())
+    }
+
+    /// Change the fee rate, keeping the configured treasury (TreasuryManager
+    /// only). Fails with InvalidAmount above MAX_FEE_BPS or while no treasury
+    /// has been set.
+    pub fn set_fee_bps(env: Env, manager: Address, fee_bps: u32) -> Result<(), Error> {
+        manager.require_auth();
+
+        if !Self::has_role(&env, &manager, Role::TreasuryManager) {
+            return Err(Error::Unauthorized);
+        }
+        if fee_bps > MAX_FEE_BPS {
+            return Err(Error::InvalidAmount);
+        }
+        let mut config: FeeConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::FeeConfig)
+            .ok_or(Error::InvalidAmount)?;
+
+        config.fee_bps = fee_bps;
+        env.storage().instance().set(&DataKey::FeeConfig, &config);
+
+        Ok(())
+    }
+
+    /// Change where fees are sent, keeping the current rate (TreasuryManager
+    /// only). On a fresh contract this configures an explicit 0 bps fee.
+    pub fn set_treasury(env: Env, manager: Address, treasury: Address) -> Result<(), Error> {
+        manager.require_auth();
+
+        if !Self::has_role(&env, &manager, Role::TreasuryManager) {
+            return Err(Error::Unauthorized);
+        }
+        let fee_bps = Self::get_fee_config(env.clone()).map_or(0, |config| config.fee_bps);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeConfig, &FeeConfig { fee_bps, treasury });
+
+        Ok(())
+    }
+
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        env.storage().instance().get(&DataKey::FeeConfig)
+    }
+
+    pub fn is_fee_configured(env: Env) -> bool {
+        env.storage().instance().has(&DataKey::FeeConfig)
+    }
+
+    /// Take the protocol fee out of a deposit the contract already holds and
+    /// return the remainder. Emits a fee event whenever fees are configured,
+    /// even at 0 bps, so audits can tell the two cases apart.
+    fn collect_fee(env: &Env, stream_id: u64, token: &Address, amount: i128) -> i128 {
+        let config: FeeConfig = match env.storage().instance().get(&DataKey::FeeConfig) {
+            Some(config) => config,
+            None => return amount,
+        };
+
+        let fee = math::calculate_fee(amount, config.fee_bps);
+        if fee > 0 {
+            let token_client = token::Client::new(env, token);
+            token_client.transfer(&env.current_contract_address(), &config.treasury, &fee);
+        }
+
+        env.events().publish(
+            (symbol_short!("fee"), stream_id),
+            FeeCollectedEvent {
+                stream_id,
+                token: token.clone(),
+                treasury: config.treasury,
+                fee_bps: config.fee_bps,
+                amount: fee,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        amount - fee
+    }
+
+    // ========== Loyalty Reward Functions ==========
+
+    /// Set the bonus paid on balances left unwithdrawn, in bps per
+    /// LOYALTY_PERIOD, up to MAX_LOYALTY_RATE_BPS. 0 (the default) turns
+    /// rewards off (Admin only).
+    pub fn configure_loyalty_reward(env: Env, admin: Address, rate_bps: u32) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+        if rate_bps > MAX_LOYALTY_RATE_BPS {
+            return Err(Error::InvalidAmount);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::LoyaltyRate, &rate_bps);
+
+        Ok(())
+    }
+
+    pub fn get_loyalty_rate(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LoyaltyRate)
+            .unwrap_or(0)
+    }
+
+    /// Add `amount` of `token` to the pool loyalty bonuses are paid from.
+    /// Bonuses stop once the pool runs dry.
+    pub fn fund_loyalty_pool(
+        env: Env,
+        funder: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        funder.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&funder, &env.current_contract_address(), &amount);
+
+        let pool = Self::get_loyalty_pool(env.clone(), token.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::LoyaltyPool(token), &(pool + amount));
+
+        Ok(())
+    }
+
+    pub fn get_loyalty_pool(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LoyaltyPool(token))
+            .unwrap_or(0)
+    }
+
+    /// Total loyalty bonus a stream has been paid so far
+    pub fn get_loyalty_earned(env: Env, stream_id: u64) -> i128 {
+        let accrual: Option<(u64, i128)> = env
+            .storage()
+            .instance()
+            .get(&DataKey::LoyaltyAccrual(stream_id));
+        accrual.map(|(_, earned)| earned).unwrap_or(0)
+    }
+
+    /// Bonus for the unlocked balance that sat unwithdrawn since the last
+    /// withdrawal. Gaps shorter than LOYALTY_MIN_IDLE earn nothing and the
+    /// bonus is capped by the token's reward pool. Moves the checkpoint to
+    /// `now` and deducts the bonus from the pool.
+    fn take_loyalty_bonus(env: &Env, stream_id: u64, stream: &Stream, now: u64) -> i128 {
+        let key = DataKey::LoyaltyAccrual(stream_id);
+        let (checkpoint, earned): (u64, i128) = env
+            .storage()
+            .instance()
+            .get(&key)
+            .unwrap_or((stream.start_time, 0));
+
+        let rate_bps = Self::get_loyalty_rate(env.clone());
+        let idle_time = now.saturating_sub(checkpoint);
+        let mut bonus = 0;
+        if rate_bps > 0 && idle_time >= LOYALTY_MIN_IDLE {
+            // Unlocked balances grow linearly between withdrawals, so the
+            // average idle balance is the midpoint of the two ends
+            let idle_start =
+                (Self::calculate_unlocked(stream, checkpoint) - stream.withdrawn_amount).max(0);
+            let idle_end = (Self::calculate_unlocked(stream, now) - stream.withdrawn_amount).max(0);
+            let average_idle = (idle_start + idle_end) / 2;
+            let pool = Self::get_loyalty_pool(env.clone(), stream.token.clone());
+            bonus = (average_idle * rate_bps as i128 * idle_time as i128
+                / (10_000 * LOYALTY_PERIOD as i128))
+                .min(pool);
+            if bonus > 0 {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::LoyaltyPool(stream.token.clone()), &(pool - bonus));
+            }
+        }
+
+        env.storage().instance().set(&key, &(now, earned + bonus));
+        bonus
+    }
+
+    // ========== Token Allowlist Functions ==========
+
+    /// Turn token allowlist enforcement on or off (Admin only)
+    pub fn set_allowlist_enabled(env: Env, admin: Address, enabled: bool) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::AllowlistEnabled, &enabled);
+
+        Ok(())
+    }
+
+    pub fn is_allowlist_enabled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowlistEnabled)
+            .unwrap_or(false)
+    }
+
+    /// Allow a token to be streamed while the allowlist is enabled (Admin only)
+    pub fn add_allowed_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut tokens = Self::get_allowed_tokens(env.clone());
+        if !tokens.contains(&token) {
+            tokens.push_back(token);
+            env.storage()
+                .instance()
+                .set(&DataKey::AllowedTokens, &tokens);
+        }
+
+        Ok(())
+    }
+
+    /// Remove a token from the allowlist (Admin only)
+    pub fn remove_allowed_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut tokens = Self::get_allowed_tokens(env.clone());
+        if let Some(index) = tokens.first_index_of(&token) {
+            tokens.remove(index);
+            env.storage()
+                .instance()
+                .set(&DataKey::AllowedTokens, &tokens);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_allowed_tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowedTokens)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Block a token from new streams, whether or not the allowlist is
+    /// enabled (Admin only)
+    pub fn block_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut tokens = Self::get_blocked_tokens(env.clone());
+        if !tokens.contains(&token) {
+            tokens.push_back(token);
+            env.storage()
+                .instance()
+                .set(&DataKey::BlockedTokens, &tokens);
+        }
+
+        Ok(())
+    }
+
+    /// Remove a token from the deny-list (Admin only)
+    pub fn unblock_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut tokens = Self::get_blocked_tokens(env.clone());
+        if let Some(index) = tokens.first_index_of(&token) {
+            tokens.remove(index);
+            env.storage()
+                .instance()
+                .set(&DataKey::BlockedTokens, &tokens);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_blocked_tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::BlockedTokens)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn is_token_blocked(env: Env, token: Address) -> bool {
+        Self::get_blocked_tokens(env).contains(&token)
+    }
+
+    /// Whether new streams may use `token`. Every token that isn't blocked is
+    /// allowed while the allowlist is disabled.
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        !Self::is_token_blocked(env.clone(), token.clone())
+            && (!Self::is_allowlist_enabled(env.clone())
+                || Self::get_allowed_tokens(env).contains(&token))
+    }
+
+    /// Halt withdrawals and new streams for a single token, e.g. during a
+    /// depeg, without pausing the whole contract (Admin only)
+    pub fn pause_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        Self::set_token_paused(&env, admin, token, true)
+    }
+
+    /// Resume activity for a paused token (Admin only)
+    pub fn unpause_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        Self::set_token_paused(&env, admin, token, false)
+    }
+
+    pub fn is_token_paused(env: Env, token: Address) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenPaused(token))
+            .unwrap_or(false)
+    }
+
+    fn set_token_paused(
+        env: &Env,
+        admin: Address,
+        token: Address,
+        paused: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        if paused {
+            env.storage()
+                .instance()
+                .set(&DataKey::TokenPaused(token), &true);
+        } else {
+            env.storage()
+                .instance()
+                .remove(&DataKey::TokenPaused(token));
+        }
+
+        Ok(())
+    }
+
+    fn validate_token(env: &Env, token: &Address) -> Result<(), Error> {
+        if Self::is_token_blocked(env.clone(), token.clone()) {
+            return Err(Error::TokenBlocked);
+        }
+        if Self::is_token_paused(env.clone(), token.clone()) {
+            return Err(Error::TokenPaused);
+        }
+        if !Self::is_token_allowed(env.clone(), token.clone()) {
+            return Err(Error::TokenNotAllowed);
+        }
+        Ok(())
+    }
+
+    // ========== OFAC Compliance Functions ==========
 
-            // Add to soulbound streams index
-            let mut soulbound_streams: Vec<u64> = env
-                .storage()
-                .persistent()
-                .get(&DataKey::SoulboundStreams)
-                .unwrap_or(Vec::new(&env));
-            soulbound_streams.push_back(stream_id);
-            env.storage()
-                .persistent()
-                .set(&DataKey::SoulboundStreams, &soulbound_streams);
+    /// Add an address to the restricted list (ComplianceOfficer only)
+    pub fn restrict_address(env: Env, officer: Address, address: Address) -> Result<(), Error> {
+        officer.require_auth();
+
+        if !Self::has_role(&env, &officer, Role::ComplianceOfficer) {
+            return Err(Error::Unauthorized);
         }
 
-        env.events().publish(
-            (symbol_short!("create"), sender.clone()),
-            StreamCreatedEvent {
-                stream_id,
-                sender: sender.clone(),
-                receiver: receiver.clone(),
-                token,
-                total_amount,
-                start_time,
-                end_time,
-                timestamp: env.ledger().timestamp(),
-            },
-        );
-        Self::mint_receipt(&env, stream_id, &receiver);
+        Self::add_restricted_address(&env, address);
 
-        Ok(stream_id)
+        Ok(())
     }
 
-    pub fn initialize(env: Env, admin: Address) {
-        admin.require_auth();
-        
-        // Set admin role
-        env.storage().instance().set(&DataKey::Admin, &admin);
-        
-        // Grant all roles to admin
-        env.storage().instance().set(&DataKey::Role(admin.clone(), Role::Admin), &true);
-        env.storage().instance().set(&DataKey::Role(admin.clone(), Role::Pauser), &true);
-        env.storage().instance().set(&DataKey::Role(admin.clone(), Role::TreasuryManager), &true);
-    }
+    /// Add several addresses to the restricted list at once
+    /// (ComplianceOfficer only)
+    pub fn restrict_addresses(
+        env: Env,
+        officer: Address,
+        addresses: Vec<Address>,
+    ) -> Result<(), Error> {
+        officer.require_auth();
 
-    pub fn grant_role(env: Env, admin: Address, target: Address, role: Role) {
-        admin.require_auth();
-        
-        // Check if admin has Admin role
-        let has_admin_role: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Role(admin, Role::Admin))
-            .unwrap_or(false);
-            
-        if !has_admin_role {
-            panic!("Unauthorized");
+        if !Self::has_role(&env, &officer, Role::ComplianceOfficer) {
+            return Err(Error::Unauthorized);
         }
-        
-        env.storage().instance().set(&DataKey::Role(target, role), &true);
-    }
+        Self::check_batch_size(addresses.len())?;
 
-    pub fn revoke_role(env: Env, admin: Address, target: Address, role: Role) {
-        admin.require_auth();
-        
-        // Check if admin has Admin role
-        let has_admin_role: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Role(admin, Role::Admin))
-            .unwrap_or(false);
-            
-        if !has_admin_role {
-            panic!("Unauthorized");
+        for address in addresses.iter() {
+            Self::add_restricted_address(&env, address);
         }
-        
-        env.storage().instance().remove(&DataKey::Role(target, role));
+
+        Ok(())
     }
 
-    pub fn check_role(env: Env, address: Address, role: Role) -> bool {
-        env.storage()
-            .instance()
-            .get(&DataKey::Role(address, role))
-            .unwrap_or(false)
+    /// Remove an address from the restricted list (ComplianceOfficer only)
+    pub fn unrestrict_address(env: Env, officer: Address, address: Address) -> Result<(), Error> {
+        officer.require_auth();
+
+        if !Self::has_role(&env, &officer, Role::ComplianceOfficer) {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut restricted = Self::get_restricted_addresses(env.clone());
+        if let Some(index) = restricted.first_index_of(&address) {
+            restricted.remove(index);
+            env.storage()
+                .instance()
+                .set(&RESTRICTED_ADDRESSES, &restricted);
+        }
+
+        Ok(())
     }
 
-    pub fn get_admin(env: Env) -> Address {
-        env.storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .expect("Admin not set")
+    pub fn is_address_restricted(env: Env, address: Address) -> bool {
+        Self::get_restricted_addresses(env).contains(&address)
     }
 
-    fn mint_receipt(env: &Env, stream_id: u64, owner: &Address) {
-        let receipt = StreamReceipt {
-            stream_id,
-            owner: owner.clone(),
-            minted_at: env.ledger().timestamp(),
-        };
+    pub fn get_restricted_addresses(env: Env) -> Vec<Address> {
         env.storage()
             .instance()
-            .set(&(RECEIPT, stream_id), &receipt);
+            .get(&RESTRICTED_ADDRESSES)
+            .unwrap_or(Vec::new(&env))
     }
 
-    pub fn get_stream(env: Env, stream_id: u64) -> Result<Stream, Error> {
-        env.storage()
-            .instance()
-            .get(&(STREAM_COUNT, stream_id))
-            .ok_or(Error::StreamNotFound)
+    /// Every role `address` holds, in declaration order
+    pub fn get_address_roles(env: Env, address: Address) -> Vec<Role> {
+        let mut roles = Vec::new(&env);
+        for role in [
+            Role::Admin,
+            Role::Pauser,
+            Role::TreasuryManager,
+            Role::ComplianceOfficer,
+            Role::Guardian,
+        ] {
+            if Self::has_role(&env, &address, role.clone()) {
+                roles.push_back(role);
+            }
+        }
+        roles
     }
 
-    pub fn get_soulbound_streams(env: Env) -> Vec<u64> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::SoulboundStreams)
-            .unwrap_or(Vec::new(&env))
+    /// Roles, restriction status and active stream counts for `address`
+    pub fn get_address_status(env: Env, address: Address) -> AddressStatus {
+        AddressStatus {
+            roles: Self::get_address_roles(env.clone(), address.clone()),
+            restricted: Self::is_address_restricted(env.clone(), address.clone()),
+            sending_streams: Self::get_sender_streams(env.clone(), address.clone()).len(),
+            receiving_streams: Self::get_receiver_streams(env, address).len(),
+        }
     }
 
-    pub fn transfer_receiver(
-        env: Env,
-        stream_id: u64,
-        caller: Address,
-        new_receiver: Address,
-    ) -> Result<(), Error> {
-        caller.require_auth();
+    /// Return everything the contract still holds for a stream to its sender
+    /// and cancel it (ComplianceOfficer only, streams with clawback enabled)
+    pub fn clawback_stream(env: Env, officer: Address, stream_id: u64) -> Result<i128, Error> {
+        officer.require_auth();
+
+        if !Self::has_role(&env, &officer, Role::ComplianceOfficer) {
+            return Err(Error::Unauthorized);
+        }
 
-        let stream_key = (STREAM_COUNT, stream_id);
         let mut stream: Stream = env
             .storage()
             .instance()
-            .get(&stream_key)
+            .get(&(STREAM_COUNT, stream_id))
             .ok_or(Error::StreamNotFound)?;
 
-        // SOULBOUND CHECK FIRST
-        if stream.is_soulbound {
-            return Err(Error::StreamIsSoulbound);
-        }
-
-        // Authorization check: only sender can transfer receiver
-        if stream.sender != caller {
+        if !stream.clawback_enabled {
             return Err(Error::Unauthorized);
         }
-
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
         }
 
-        // Update receiver
-        stream.receiver = new_receiver.clone();
-        env.storage().instance().set(&stream_key, &stream);
+        // Already-withdrawn funds left the contract and are not clawed back
+        let amount = stream.total_amount - stream.withdrawn_amount;
+        Self::close_stream(&env, stream_id, &mut stream, 0, amount)?;
+
+        env.events().publish(
+            (symbol_short!("clawback"), stream_id),
+            ClawbackEvent {
+                stream_id,
+                officer,
+                amount_clawed: amount,
+                issuer: stream.sender.clone(),
+                reason: None,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(amount)
+    }
+
+    fn add_restricted_address(env: &Env, address: Address) {
+        let mut restricted = Self::get_restricted_addresses(env.clone());
+        if !restricted.contains(&address) {
+            restricted.push_back(address);
+            env.storage()
+                .instance()
+                .set(&RESTRICTED_ADDRESSES, &restricted);
+        }
+    }
 
+    fn validate_receiver(env: &Env, receiver: &Address) -> Result<(), Error> {
+        if Self::is_address_restricted(env.clone(), receiver.clone()) {
+            return Err(Error::AddressRestricted);
+        }
         Ok(())
     }
 
-    /// Top up an active stream with additional funds
-    pub fn top_up_stream(
+    // ========== Dispute Functions ==========
+
+    /// Set the arbiter applied to streams created without one (Admin only)
+    pub fn set_default_arbiter(env: Env, admin: Address, arbiter: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        if !Self::has_role(&env, &admin, Role::Admin) {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::DefaultArbiter, &arbiter);
+
+        Ok(())
+    }
+
+    pub fn get_default_arbiter(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::DefaultArbiter)
+    }
+
+    /// Override the arbiter for a single stream (sender, with the
+    /// receiver's consent). Neither party may arbitrate its own stream.
+    pub fn set_arbiter(
         env: Env,
         stream_id: u64,
         sender: Address,
-        amount: i128,
+        arbiter: Address,
     ) -> Result<(), Error> {
         sender.require_auth();
 
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
-        }
-
         let key = (STREAM_COUNT, stream_id);
         let mut stream: Stream = env
             .storage()
@@ -561,48 +5288,61 @@ impl StellarStreamContract {
         if stream.sender != sender {
             return Err(Error::Unauthorized);
         }
-
+        // The arbiter can claw back vested funds, so the receiver must agree
+        stream.receiver.require_auth();
+        if arbiter == stream.sender || arbiter == stream.receiver {
+            return Err(Error::Unauthorized);
+        }
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
         }
-
-        let current_time = env.ledger().timestamp();
-        if current_time >= stream.end_time {
-            return Err(Error::StreamEnded);
+        if stream.is_frozen {
+            return Err(Error::StreamFrozen);
         }
 
-        // Transfer tokens from sender
-        let token_client = token::Client::new(&env, &stream.token);
-        token_client.transfer(&sender, &env.current_contract_address(), &amount);
+        stream.arbiter = Some(arbiter);
+        env.storage().instance().set(&key, &stream);
 
-        // Calculate new end time based on flow rate
-        let total_duration = stream.end_time.saturating_sub(stream.start_time);
-        let flow_rate = stream.total_amount / total_duration as i128;
+        Ok(())
+    }
 
-        let new_total = stream.total_amount + amount;
-        let additional_duration = amount / flow_rate;
-        let new_end_time = stream.end_time + additional_duration as u64;
+    /// Freeze a stream pending dispute resolution (stream arbiter only)
+    pub fn freeze_stream(env: Env, stream_id: u64, arbiter: Address) -> Result<(), Error> {
+        arbiter.require_auth();
 
-        stream.total_amount = new_total;
-        stream.end_time = new_end_time;
+        let key = (STREAM_COUNT, stream_id);
+        let mut stream: Stream = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(Error::StreamNotFound)?;
+
+        if stream.arbiter != Some(arbiter.clone()) {
+            return Err(Error::Unauthorized);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+
+        stream.is_frozen = true;
+        stream.last_activity = env.ledger().timestamp();
         env.storage().instance().set(&key, &stream);
 
         env.events().publish(
-            (symbol_short!("topup"), stream_id),
-            types::StreamToppedUpEvent {
+            (symbol_short!("freeze"), stream_id),
+            StreamFrozenEvent {
                 stream_id,
-                sender,
-                amount,
-                new_total,
-                new_end_time,
-                timestamp: current_time,
+                arbiter,
+                timestamp: env.ledger().timestamp(),
             },
         );
 
         Ok(())
     }
 
-    pub fn pause_stream(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+    /// Raise a dispute on a stream that has an arbiter, freezing it until the
+    /// arbiter resolves it (sender or receipt owner)
+    pub fn raise_dispute(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
         caller.require_auth();
 
         let key = (STREAM_COUNT, stream_id);
@@ -612,25 +5352,49 @@ impl StellarStreamContract {
             .get(&key)
             .ok_or(Error::StreamNotFound)?;
 
-        if stream.sender != caller {
+        if caller != stream.sender && caller != stream.receipt_owner {
             return Err(Error::Unauthorized);
         }
-        if stream.cancelled {
-            return Err(Error::AlreadyCancelled);
-        }
-        if stream.is_paused {
-            return Ok(());
+        let arbiter = stream.arbiter.clone().ok_or(Error::Unauthorized)?;
+        // A stream the arbiter froze on its own can still be taken to a ruling
+        Self::require_operable(&stream, true, true)?;
+        if env.storage().instance().has(&(DISPUTE_RAISED, stream_id)) {
+            return Err(Error::StreamFrozen);
         }
 
-        stream.is_paused = true;
-        stream.paused_time = env.ledger().timestamp();
+        stream.is_frozen = true;
+        stream.last_activity = env.ledger().timestamp();
         env.storage().instance().set(&key, &stream);
+        env.storage()
+            .instance()
+            .set(&(DISPUTE_RAISED, stream_id), &caller);
+
+        env.events().publish(
+            (symbol_short!("dispute"), stream_id),
+            DisputeRaisedEvent {
+                stream_id,
+                raised_by: caller,
+                arbiter,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
 
         Ok(())
     }
 
-    pub fn unpause_stream(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
-        caller.require_auth();
+    /// Settle a disputed stream, releasing `release_to_receiver` to the receiver
+    /// and refunding `refund_to_sender`; the two must add up to the remaining
+    /// balance (stream arbiter only, after `raise_dispute`)
+    pub fn resolve_dispute(
+        env: Env,
+        stream_id: u64,
+        arbiter: Address,
+        release_to_receiver: i128,
+        refund_to_sender: i128,
+    ) -> Result<(), Error> {
+        arbiter.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
 
         let key = (STREAM_COUNT, stream_id);
         let mut stream: Stream = env
@@ -639,29 +5403,63 @@ impl StellarStreamContract {
             .get(&key)
             .ok_or(Error::StreamNotFound)?;
 
-        if stream.sender != caller {
+        if stream.arbiter != Some(arbiter.clone()) {
             return Err(Error::Unauthorized);
         }
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
         }
-        if !stream.is_paused {
-            return Ok(());
+        // Only a raised dispute gives the arbiter a say over the funds; a
+        // freeze_stream alone is not one
+        let dispute_key = (DISPUTE_RAISED, stream_id);
+        if !stream.is_frozen || !env.storage().instance().has(&dispute_key) {
+            return Err(Error::NoPendingChange);
+        }
+        let remaining = stream.total_amount - stream.withdrawn_amount;
+        if release_to_receiver < 0
+            || refund_to_sender < 0
+            || release_to_receiver + refund_to_sender != remaining
+        {
+            return Err(Error::InvalidAmount);
         }
 
-        let current_time = env.ledger().timestamp();
-        let pause_duration = current_time - stream.paused_time;
-        stream.total_paused_duration += pause_duration;
-        stream.is_paused = false;
-        stream.paused_time = 0;
+        stream.is_frozen = false;
+        stream.last_activity = env.ledger().timestamp();
+        env.storage().instance().remove(&dispute_key);
+        Self::close_stream(
+            &env,
+            stream_id,
+            &mut stream,
+            release_to_receiver,
+            refund_to_sender,
+        )?;
 
-        env.storage().instance().set(&key, &stream);
+        env.events().publish(
+            (symbol_short!("resolve"), stream_id),
+            DisputeResolvedEvent {
+                stream_id,
+                arbiter,
+                to_sender: refund_to_sender,
+                to_receiver: release_to_receiver,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
 
         Ok(())
     }
 
-    pub fn withdraw(env: Env, stream_id: u64, caller: Address) -> Result<i128, Error> {
-        caller.require_auth();
+    /// Rule on a raised dispute (stream arbiter only). In the receiver's favour
+    /// the stream is unfrozen and keeps streaming; otherwise it is settled like
+    /// `cancel`, vested funds to the receiver and the rest back to the sender.
+    pub fn rule_on_dispute(
+        env: Env,
+        stream_id: u64,
+        arbiter: Address,
+        release_to_receiver: bool,
+    ) -> Result<(), Error> {
+        arbiter.require_auth();
+
+        Self::require_not_globally_paused(&env)?;
 
         let key = (STREAM_COUNT, stream_id);
         let mut stream: Stream = env
@@ -670,197 +5468,294 @@ impl StellarStreamContract {
             .get(&key)
             .ok_or(Error::StreamNotFound)?;
 
-        if stream.receiver != caller {
+        if stream.arbiter != Some(arbiter.clone()) {
             return Err(Error::Unauthorized);
         }
-
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
         }
-        if stream.is_paused {
-            return Err(Error::StreamPaused);
-        }
-
-        let current_time = env.ledger().timestamp();
-        let unlocked = Self::calculate_unlocked(&stream, current_time);
-        let to_withdraw = unlocked - stream.withdrawn_amount;
-
-        if to_withdraw <= 0 {
-            return Err(Error::InsufficientBalance);
+        let dispute_key = (DISPUTE_RAISED, stream_id);
+        if !stream.is_frozen || !env.storage().instance().has(&dispute_key) {
+            return Err(Error::NoPendingChange);
         }
 
-        stream.withdrawn_amount += to_withdraw;
-        env.storage().instance().set(&key, &stream);
+        stream.is_frozen = false;
+        stream.last_activity = env.ledger().timestamp();
+        env.storage().instance().remove(&dispute_key);
+        let (to_receiver, to_sender) = if release_to_receiver {
+            env.storage().instance().set(&key, &stream);
+            (0, 0)
+        } else {
+            let (to_receiver, to_sender) = Self::cancellation_split(&env, &stream);
+            Self::close_stream(&env, stream_id, &mut stream, to_receiver, to_sender)?;
+            (to_receiver, to_sender)
+        };
 
-        let token_client = token::Client::new(&env, &stream.token);
-        token_client.transfer(
-            &env.current_contract_address(),
-            &stream.receiver,
-            &to_withdraw,
+        env.events().publish(
+            (symbol_short!("resolve"), stream_id),
+            DisputeResolvedEvent {
+                stream_id,
+                arbiter,
+                to_sender,
+                to_receiver,
+                timestamp: env.ledger().timestamp(),
+            },
         );
 
-        Ok(to_withdraw)
+        Ok(())
     }
 
-    pub fn cancel(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
-        caller.require_auth();
+    // ========== Emergency Drain Functions ==========
 
-        let key = (STREAM_COUNT, stream_id);
-        let mut stream: Stream = env
-            .storage()
-            .instance()
-            .get(&key)
-            .ok_or(Error::StreamNotFound)?;
+    /// Start the timelock for an emergency drain (Guardian only)
+    pub fn schedule_emergency_drain(env: Env, guardian: Address) -> Result<u64, Error> {
+        guardian.require_auth();
 
-        if stream.sender != caller && stream.receiver != caller {
+        if !Self::has_role(&env, &guardian, Role::Guardian) {
             return Err(Error::Unauthorized);
         }
-        if stream.cancelled {
-            return Err(Error::AlreadyCancelled);
+        if Self::is_disabled(env.clone()) {
+            return Err(Error::ContractDisabled);
         }
 
-        let current_time = env.ledger().timestamp();
-        let unlocked = Self::calculate_unlocked(&stream, current_time);
-        let to_receiver = unlocked - stream.withdrawn_amount;
-        let to_sender = stream.total_amount - unlocked;
+        let executable_at = env.ledger().timestamp() + EMERGENCY_DRAIN_DELAY;
+        env.storage()
+            .instance()
+            .set(&DataKey::DrainScheduled, &executable_at);
+        env.storage().instance().set(&DataKey::DrainCursor, &0u64);
+        env.storage().instance().remove(&DRAIN_SKIPPED);
 
-        stream.cancelled = true;
-        stream.withdrawn_amount = unlocked;
-        env.storage().instance().set(&key, &stream);
+        Ok(executable_at)
+    }
 
-        let token_client = token::Client::new(&env, &stream.token);
-        if to_receiver > 0 {
-            token_client.transfer(
-                &env.current_contract_address(),
-                &stream.receiver,
-                &to_receiver,
-            );
+    /// Abort a scheduled drain before it completes (Guardian only)
+    pub fn cancel_emergency_drain(env: Env, guardian: Address) -> Result<(), Error> {
+        guardian.require_auth();
+
+        if !Self::has_role(&env, &guardian, Role::Guardian) {
+            return Err(Error::Unauthorized);
         }
-        if to_sender > 0 {
-            token_client.transfer(&env.current_contract_address(), &stream.sender, &to_sender);
+        if !env.storage().instance().has(&DataKey::DrainScheduled) {
+            return Err(Error::DrainNotScheduled);
         }
 
+        env.storage().instance().remove(&DataKey::DrainScheduled);
+        env.storage().instance().remove(&DataKey::DrainCursor);
+
         Ok(())
     }
 
-    fn calculate_unlocked(stream: &Stream, current_time: u64) -> i128 {
-        if current_time <= stream.start_time {
-            return 0;
-        }
+    /// Cancel the next batch of active streams once the timelock has elapsed,
+    /// paying each party its vested/unvested split. Streams that can't be
+    /// settled, e.g. because their vault won't redeem, are left open and
+    /// listed in `get_drain_skipped`. Returns true when every stream has been
+    /// processed and the contract is disabled.
+    pub fn emergency_drain(env: Env, guardian: Address) -> Result<bool, Error> {
+        guardian.require_auth();
 
-        let mut effective_time = current_time;
-        if stream.is_paused {
-            effective_time = stream.paused_time;
+        if !Self::has_role(&env, &guardian, Role::Guardian) {
+            return Err(Error::Unauthorized);
         }
-
-        let adjusted_end = stream.end_time + stream.total_paused_duration;
-        if effective_time >= adjusted_end {
-            return stream.total_amount;
+        if Self::is_disabled(env.clone()) {
+            return Err(Error::ContractDisabled);
         }
 
-        let elapsed = (effective_time - stream.start_time) as i128;
-        let paused = stream.total_paused_duration as i128;
-        let effective_elapsed = elapsed - paused;
-
-        if effective_elapsed <= 0 {
-            return 0;
+        let executable_at: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DrainScheduled)
+            .ok_or(Error::DrainNotScheduled)?;
+        if env.ledger().timestamp() < executable_at {
+            return Err(Error::TimelockNotElapsed);
         }
 
-        let duration = (stream.end_time - stream.start_time) as i128;
-
-        // Calculate base unlocked amount based on curve type
-        match stream.curve_type {
-            CurveType::Linear => (stream.total_amount * effective_elapsed) / duration,
-            CurveType::Exponential => {
-                // Use exponential curve with overflow protection
-                let adjusted_start = stream.start_time;
-                let adjusted_current = stream.start_time + effective_elapsed as u64;
+        let stream_count: u64 = env.storage().instance().get(&STREAM_COUNT).unwrap_or(0);
+        let cursor: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DrainCursor)
+            .unwrap_or(0);
+        let batch_end = (cursor + MAX_BATCH as u64).min(stream_count);
 
-                math::calculate_exponential_unlocked(
-                    stream.total_amount,
-                    adjusted_start,
-                    stream.end_time,
-                    adjusted_current,
-                )
-                .unwrap_or((stream.total_amount * effective_elapsed) / duration)
+        let mut skipped: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&DRAIN_SKIPPED)
+            .unwrap_or(Vec::new(&env));
+        for stream_id in cursor..batch_end {
+            let stream: Option<Stream> = env.storage().instance().get(&(STREAM_COUNT, stream_id));
+            if let Some(mut stream) = stream {
+                // A failed settlement writes nothing, so the stream can be
+                // skipped without blocking the rest of the drain
+                if !stream.cancelled
+                    && Self::settle_cancellation(&env, stream_id, &mut stream).is_err()
+                {
+                    skipped.push_back(stream_id);
+                }
             }
         }
+        env.storage().instance().set(&DRAIN_SKIPPED, &skipped);
 
-        let duration = (stream.end_time - stream.start_time) as i128;
-        (total_usd * effective_elapsed) / duration
-    }
+        let completed = batch_end >= stream_count;
+        if completed {
+            env.storage().instance().set(&DataKey::Disabled, &true);
+            env.storage().instance().remove(&DataKey::DrainScheduled);
+            env.storage().instance().remove(&DataKey::DrainCursor);
+        } else {
+            env.storage()
+                .instance()
+                .set(&DataKey::DrainCursor, &batch_end);
+        }
 
-    // ========== RBAC Functions ==========
+        env.events().publish(
+            (symbol_short!("drain"), guardian.clone()),
+            EmergencyDrainEvent {
+                guardian,
+                settled_up_to: batch_end,
+                completed,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
 
-    /// Grant a role to an address (Admin only)
-    pub fn grant_role(env: Env, admin: Address, target: Address, role: Role) {
-        admin.require_auth();
+        Ok(completed)
+    }
 
-        // Check if caller has Admin role
-        if !Self::has_role(&env, &admin, Role::Admin) {
-            panic!("{}", Error::Unauthorized as u32);
-        }
+    pub fn get_drain_scheduled_at(env: Env) -> Option<u64> {
+        env.storage().instance().get(&DataKey::DrainScheduled)
+    }
 
-        // Grant the role
+    /// IDs of streams the current or last drain could not settle
+    pub fn get_drain_skipped(env: Env) -> Vec<u64> {
         env.storage()
             .instance()
-            .set(&DataKey::Role(target.clone(), role.clone()), &true);
+            .get(&DRAIN_SKIPPED)
+            .unwrap_or(Vec::new(&env))
+    }
 
-        // Emit event
-        env.events().publish((symbol_short!("grant"), target), role);
+    /// Whether the contract has been shut down by an emergency drain
+    pub fn is_disabled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Disabled)
+            .unwrap_or(false)
     }
 
-    /// Revoke a role from an address (Admin only)
-    pub fn revoke_role(env: Env, admin: Address, target: Address, role: Role) {
-        admin.require_auth();
+    fn require_enabled(env: &Env) -> Result<(), Error> {
+        if Self::is_disabled(env.clone()) {
+            return Err(Error::ContractDisabled);
+        }
+        Ok(())
+    }
 
-        // Check if caller has Admin role
-        if !Self::has_role(&env, &admin, Role::Admin) {
-            return; // Error::Unauthorized;
+    /// Stop or resume new streams, top-ups and stream proposals while still
+    /// letting receivers withdraw and senders cancel (Guardian only)
+    pub fn set_withdraw_only(env: Env, guardian: Address, enabled: bool) -> Result<(), Error> {
+        guardian.require_auth();
+
+        if !Self::has_role(&env, &guardian, Role::Guardian) {
+            return Err(Error::Unauthorized);
         }
 
-        // Revoke the role
+        if enabled {
+            env.storage().instance().set(&DataKey::WithdrawOnly, &true);
+        } else {
+            env.storage().instance().remove(&DataKey::WithdrawOnly);
+        }
+
+        env.events().publish(
+            (symbol_short!("wdonly"), guardian.clone()),
+            WithdrawOnlyEvent {
+                guardian,
+                enabled,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_withdraw_only(env: Env) -> bool {
         env.storage()
             .instance()
-            .remove(&DataKey::Role(target.clone(), role.clone()));
+            .get(&DataKey::WithdrawOnly)
+            .unwrap_or(false)
+    }
 
-        // Emit event
-        env.events()
-            .publish((symbol_short!("revoke"), target), role);
+    /// Entry points that bring new funds into the contract
+    fn require_accepting_funds(env: &Env) -> Result<(), Error> {
+        Self::require_enabled(env)?;
+        Self::require_not_globally_paused(env)?;
+        if Self::is_withdraw_only(env.clone()) {
+            return Err(Error::WithdrawOnly);
+        }
+        Ok(())
     }
 
-    /// Check if an address has a specific role
-    pub fn check_role(env: Env, address: Address, role: Role) -> bool {
-        Self::has_role(&env, &address, role)
+    /// Halt or resume creating, topping up, withdrawing from and cancelling
+    /// streams across the whole contract (Guardian only). Emergency drains
+    /// and governance keep working while paused.
+    pub fn set_global_pause(env: Env, guardian: Address, paused: bool) -> Result<(), Error> {
+        guardian.require_auth();
+
+        if !Self::has_role(&env, &guardian, Role::Guardian) {
+            return Err(Error::Unauthorized);
+        }
+
+        if paused {
+            env.storage().instance().set(&DataKey::GlobalPaused, &true);
+        } else {
+            env.storage().instance().remove(&DataKey::GlobalPaused);
+        }
+
+        env.events().publish(
+            (symbol_short!("gpause"), guardian.clone()),
+            GlobalPauseEvent {
+                guardian,
+                paused,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        Ok(())
     }
 
-    /// Internal helper to check if an address has a role
-    fn has_role(env: &Env, address: &Address, role: Role) -> bool {
+    pub fn is_globally_paused(env: Env) -> bool {
         env.storage()
             .instance()
-            .get(&DataKey::Role(address.clone(), role))
+            .get(&DataKey::GlobalPaused)
             .unwrap_or(false)
     }
 
-    // ========== Contract Upgrade Functions ==========
-
-    /// Upgrade the contract to a new WASM hash
-    /// Only addresses with Admin role can perform this operation
-    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: soroban_sdk::BytesN<32>) {
-        admin.require_auth();
+    /// (paused, frozen) for frontends to grey out actions up front. Paused
+    /// is the global pause; frozen means no new funds are accepted, either
+    /// because of withdraw-only mode or because an emergency drain disabled
+    /// the contract.
+    pub fn get_operational_state(env: Env) -> (bool, bool) {
+        let paused = Self::is_globally_paused(env.clone());
+        let frozen = Self::is_withdraw_only(env.clone()) || Self::is_disabled(env);
+        (paused, frozen)
+    }
 
-        // Check if caller has Admin role
-        if !Self::has_role(&env, &admin, Role::Admin) {
-            return; // Error::Unauthorized;
+    fn require_not_globally_paused(env: &Env) -> Result<(), Error> {
+        if Self::is_globally_paused(env.clone()) {
+            return Err(Error::StreamPaused);
         }
+        Ok(())
+    }
+
+    // ========== Contract Upgrade Functions ==========
 
+    /// Upgrade the contract to a new WASM hash
+    /// Only reachable through an Upgrade proposal approved by at least
+    /// MIN_UPGRADE_APPROVALS admins
+    fn apply_upgrade(env: &Env, new_wasm_hash: BytesN<32>) {
         // Update the contract WASM
         env.deployer()
             .update_current_contract_wasm(new_wasm_hash.clone());
 
         // Emit upgrade event with new WASM hash
         env.events()
-            .publish((symbol_short!("upgrade"), admin), new_wasm_hash);
+            .publish((symbol_short!("upgrade"),), new_wasm_hash);
     }
 
     /// Get the current admin address (for backward compatibility)
@@ -944,7 +5839,9 @@ impl StellarStreamContract {
             request.total_amount,
             request.start_time,
             request.start_time + request.duration,
+            request.start_time,
             CurveType::Linear,
+            false,
         )?;
         env.events().publish(
             (
@@ -970,10 +5867,11 @@ impl StellarStreamContract {
 
 // Contract metadata for explorer display (Stellar.Expert, etc.)
 soroban_sdk::contractmeta!(
-    desc = "StellarStream: Token streaming with multi-sig proposals, dynamic vesting curves (linear/exponential), yield optimization, and OFAC compliance. Create, manage, and withdraw from streams with flexible approval workflows.",
-    version = "0.1.0",
-    name = "StellarStream"
+    key = "desc",
+    val = "StellarStream: Token streaming with multi-sig proposals, dynamic vesting curves (linear/exponential), yield optimization, and OFAC compliance. Create, manage, and withdraw from streams with flexible approval workflows."
 );
+soroban_sdk::contractmeta!(key = "version", val = "0.1.0");
+soroban_sdk::contractmeta!(key = "name", val = "StellarStream");
 
 #[cfg(test)]
 mod test {
@@ -984,11 +5882,12 @@ mod test {
         Address, Env,
     };
 
-    fn set_admin_role(env: &Env, contract_id: &Address, admin: &Address) {
+    fn set_compliance_role(env: &Env, contract_id: &Address, officer: &Address) {
         env.as_contract(contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Role(admin.clone(), Role::Admin), &true);
+            env.storage().instance().set(
+                &DataKey::Role(officer.clone(), Role::ComplianceOfficer),
+                &true,
+            );
         });
     }
 
@@ -1013,8 +5912,18 @@ mod test {
         let admin = Address::generate(&env);
         let (token_id, _) = create_token_contract(&env, &admin);
 
-        let proposal_id =
-            client.create_proposal(&sender, &receiver, &token_id, &1000, &100, &200, &2, &1000);
+        let proposal_id = client.create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &2,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         assert_eq!(proposal_id, 0);
     }
@@ -1039,8 +5948,18 @@ mod test {
         let approver1 = Address::generate(&env);
         let approver2 = Address::generate(&env);
 
-        let proposal_id =
-            client.create_proposal(&sender, &receiver, &token_id, &1000, &100, &200, &2, &1000);
+        let proposal_id = client.create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &2,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         client.approve_proposal(&proposal_id, &approver1);
 
@@ -1071,8 +5990,18 @@ mod test {
 
         let approver = Address::generate(&env);
 
-        let proposal_id =
-            client.create_proposal(&sender, &receiver, &token_id, &1000, &100, &200, &2, &1000);
+        let proposal_id = client.create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &2,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         client.approve_proposal(&proposal_id, &approver);
         let result = client.try_approve_proposal(&proposal_id, &approver);
@@ -1107,8 +6036,18 @@ mod test {
         let admin = Address::generate(&env);
         let (token_id, _) = create_token_contract(&env, &admin);
 
-        let result =
-            client.try_create_proposal(&sender, &receiver, &token_id, &1000, &200, &100, &2, &1000);
+        let result = client.try_create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &200,
+            &100,
+            &2,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
     }
@@ -1126,8 +6065,18 @@ mod test {
         let admin = Address::generate(&env);
         let (token_id, _) = create_token_contract(&env, &admin);
 
-        let result =
-            client.try_create_proposal(&sender, &receiver, &token_id, &0, &100, &200, &2, &1000);
+        let result = client.try_create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &0,
+            &100,
+            &200,
+            &2,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         assert_eq!(result, Err(Ok(Error::InvalidAmount)));
     }
@@ -1145,8 +6094,18 @@ mod test {
         let admin = Address::generate(&env);
         let (token_id, _) = create_token_contract(&env, &admin);
 
-        let result =
-            client.try_create_proposal(&sender, &receiver, &token_id, &1000, &100, &200, &0, &1000);
+        let result = client.try_create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &0,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         assert_eq!(result, Err(Ok(Error::InvalidApprovalThreshold)));
     }
@@ -1174,7 +6133,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         assert_eq!(stream_id, 0);
@@ -1214,7 +6175,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         client.transfer_receipt(&stream_id, &receiver, &new_owner);
@@ -1251,13 +6214,15 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         client.transfer_receipt(&stream_id, &receiver, &new_owner);
 
         let result = client.try_withdraw(&stream_id, &receiver);
-        assert_eq!(result, Err(Ok(Error::NotReceiptOwner)));
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
 
         let withdrawn = client.withdraw(&stream_id, &new_owner);
         assert!(withdrawn > 0);
@@ -1287,7 +6252,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         let metadata = client.get_receipt_metadata(&stream_id);
@@ -1315,8 +6282,18 @@ mod test {
         let token_admin_client = StellarAssetClient::new(&env, &token_id);
         token_admin_client.mint(&sender, &100000);
 
-        let proposal_id =
-            client.create_proposal(&sender, &receiver, &token_id, &50000, &100, &200, &3, &1000);
+        let proposal_id = client.create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &50000,
+            &100,
+            &200,
+            &3,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         let approver1 = Address::generate(&env);
         let approver2 = Address::generate(&env);
@@ -1353,8 +6330,18 @@ mod test {
         let token_admin_client = StellarAssetClient::new(&env, &token_id);
         token_admin_client.mint(&sender, &10000);
 
-        let proposal_id =
-            client.create_proposal(&sender, &receiver, &token_id, &1000, &100, &200, &1, &1000);
+        let proposal_id = client.create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &1,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         let approver1 = Address::generate(&env);
         client.approve_proposal(&proposal_id, &approver1);
@@ -1389,7 +6376,9 @@ mod test {
             &1000,
             &100,
             &300,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         env.ledger().with_mut(|li| li.timestamp = 150);
@@ -1431,7 +6420,9 @@ mod test {
             &1000,
             &100,
             &300,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         client.pause_stream(&stream_id, &sender);
@@ -1466,7 +6457,9 @@ mod test {
             &1000,
             &100,
             &300,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         env.ledger().with_mut(|li| li.timestamp = 150);
@@ -1531,6 +6524,8 @@ mod test {
             &360,
             &milestones,
             &CurveType::Linear,
+            &false,
+            &None,
         );
 
         env.ledger().with_mut(|li| li.timestamp = 45);
@@ -1578,6 +6573,8 @@ mod test {
             &200,
             &milestones,
             &CurveType::Linear,
+            &false,
+            &None,
         );
 
         env.ledger().with_mut(|li| li.timestamp = 50);
@@ -1622,7 +6619,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         assert_eq!(stream_id, 0);
@@ -1653,7 +6652,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         // Withdraw - should emit claim event
@@ -1686,7 +6687,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         // Cancel - should emit cancel event
@@ -1718,7 +6721,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         // Transfer receipt - should emit transfer event
@@ -1750,7 +6755,9 @@ mod test {
             &1000,
             &100,
             &300,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         // Pause stream - should emit pause event
@@ -1782,7 +6789,9 @@ mod test {
             &1000,
             &100,
             &300,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         client.pause_stream(&stream_id, &sender);
@@ -1811,8 +6820,18 @@ mod test {
         let token_admin_client = StellarAssetClient::new(&env, &token_id);
         token_admin_client.mint(&sender, &10000);
 
-        let proposal_id =
-            client.create_proposal(&sender, &receiver, &token_id, &1000, &100, &200, &2, &1000);
+        let proposal_id = client.create_proposal(
+            &sender,
+            &receiver,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &2,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        );
 
         let approver1 = Address::generate(&env);
         let approver2 = Address::generate(&env);
@@ -1850,7 +6869,9 @@ mod test {
             &1000,
             &0,
             &100,
+            &0,
             &CurveType::Exponential,
+            &false,
         );
 
         // At 50% time: should have ~25% unlocked (0.5^2 = 0.25)
@@ -1886,7 +6907,7 @@ mod test {
         let admin = Address::generate(&env);
         let restricted_addr = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Admin restricts an address
         client.restrict_address(&admin, &restricted_addr);
@@ -1906,12 +6927,7 @@ mod test {
         let admin = Address::generate(&env);
         let restricted_addr = Address::generate(&env);
 
-        // Manually set admin role in storage to bootstrap
-        env.as_contract(&contract_id, || {
-            env.storage()
-                .instance()
-                .set(&DataKey::Role(admin.clone(), Role::Admin), &true);
-        });
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Admin restricts an address
         client.restrict_address(&admin, &restricted_addr);
@@ -1941,7 +6957,7 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #20)")]
+    #[should_panic(expected = "Error(Contract, #22)")]
     fn test_cannot_create_stream_to_restricted_address() {
         let env = Env::default();
         env.mock_all_auths();
@@ -1955,10 +6971,12 @@ mod test {
         let restricted_receiver = Address::generate(&env);
         let token_admin = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Create token
-        let token_id = env.register_stellar_asset_contract(token_admin.clone());
+        let token_id = env
+            .register_stellar_asset_contract_v2(token_admin.clone())
+            .address();
 
         // Mint tokens to sender
         let token_client = token::StellarAssetClient::new(&env, &token_id);
@@ -1975,12 +6993,14 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #20)")]
+    #[should_panic(expected = "Error(Contract, #22)")]
     fn test_cannot_create_proposal_to_restricted_address() {
         let env = Env::default();
         env.mock_all_auths();
@@ -1994,10 +7014,12 @@ mod test {
         let restricted_receiver = Address::generate(&env);
         let token_admin = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Create token
-        let token_id = env.register_stellar_asset_contract(token_admin.clone());
+        let token_id = env
+            .register_stellar_asset_contract_v2(token_admin.clone())
+            .address();
 
         // Mint tokens to sender
         let token_client = token::StellarAssetClient::new(&env, &token_id);
@@ -2016,11 +7038,13 @@ mod test {
             &200,
             &2,
             &1000,
+            &false,
+            &Vec::new(&env),
         );
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #20)")]
+    #[should_panic(expected = "Error(Contract, #22)")]
     fn test_cannot_transfer_receipt_to_restricted_address() {
         let env = Env::default();
         env.mock_all_auths();
@@ -2035,10 +7059,12 @@ mod test {
         let restricted_addr = Address::generate(&env);
         let token_admin = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Create token
-        let token_id = env.register_stellar_asset_contract(token_admin.clone());
+        let token_id = env
+            .register_stellar_asset_contract_v2(token_admin.clone())
+            .address();
 
         // Mint tokens to sender
         let token_client = token::StellarAssetClient::new(&env, &token_id);
@@ -2052,7 +7078,9 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
         // Admin restricts an address
@@ -2075,7 +7103,7 @@ mod test {
         let addr2 = Address::generate(&env);
         let addr3 = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Initially, no restricted addresses
         let restricted = client.get_restricted_addresses();
@@ -2102,7 +7130,7 @@ mod test {
         let admin = Address::generate(&env);
         let restricted_addr = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Restrict address first time
         client.restrict_address(&admin, &restricted_addr);
@@ -2132,10 +7160,12 @@ mod test {
         let receiver = Address::generate(&env);
         let token_admin = Address::generate(&env);
 
-        set_admin_role(&env, &contract_id, &admin);
+        set_compliance_role(&env, &contract_id, &admin);
 
         // Create token
-        let token_id = env.register_stellar_asset_contract(token_admin.clone());
+        let token_id = env
+            .register_stellar_asset_contract_v2(token_admin.clone())
+            .address();
 
         // Mint tokens to sender
         let token_client = token::StellarAssetClient::new(&env, &token_id);
@@ -2155,10 +7185,12 @@ mod test {
             &1000,
             &100,
             &200,
+            &100,
             &CurveType::Linear,
+            &false,
         );
 
-        // Verify stream was created (stream_id >= 0)
-        assert!(stream_id >= 0);
+        // Verify stream was created
+        assert_eq!(client.get_stream(&stream_id).receiver, receiver);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,123 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::storage::MAX_LOYALTY_RATE_BPS;
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct LoyaltyContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    token_id: Address,
+    admin: Address,
+    sender: Address,
+}
+
+fn setup(env: &Env) -> LoyaltyContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10_000_000);
+
+    LoyaltyContext {
+        client,
+        token_id,
+        admin,
+        sender,
+    }
+}
+
+fn create_stream(ctx: &LoyaltyContext, receiver: &Address) -> u64 {
+    ctx.client.create_stream(
+        &ctx.sender,
+        receiver,
+        &ctx.token_id,
+        &1_000_000,
+        &0,
+        &3_000_000,
+        &0,
+        &CurveType::Linear,
+        &false,
+    )
+}
+
+#[test]
+fn test_untouched_stream_earns_larger_loyalty_bonus() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client
+        .configure_loyalty_reward(&ctx.admin, &MAX_LOYALTY_RATE_BPS);
+    ctx.client
+        .fund_loyalty_pool(&ctx.sender, &ctx.token_id, &100_000);
+
+    let patient = Address::generate(&env);
+    let frequent = Address::generate(&env);
+    let patient_stream = create_stream(&ctx, &patient);
+    let frequent_stream = create_stream(&ctx, &frequent);
+
+    for t in [1_000_000, 2_000_000] {
+        env.ledger().with_mut(|li| li.timestamp = t);
+        ctx.client.withdraw(&frequent_stream, &frequent);
+    }
+    env.ledger().with_mut(|li| li.timestamp = 3_000_000);
+    ctx.client.withdraw(&frequent_stream, &frequent);
+    ctx.client.withdraw(&patient_stream, &patient);
+
+    let token = TokenClient::new(&env, &ctx.token_id);
+    let patient_bonus = ctx.client.get_loyalty_earned(&patient_stream);
+    let frequent_bonus = ctx.client.get_loyalty_earned(&frequent_stream);
+    assert!(frequent_bonus > 0);
+    assert!(patient_bonus > frequent_bonus);
+    assert_eq!(token.balance(&patient), 1_000_000 + patient_bonus);
+    assert_eq!(token.balance(&frequent), 1_000_000 + frequent_bonus);
+    assert_eq!(
+        ctx.client.get_loyalty_pool(&ctx.token_id),
+        100_000 - patient_bonus - frequent_bonus
+    );
+}
+
+#[test]
+fn test_loyalty_bonus_bounded_by_rate_and_pool() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let result = ctx
+        .client
+        .try_configure_loyalty_reward(&ctx.admin, &(MAX_LOYALTY_RATE_BPS + 1));
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+    // Disabled by default: nothing accrues even with a funded pool
+    ctx.client
+        .fund_loyalty_pool(&ctx.sender, &ctx.token_id, &10);
+    let receiver = Address::generate(&env);
+    let stream_id = create_stream(&ctx, &receiver);
+    env.ledger().with_mut(|li| li.timestamp = 1_500_000);
+    assert_eq!(ctx.client.withdraw(&stream_id, &receiver), 500_000);
+
+    // Once enabled, the bonus can't exceed what the pool holds
+    ctx.client
+        .configure_loyalty_reward(&ctx.admin, &MAX_LOYALTY_RATE_BPS);
+    env.ledger().with_mut(|li| li.timestamp = 3_000_000);
+    assert_eq!(ctx.client.withdraw(&stream_id, &receiver), 500_000 + 10);
+    assert_eq!(ctx.client.get_loyalty_pool(&ctx.token_id), 0);
+}
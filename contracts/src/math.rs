@@ -54,6 +54,39 @@ pub fn calculate_exponential_unlocked(
     Ok(numerator / duration_squared)
 }
 
+/// Fixed-point scale for the square-root fraction in the logarithmic curve
+const SQRT_SCALE: i128 = 1_000_000_000;
+
+/// Calculate unlocked amount using a logarithmic-shaped curve (square root)
+/// Front-loads payout: half the total unlocks after a quarter of the duration
+/// Uses checked math to prevent overflow; on Err, callers fall back to linear
+/// the same way they do for the exponential curve
+pub fn calculate_logarithmic_unlocked(
+    total_amount: i128,
+    start_time: u64,
+    end_time: u64,
+    current_time: u64,
+) -> Result<i128, ()> {
+    if current_time < start_time {
+        return Ok(0);
+    }
+
+    if current_time >= end_time {
+        return Ok(total_amount);
+    }
+
+    let elapsed = (current_time - start_time) as i128;
+    let duration = (end_time - start_time) as i128;
+
+    // sqrt(elapsed / duration) scaled by SQRT_SCALE:
+    // isqrt(elapsed * SQRT_SCALE^2 / duration), always < SQRT_SCALE before end
+    let scaled = elapsed.checked_mul(SQRT_SCALE * SQRT_SCALE).ok_or(())? / duration;
+    let fraction = isqrt(scaled);
+    let numerator = total_amount.checked_mul(fraction).ok_or(())?;
+
+    Ok(numerator / SQRT_SCALE)
+}
+
 /// Calculate withdrawable amount
 /// For final withdrawal, caller should use total_amount - withdrawn_amount
 /// to avoid precision loss
@@ -111,7 +144,6 @@ pub fn calculate_withdrawable(
 
 /// Calculate fee based on basis points (bps)
 /// fee_bps is in hundredths of a percent (100 bps = 1%)
-#[allow(dead_code)]
 pub fn calculate_fee(amount: i128, fee_bps: u32) -> i128 {
     if fee_bps == 0 || amount <= 0 {
         return 0;
@@ -120,6 +152,23 @@ pub fn calculate_fee(amount: i128, fee_bps: u32) -> i128 {
     (amount * (fee_bps as i128)) / 10_000
 }
 
+/// Integer square root (floor) using Newton's method
+/// Returns 0 for negative input. Safe for the full i128 range.
+pub fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+
+    // n / 2 + 1 is always >= sqrt(n), and keeps x + n / x from overflowing
+    let mut x = n / 2 + 1;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -187,6 +236,94 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_logarithmic_curve() {
+        let total = 1000_i128;
+        let start = 0;
+        let end = 100;
+
+        assert_eq!(
+            calculate_logarithmic_unlocked(total, start, end, 0).unwrap(),
+            0
+        );
+
+        // At 25%: 50% unlocked (sqrt(0.25) = 0.5), well before halfway
+        assert_eq!(
+            calculate_logarithmic_unlocked(total, start, end, 25).unwrap(),
+            500
+        );
+
+        // At 64%: 80% unlocked (sqrt(0.64) = 0.8)
+        assert_eq!(
+            calculate_logarithmic_unlocked(total, start, end, 64).unwrap(),
+            800
+        );
+
+        assert_eq!(
+            calculate_logarithmic_unlocked(total, start, end, 100).unwrap(),
+            1000
+        );
+        assert_eq!(
+            calculate_logarithmic_unlocked(total, start, end, 150).unwrap(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_logarithmic_curve_halfway() {
+        // sqrt(0.5) ~= 0.7071, so about 70% is out at the halfway point
+        assert_eq!(
+            calculate_logarithmic_unlocked(1000, 0, 100, 50).unwrap(),
+            707
+        );
+        assert_eq!(
+            calculate_logarithmic_unlocked(1_000_000, 1000, 3000, 2000).unwrap(),
+            707_106
+        );
+    }
+
+    #[test]
+    fn test_logarithmic_curve_overflow_errors() {
+        assert!(calculate_logarithmic_unlocked(i128::MAX, 0, 100, 50).is_err());
+    }
+
+    #[test]
+    fn test_isqrt_small_and_perfect_squares() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(2), 1);
+        assert_eq!(isqrt(3), 1);
+        assert_eq!(isqrt(4), 2);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(17), 4);
+        assert_eq!(isqrt(-9), 0);
+
+        for root in [10_i128, 999, 65_536, 1_000_000_007] {
+            assert_eq!(isqrt(root * root), root);
+            assert_eq!(isqrt(root * root - 1), root - 1);
+            assert_eq!(isqrt(root * root + 1), root);
+        }
+    }
+
+    #[test]
+    fn test_isqrt_large_values() {
+        assert_eq!(isqrt(i128::MAX), 13_043_817_825_332_782_212);
+
+        // Floor property holds near the top of the range
+        for n in [
+            i128::MAX,
+            i128::MAX - 1,
+            i128::MAX / 2,
+            i64::MAX as i128 * 3,
+        ] {
+            let root = isqrt(n);
+            assert!(root * root <= n);
+            let next = root + 1;
+            assert!(next.checked_mul(next).is_none_or(|sq| sq > n));
+        }
+    }
+
     #[test]
     fn test_exponential_overflow_protection() {
         // Test with large values that could overflow
@@ -281,7 +418,46 @@ mod proofs {
         assert_eq!(result, 0);
     }
 
-    /// Invariant 5: Cliff support — nothing unlocked before cliff
+    /// Invariant 5: Logarithmic curve never exceeds total (Boundedness)
+    #[kani::proof]
+    fn proof_logarithmic_never_exceeds_total() {
+        let total: i128 = kani::any();
+        let start: u64 = kani::any();
+        let end: u64 = kani::any();
+        let current: u64 = kani::any();
+
+        kani::assume(total >= 0);
+        kani::assume(end > start);
+        kani::assume(total <= i64::MAX as i128);
+
+        if let Ok(result) = calculate_logarithmic_unlocked(total, start, end, current) {
+            assert!(result >= 0);
+            assert!(result <= total);
+        }
+    }
+
+    /// Invariant 6: Logarithmic curve is monotonic over time
+    #[kani::proof]
+    fn proof_logarithmic_monotonic_over_time() {
+        let total: i128 = kani::any();
+        let start: u64 = kani::any();
+        let end: u64 = kani::any();
+        let t1: u64 = kani::any();
+        let t2: u64 = kani::any();
+
+        kani::assume(total >= 0);
+        kani::assume(end > start);
+        kani::assume(t2 >= t1);
+        kani::assume(total <= i64::MAX as i128);
+
+        let r1 = calculate_logarithmic_unlocked(total, start, end, t1);
+        let r2 = calculate_logarithmic_unlocked(total, start, end, t2);
+        if let (Ok(r1), Ok(r2)) = (r1, r2) {
+            assert!(r2 >= r1);
+        }
+    }
+
+    /// Invariant 7: Cliff support — nothing unlocked before cliff
     #[kani::proof]
     fn proof_cliff_nothing_before_cliff() {
         let total: i128 = kani::any();
@@ -1,4 +1,4 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{Address, Env, IntoVal};
 
 /// Fetch price from oracle with staleness check
 pub fn get_price(env: &Env, oracle: &Address, max_staleness: u64) -> Result<i128, ()> {
@@ -28,6 +28,21 @@ pub fn get_price(env: &Env, oracle: &Address, max_staleness: u64) -> Result<i128
     Ok(price)
 }
 
+/// Ask a condition oracle whether the condition attached to a stream holds
+/// Oracle interface: condition(stream_id: u64) -> bool
+pub fn get_condition(env: &Env, oracle: &Address, stream_id: u64) -> Result<bool, ()> {
+    let result = env.try_invoke_contract::<bool, soroban_sdk::Error>(
+        oracle,
+        &soroban_sdk::symbol_short!("condition"),
+        soroban_sdk::vec![env, stream_id.into_val(env)],
+    );
+
+    match result {
+        Ok(Ok(holds)) => Ok(holds),
+        _ => Err(()),
+    }
+}
+
 /// Calculate token amount based on USD value and current price
 /// usd_amount: USD value with 7 decimals
 /// price: Token price in USD with 7 decimals
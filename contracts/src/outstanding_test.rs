@@ -0,0 +1,156 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::types::{CurveType, DataKey};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_total_outstanding_per_token() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_a, _) = create_token_contract(&env, &admin);
+    let (token_b, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_a).mint(&sender, &10000);
+    StellarAssetClient::new(&env, &token_b).mint(&sender, &10000);
+
+    let stream_a = client.create_stream(
+        &sender,
+        &receiver,
+        &token_a,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    client.create_stream(
+        &sender,
+        &receiver,
+        &token_a,
+        &500,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    client.create_stream(
+        &sender,
+        &receiver,
+        &token_b,
+        &2000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    let totals = client.get_total_outstanding();
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals.get(0).unwrap(), (token_a.clone(), 1500));
+    assert_eq!(totals.get(1).unwrap(), (token_b.clone(), 2000));
+
+    // Withdrawals reduce what is still owed
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&stream_a, &receiver);
+
+    let totals = client.get_total_outstanding();
+    assert_eq!(totals.get(0).unwrap(), (token_a, 1000));
+    assert_eq!(totals.get(1).unwrap(), (token_b, 2000));
+}
+
+#[test]
+fn test_rebuild_outstanding_repairs_drifted_counter() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_a, _) = create_token_contract(&env, &admin);
+    let (token_b, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_a).mint(&sender, &10000);
+    StellarAssetClient::new(&env, &token_b).mint(&sender, &10000);
+
+    let stream_a = client.create_stream(
+        &sender,
+        &receiver,
+        &token_a,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    let cancelled = client.create_stream(
+        &sender,
+        &receiver,
+        &token_a,
+        &500,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    client.create_stream(
+        &sender,
+        &receiver,
+        &token_b,
+        &2000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&stream_a, &receiver);
+    client.cancel(&cancelled, &sender);
+
+    // Simulate a bug that left the counter wrong
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::Outstanding(token_a.clone()), &12345i128);
+    });
+
+    assert_eq!(client.rebuild_outstanding(&admin, &token_a), 500);
+    let totals = client.get_total_outstanding();
+    assert_eq!(totals.get(0).unwrap(), (token_a.clone(), 500));
+    assert_eq!(totals.get(1).unwrap(), (token_b, 2000));
+
+    let outsider = Address::generate(&env);
+    let result = client.try_rebuild_outstanding(&outsider, &token_a);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
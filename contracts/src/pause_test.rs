@@ -0,0 +1,210 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+fn setup_stream<'a>(env: &Env) -> (StellarStreamContractClient<'a>, u64, Address, Address) {
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+
+    let token_admin_client = StellarAssetClient::new(env, &token_id);
+    token_admin_client.mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &300,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    (client, stream_id, sender, receiver)
+}
+
+#[test]
+fn test_effective_end_time_shifts_by_pause_duration() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    assert_eq!(client.get_effective_end_time(&stream_id), 300);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.pause_stream(&stream_id, &sender);
+
+    // While paused, the ongoing pause already pushes the end time out
+    env.ledger().with_mut(|li| li.timestamp = 190);
+    assert_eq!(client.get_effective_end_time(&stream_id), 340);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.unpause_stream(&stream_id, &sender);
+
+    // Paused for exactly 50 seconds
+    env.ledger().with_mut(|li| li.timestamp = 250);
+    assert_eq!(client.get_effective_end_time(&stream_id), 350);
+}
+
+#[test]
+fn test_pause_budget_exhausted() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    client.set_pause_budget(&stream_id, &sender, &50);
+
+    // First pause uses 30 of the 50 second budget
+    client.pause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 130);
+    client.unpause_stream(&stream_id, &sender);
+
+    // Second pause overruns; only the remaining 20 seconds are credited
+    client.pause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 170);
+    client.unpause_stream(&stream_id, &sender);
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.total_paused_duration, 50);
+
+    let result = client.try_pause_stream(&stream_id, &sender);
+    assert_eq!(result, Err(Ok(Error::PauseBudgetExceeded)));
+}
+
+#[test]
+fn test_pause_budget_can_only_tighten() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    client.set_pause_budget(&stream_id, &sender, &50);
+
+    let result = client.try_set_pause_budget(&stream_id, &sender, &100);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+
+    client.set_pause_budget(&stream_id, &sender, &25);
+    assert_eq!(client.get_stream(&stream_id).pause_budget, 25);
+}
+
+#[test]
+fn test_pause_just_before_end_fully_vests_after_adjusted_end() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 299);
+    client.pause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 400);
+    client.unpause_stream(&stream_id, &sender);
+    assert_eq!(client.get_effective_end_time(&stream_id), 401);
+
+    env.ledger().with_mut(|li| li.timestamp = 401);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 1000);
+}
+
+#[test]
+fn test_pause_beyond_budget_keeps_vesting_while_paused() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    client.set_pause_budget(&stream_id, &sender, &50);
+
+    env.ledger().with_mut(|li| li.timestamp = 280);
+    client.pause_stream(&stream_id, &sender);
+
+    // Still paused, but past end_time + budget: the stream is fully vested
+    env.ledger().with_mut(|li| li.timestamp = 400);
+    client.cancel(&stream_id, &sender);
+    assert_eq!(client.get_stream(&stream_id).withdrawn_amount, 1000);
+}
+
+#[test]
+fn test_pause_token_halts_only_that_token() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    client.initialize(&admin);
+
+    let (paused_token, _) = create_token_contract(&env, &admin);
+    let (other_token, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &paused_token).mint(&sender, &10000);
+    StellarAssetClient::new(&env, &other_token).mint(&sender, &10000);
+
+    let paused_stream = client.create_stream(
+        &sender,
+        &receiver,
+        &paused_token,
+        &1000,
+        &100,
+        &300,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    let other_stream = client.create_stream(
+        &sender,
+        &receiver,
+        &other_token,
+        &1000,
+        &100,
+        &300,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    client.pause_token(&admin, &paused_token);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+
+    let result = client.try_withdraw(&paused_stream, &receiver);
+    assert_eq!(result, Err(Ok(Error::TokenPaused)));
+    let result = client.try_create_stream(
+        &sender,
+        &receiver,
+        &paused_token,
+        &1000,
+        &200,
+        &300,
+        &200,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::TokenPaused)));
+    assert_eq!(client.withdraw(&other_stream, &receiver), 500);
+
+    client.unpause_token(&admin, &paused_token);
+    assert_eq!(client.withdraw(&paused_stream, &receiver), 500);
+}
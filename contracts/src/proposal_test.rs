@@ -0,0 +1,497 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Bytes, BytesN, Env, Symbol, TryFromVal, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::{
+    MAX_PROPOSAL_APPROVERS, MAX_PROPOSAL_EXTENSION, MIN_UPGRADE_APPROVALS, PROPOSAL_COUNT,
+};
+use crate::types::{FeeConfig, Proposal, ProposalAction, Role};
+
+// Smallest module the host accepts: header plus a contractenvmetav0 section
+// declaring protocol 22
+const EMPTY_CONTRACT_WASM: [u8; 40] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x1e, 0x11, b'c', b'o', b'n', b't', b'r',
+    b'a', b'c', b't', b'e', b'n', b'v', b'm', b'e', b't', b'a', b'v', b'0', 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x16, 0x00, 0x00, 0x00, 0x00,
+];
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct ProposalContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    contract_id: Address,
+    token: TokenClient<'a>,
+    token_id: Address,
+    sender: Address,
+    receiver: Address,
+}
+
+fn setup(env: &Env) -> ProposalContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 50);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, token) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    ProposalContext {
+        client,
+        contract_id,
+        token,
+        token_id,
+        sender,
+        receiver,
+    }
+}
+
+#[test]
+fn test_escrowed_proposal_pulls_funds_at_creation() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &2,
+        &1000,
+        &true,
+        &Vec::new(&env),
+    );
+
+    let proposal = ctx.client.get_proposal(&proposal_id);
+    assert!(proposal.escrowed);
+    assert_eq!(ctx.token.balance(&ctx.sender), 9000);
+    assert_eq!(ctx.token.balance(&ctx.contract_id), 1000);
+}
+
+#[test]
+fn test_escrowed_proposal_refunded_after_expiry() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &2,
+        &1000,
+        &true,
+        &Vec::new(&env),
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1001);
+    ctx.client.cancel_proposal(&proposal_id, &ctx.sender);
+
+    assert_eq!(ctx.token.balance(&ctx.sender), 10000);
+    assert_eq!(ctx.token.balance(&ctx.contract_id), 0);
+
+    let approver = Address::generate(&env);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &approver);
+    assert_eq!(result, Err(Ok(Error::ProposalCancelled)));
+}
+
+#[test]
+fn test_escrowed_proposal_executes_from_escrow() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &1,
+        &1000,
+        &true,
+        &Vec::new(&env),
+    );
+
+    // Sender spends the rest of its balance; execution must still succeed
+    let elsewhere = Address::generate(&env);
+    ctx.token.transfer(&ctx.sender, &elsewhere, &9000);
+
+    let approver = Address::generate(&env);
+    ctx.client.approve_proposal(&proposal_id, &approver);
+
+    let proposal = ctx.client.get_proposal(&proposal_id);
+    assert!(proposal.executed);
+    assert_eq!(ctx.token.balance(&ctx.contract_id), 1000);
+}
+
+#[test]
+fn test_set_fee_proposal_applies_only_at_threshold() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let admin = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    ctx.client.initialize(&admin);
+    ctx.client
+        .grant_role(&admin, &manager, &Role::TreasuryManager);
+
+    let proposal_id = ctx.client.create_action_proposal(
+        &admin,
+        &ProposalAction::SetFee(100, treasury.clone()),
+        &2,
+        &1000,
+    );
+
+    ctx.client.approve_proposal(&proposal_id, &admin);
+    assert!(!ctx.client.is_fee_configured());
+
+    ctx.client.approve_proposal(&proposal_id, &manager);
+    assert_eq!(
+        ctx.client.get_fee_config(),
+        Some(FeeConfig {
+            fee_bps: 100,
+            treasury
+        })
+    );
+    assert!(ctx.client.get_proposal(&proposal_id).executed);
+}
+
+#[test]
+fn test_action_proposal_requires_role_to_approve() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+
+    let target = Address::generate(&env);
+    let proposal_id = ctx.client.create_action_proposal(
+        &admin,
+        &ProposalAction::RestrictAddress(target.clone()),
+        &1,
+        &1000,
+    );
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client.approve_proposal(&proposal_id, &admin);
+    assert!(ctx.client.is_address_restricted(&target));
+}
+
+#[test]
+fn test_required_approvals_above_cap_rejected() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let result = ctx.client.try_create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &(MAX_PROPOSAL_APPROVERS + 1),
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidApprovalThreshold)));
+}
+
+#[test]
+fn test_required_approvals_above_approver_set_rejected() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let approvers = vec![&env, Address::generate(&env), Address::generate(&env)];
+    let result = ctx.client.try_create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &3,
+        &1000,
+        &false,
+        &approvers,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidApprovalThreshold)));
+
+    // Listing the same approver twice doesn't make the threshold reachable
+    let first = approvers.get(0).unwrap();
+    let result = ctx.client.try_create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &2,
+        &1000,
+        &false,
+        &vec![&env, first.clone(), first],
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidApprovalThreshold)));
+}
+
+#[test]
+fn test_only_authorized_approvers_can_approve() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let approver = Address::generate(&env);
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &1,
+        &1000,
+        &false,
+        &vec![&env, approver.clone()],
+    );
+
+    let outsider = Address::generate(&env);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client.approve_proposal(&proposal_id, &approver);
+    assert!(ctx.client.get_proposal(&proposal_id).executed);
+}
+
+#[test]
+fn test_approvals_stop_at_cap() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &MAX_PROPOSAL_APPROVERS,
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+
+    // Simulate a proposal stored before the cap with an unreachable threshold
+    env.as_contract(&ctx.contract_id, || {
+        let key = (PROPOSAL_COUNT, proposal_id);
+        let mut proposal: Proposal = env.storage().instance().get(&key).unwrap();
+        proposal.required_approvals = MAX_PROPOSAL_APPROVERS * 2;
+        env.storage().instance().set(&key, &proposal);
+    });
+
+    for _ in 0..MAX_PROPOSAL_APPROVERS {
+        ctx.client
+            .approve_proposal(&proposal_id, &Address::generate(&env));
+    }
+
+    let result = ctx
+        .client
+        .try_approve_proposal(&proposal_id, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::ApproverLimitReached)));
+    assert_eq!(
+        ctx.client.get_proposal(&proposal_id).approvers.len(),
+        MAX_PROPOSAL_APPROVERS
+    );
+}
+
+#[test]
+fn test_proposer_extends_live_deadline_within_cap() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &2,
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+
+    let outsider = Address::generate(&env);
+    let result = ctx
+        .client
+        .try_extend_proposal_deadline(&proposal_id, &outsider, &2000);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client
+        .extend_proposal_deadline(&proposal_id, &ctx.sender, &2000);
+    assert_eq!(ctx.client.get_proposal(&proposal_id).deadline, 2000);
+
+    // Extensions add up against the original deadline
+    let too_far = 1000 + MAX_PROPOSAL_EXTENSION + 1;
+    let result = ctx
+        .client
+        .try_extend_proposal_deadline(&proposal_id, &ctx.sender, &too_far);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+
+    // Approvals after the original deadline now count
+    env.ledger().with_mut(|li| li.timestamp = 1500);
+    ctx.client
+        .approve_proposal(&proposal_id, &Address::generate(&env));
+}
+
+#[test]
+fn test_expired_proposal_cannot_be_extended() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let proposal_id = ctx.client.create_proposal(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &200,
+        &2,
+        &1000,
+        &false,
+        &Vec::new(&env),
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1001);
+    let result = ctx
+        .client
+        .try_extend_proposal_deadline(&proposal_id, &ctx.sender, &2000);
+    assert_eq!(result, Err(Ok(Error::ProposalExpired)));
+    assert_eq!(ctx.client.get_proposal(&proposal_id).deadline, 1000);
+}
+
+fn upload_wasm(env: &Env) -> BytesN<32> {
+    env.deployer()
+        .upload_contract_wasm(Bytes::from_array(env, &EMPTY_CONTRACT_WASM))
+}
+
+#[test]
+fn test_upgrade_requires_two_admin_approvals() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let admin = Address::generate(&env);
+    let second_admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+    ctx.client.grant_role(&admin, &second_admin, &Role::Admin);
+
+    let wasm_hash = upload_wasm(&env);
+    let proposal_id = ctx.client.create_action_proposal(
+        &admin,
+        &ProposalAction::Upgrade(wasm_hash.clone()),
+        &MIN_UPGRADE_APPROVALS,
+        &1000,
+    );
+
+    ctx.client.approve_proposal(&proposal_id, &admin);
+    assert!(!ctx.client.get_proposal(&proposal_id).executed);
+
+    ctx.client.approve_proposal(&proposal_id, &second_admin);
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("upgrade"))
+        })
+        .unwrap();
+    assert_eq!(BytesN::<32>::try_from_val(&env, &data).unwrap(), wasm_hash);
+
+    // The contract now runs the empty module, so the old entry points are gone
+    assert!(ctx.client.try_get_proposal(&proposal_id).is_err());
+}
+
+#[test]
+fn test_single_admin_upgrade_rejected() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+
+    let wasm_hash = upload_wasm(&env);
+    let result = ctx.client.try_create_action_proposal(
+        &admin,
+        &ProposalAction::Upgrade(wasm_hash),
+        &1,
+        &1000,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidApprovalThreshold)));
+}
+
+#[test]
+fn test_stream_proposal_validated_at_creation_and_execution() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let admin = Address::generate(&env);
+    let officer = Address::generate(&env);
+    ctx.client.initialize(&admin);
+    ctx.client
+        .grant_role(&admin, &officer, &Role::ComplianceOfficer);
+
+    let propose = || {
+        ctx.client.try_create_proposal(
+            &ctx.sender,
+            &ctx.receiver,
+            &ctx.token_id,
+            &1000,
+            &100,
+            &200,
+            &1,
+            &1000,
+            &false,
+            &Vec::new(&env),
+        )
+    };
+
+    ctx.client.block_token(&admin, &ctx.token_id);
+    assert_eq!(propose(), Err(Ok(Error::TokenBlocked)));
+    ctx.client.unblock_token(&admin, &ctx.token_id);
+
+    let proposal_id = propose().unwrap().unwrap();
+    let approver = Address::generate(&env);
+
+    // Rules that changed after proposing still apply at execution
+    ctx.client.pause_token(&admin, &ctx.token_id);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &approver);
+    assert_eq!(result, Err(Ok(Error::TokenPaused)));
+    ctx.client.unpause_token(&admin, &ctx.token_id);
+
+    ctx.client.restrict_address(&officer, &ctx.receiver);
+    let result = ctx.client.try_approve_proposal(&proposal_id, &approver);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+    assert!(!ctx.client.get_proposal(&proposal_id).executed);
+}
@@ -615,9 +615,9 @@ mod tests {
         let addr = Address::generate(&env);
         
         // Test that has_role returns false when role has no members
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr), false);
-        assert_eq!(client.test_has_role(&Role::FinancialOperator, &addr), false);
-        assert_eq!(client.test_has_role(&Role::Guardian, &addr), false);
+        assert!(!client.test_has_role(&Role::SuperAdmin, &addr));
+        assert!(!client.test_has_role(&Role::FinancialOperator, &addr));
+        assert!(!client.test_has_role(&Role::Guardian, &addr));
     }
 
     #[test]
@@ -636,8 +636,8 @@ mod tests {
         client.test_set_role_members(&Role::SuperAdmin, &members);
         
         // Test that has_role returns true for addresses in the role
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr1), true);
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr2), true);
+        assert!(client.test_has_role(&Role::SuperAdmin, &addr1));
+        assert!(client.test_has_role(&Role::SuperAdmin, &addr2));
     }
 
     #[test]
@@ -657,7 +657,7 @@ mod tests {
         client.test_set_role_members(&Role::SuperAdmin, &members);
         
         // Test that has_role returns false for address not in the role
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr3), false);
+        assert!(!client.test_has_role(&Role::SuperAdmin, &addr3));
     }
 
     #[test]
@@ -674,9 +674,9 @@ mod tests {
         client.test_set_role_members(&Role::SuperAdmin, &members);
         
         // Test that address in one role doesn't appear in other roles
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr), true);
-        assert_eq!(client.test_has_role(&Role::FinancialOperator, &addr), false);
-        assert_eq!(client.test_has_role(&Role::Guardian, &addr), false);
+        assert!(client.test_has_role(&Role::SuperAdmin, &addr));
+        assert!(!client.test_has_role(&Role::FinancialOperator, &addr));
+        assert!(!client.test_has_role(&Role::Guardian, &addr));
     }
 
     #[test]
@@ -748,9 +748,9 @@ mod tests {
         client.test_add_role_member(&Role::Guardian, &addr);
         
         // Verify address exists in all three roles
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr), true);
-        assert_eq!(client.test_has_role(&Role::FinancialOperator, &addr), true);
-        assert_eq!(client.test_has_role(&Role::Guardian, &addr), true);
+        assert!(client.test_has_role(&Role::SuperAdmin, &addr));
+        assert!(client.test_has_role(&Role::FinancialOperator, &addr));
+        assert!(client.test_has_role(&Role::Guardian, &addr));
     }
 
     #[test]
@@ -799,7 +799,7 @@ mod tests {
         assert_eq!(members.len(), 2);
         assert_eq!(members.get(0).unwrap(), addr1);
         assert_eq!(members.get(1).unwrap(), addr3);
-        assert_eq!(client.test_has_role(&Role::Guardian, &addr2), false);
+        assert!(!client.test_has_role(&Role::Guardian, &addr2));
     }
 
     #[test]
@@ -825,7 +825,7 @@ mod tests {
         assert_eq!(members.len(), 2);
         assert_eq!(members.get(0).unwrap(), addr2);
         assert_eq!(members.get(1).unwrap(), addr3);
-        assert_eq!(client.test_has_role(&Role::FinancialOperator, &addr1), false);
+        assert!(!client.test_has_role(&Role::FinancialOperator, &addr1));
     }
 
     #[test]
@@ -851,7 +851,7 @@ mod tests {
         assert_eq!(members.len(), 2);
         assert_eq!(members.get(0).unwrap(), addr1);
         assert_eq!(members.get(1).unwrap(), addr2);
-        assert_eq!(client.test_has_role(&Role::Guardian, &addr3), false);
+        assert!(!client.test_has_role(&Role::Guardian, &addr3));
     }
 
     #[test]
@@ -943,9 +943,9 @@ mod tests {
         client.test_remove_role_member(&Role::SuperAdmin, &addr);
         
         // Verify address removed from SuperAdmin but still in other roles
-        assert_eq!(client.test_has_role(&Role::SuperAdmin, &addr), false);
-        assert_eq!(client.test_has_role(&Role::FinancialOperator, &addr), true);
-        assert_eq!(client.test_has_role(&Role::Guardian, &addr), true);
+        assert!(!client.test_has_role(&Role::SuperAdmin, &addr));
+        assert!(client.test_has_role(&Role::FinancialOperator, &addr));
+        assert!(client.test_has_role(&Role::Guardian, &addr));
     }
 
     #[test]
@@ -2041,9 +2041,9 @@ mod tests {
         let addr = Address::generate(&env);
         
         // Test that has_role returns false when role has no members
-        assert_eq!(client.has_role(&Role::SuperAdmin, &addr), false);
-        assert_eq!(client.has_role(&Role::FinancialOperator, &addr), false);
-        assert_eq!(client.has_role(&Role::Guardian, &addr), false);
+        assert!(!client.has_role(&Role::SuperAdmin, &addr));
+        assert!(!client.has_role(&Role::FinancialOperator, &addr));
+        assert!(!client.has_role(&Role::Guardian, &addr));
     }
 
     #[test]
@@ -2066,9 +2066,9 @@ mod tests {
         client.add_role(&super_admin, &Role::Guardian, &guardian);
         
         // Test that has_role returns true for addresses in their respective roles
-        assert_eq!(client.has_role(&Role::SuperAdmin, &super_admin), true);
-        assert_eq!(client.has_role(&Role::FinancialOperator, &financial_op), true);
-        assert_eq!(client.has_role(&Role::Guardian, &guardian), true);
+        assert!(client.has_role(&Role::SuperAdmin, &super_admin));
+        assert!(client.has_role(&Role::FinancialOperator, &financial_op));
+        assert!(client.has_role(&Role::Guardian, &guardian));
     }
 
     #[test]
@@ -2090,8 +2090,8 @@ mod tests {
         client.add_role(&super_admin, &Role::FinancialOperator, &financial_op);
         
         // Test that has_role returns false for address not in the role
-        assert_eq!(client.has_role(&Role::FinancialOperator, &non_member), false);
-        assert_eq!(client.has_role(&Role::Guardian, &super_admin), false);
+        assert!(!client.has_role(&Role::FinancialOperator, &non_member));
+        assert!(!client.has_role(&Role::Guardian, &super_admin));
     }
 
     #[test]
@@ -2112,9 +2112,9 @@ mod tests {
         client.add_role(&super_admin, &Role::FinancialOperator, &financial_op);
         
         // Test that address in one role doesn't appear in other roles
-        assert_eq!(client.has_role(&Role::FinancialOperator, &financial_op), true);
-        assert_eq!(client.has_role(&Role::SuperAdmin, &financial_op), false);
-        assert_eq!(client.has_role(&Role::Guardian, &financial_op), false);
+        assert!(client.has_role(&Role::FinancialOperator, &financial_op));
+        assert!(!client.has_role(&Role::SuperAdmin, &financial_op));
+        assert!(!client.has_role(&Role::Guardian, &financial_op));
     }
 
     #[test]
@@ -2135,13 +2135,13 @@ mod tests {
         client.add_role(&super_admin, &Role::Guardian, &guardian);
         
         // Verify guardian has the role
-        assert_eq!(client.has_role(&Role::Guardian, &guardian), true);
+        assert!(client.has_role(&Role::Guardian, &guardian));
         
         // Remove guardian
         client.remove_role(&super_admin, &Role::Guardian, &guardian);
         
         // Verify guardian no longer has the role
-        assert_eq!(client.has_role(&Role::Guardian, &guardian), false);
+        assert!(!client.has_role(&Role::Guardian, &guardian));
     }
 
     #[test]
@@ -2162,9 +2162,9 @@ mod tests {
         client.add_role(&super_admin, &Role::Guardian, &super_admin);
         
         // Verify super admin has all three roles
-        assert_eq!(client.has_role(&Role::SuperAdmin, &super_admin), true);
-        assert_eq!(client.has_role(&Role::FinancialOperator, &super_admin), true);
-        assert_eq!(client.has_role(&Role::Guardian, &super_admin), true);
+        assert!(client.has_role(&Role::SuperAdmin, &super_admin));
+        assert!(client.has_role(&Role::FinancialOperator, &super_admin));
+        assert!(client.has_role(&Role::Guardian, &super_admin));
     }
 }
 
@@ -0,0 +1,183 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::MAX_BATCH;
+use crate::types::{CurveType, DataKey};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_receiver_stream_limit_rejects_extra_stream() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_max_streams_per_receiver(&admin, &2);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    for _ in 0..2 {
+        client.create_stream(
+            &sender,
+            &receiver,
+            &token_id,
+            &100,
+            &100,
+            &200,
+            &100,
+            &CurveType::Linear,
+            &false,
+        );
+    }
+    assert_eq!(client.get_receiver_streams(&receiver).len(), 2);
+
+    let result = client.try_create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &100,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    assert_eq!(result, Err(Ok(Error::ReceiverStreamLimitReached)));
+
+    // Another receiver is unaffected
+    let other_receiver = Address::generate(&env);
+    client.create_stream(
+        &sender,
+        &other_receiver,
+        &token_id,
+        &100,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+}
+
+#[test]
+fn test_rebuild_soulbound_index_repairs_corruption() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    // More streams than one batch covers, every third one soulbound
+    let mut expected = Vec::new(&env);
+    for i in 0..(MAX_BATCH as u64 + 5) {
+        let stream_id = client.create_stream(
+            &sender,
+            &Address::generate(&env),
+            &token_id,
+            &100,
+            &100,
+            &200,
+            &100,
+            &CurveType::Linear,
+            &(i % 3 == 0),
+        );
+        if i % 3 == 0 {
+            expected.push_back(stream_id);
+        }
+    }
+    assert_eq!(client.get_soulbound_streams(), expected);
+
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::SoulboundStreams, &vec![&env, 1u64, 2, 500]);
+    });
+
+    let outsider = Address::generate(&env);
+    let result = client.try_rebuild_soulbound_index(&outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    assert!(!client.rebuild_soulbound_index(&admin));
+    assert!(client.rebuild_soulbound_index(&admin));
+    assert_eq!(client.get_soulbound_streams(), expected);
+}
+
+#[test]
+fn test_streams_by_receiver_tracks_transfers_and_keeps_cancelled() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    let mut ids = Vec::new(&env);
+    for receiver in [&alice, &alice, &bob] {
+        ids.push_back(client.create_stream(
+            &sender,
+            receiver,
+            &token_id,
+            &100,
+            &100,
+            &200,
+            &100,
+            &CurveType::Linear,
+            &false,
+        ));
+    }
+    let (first, second, third) = (
+        ids.get(0).unwrap(),
+        ids.get(1).unwrap(),
+        ids.get(2).unwrap(),
+    );
+    assert_eq!(
+        client.get_streams_by_receiver(&alice),
+        vec![&env, first, second]
+    );
+    assert_eq!(client.get_streams_by_receiver(&bob), vec![&env, third]);
+
+    client.transfer_receiver(&second, &sender, &bob);
+    client.cancel(&third, &sender);
+
+    assert_eq!(client.get_streams_by_receiver(&alice), vec![&env, first]);
+    assert_eq!(
+        client.get_streams_by_receiver(&bob),
+        vec![&env, third, second]
+    );
+    // The active index drops the cancelled stream, the history keeps it
+    assert_eq!(client.get_receiver_streams(&bob), vec![&env, second]);
+}
@@ -0,0 +1,417 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, IssuerFlags, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env,
+};
+
+use crate::errors::Error;
+use crate::storage::REDIRECT_GRACE;
+use crate::types::{CurveType, Role};
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+fn setup_stream<'a>(env: &Env) -> (StellarStreamContractClient<'a>, u64, Address, Address) {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    (client, stream_id, sender, receiver)
+}
+
+#[test]
+fn test_receiver_change_waits_for_consent() {
+    let env = Env::default();
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    let new_receiver = Address::generate(&env);
+
+    client.set_require_receiver_consent(&stream_id, &receiver, &true);
+    client.transfer_receiver(&stream_id, &sender, &new_receiver);
+
+    // Change is only pending until the current receiver approves it
+    assert_eq!(client.get_stream(&stream_id).receiver, receiver);
+    assert_eq!(
+        client.get_pending_receiver(&stream_id),
+        Some(new_receiver.clone())
+    );
+
+    client.approve_receiver_change(&stream_id, &receiver);
+
+    assert_eq!(client.get_stream(&stream_id).receiver, new_receiver);
+    assert_eq!(client.get_pending_receiver(&stream_id), None);
+}
+
+#[test]
+fn test_receiver_change_immediate_without_consent_flag() {
+    let env = Env::default();
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    let new_receiver = Address::generate(&env);
+
+    client.transfer_receiver(&stream_id, &sender, &new_receiver);
+
+    assert_eq!(client.get_stream(&stream_id).receiver, new_receiver);
+}
+
+#[test]
+fn test_only_current_receiver_can_approve_change() {
+    let env = Env::default();
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    let new_receiver = Address::generate(&env);
+
+    client.set_require_receiver_consent(&stream_id, &receiver, &true);
+    client.transfer_receiver(&stream_id, &sender, &new_receiver);
+
+    let result = client.try_approve_receiver_change(&stream_id, &new_receiver);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_transfer_lock_expires() {
+    let env = Env::default();
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    let new_receiver = Address::generate(&env);
+
+    client.set_transfer_lock(&stream_id, &sender, &150);
+    let result = client.try_set_transfer_lock(&stream_id, &sender, &120);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+
+    env.ledger().with_mut(|li| li.timestamp = 149);
+    let result = client.try_transfer_receiver(&stream_id, &sender, &new_receiver);
+    assert_eq!(result, Err(Ok(Error::TransferLocked)));
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.transfer_receiver(&stream_id, &sender, &new_receiver);
+    assert_eq!(client.get_stream(&stream_id).receiver, new_receiver);
+}
+
+#[test]
+fn test_transfer_all_receipts_skips_soulbound() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let old_key = Address::generate(&env);
+    let new_key = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    let create = |is_soulbound: bool| {
+        client.create_stream(
+            &sender,
+            &old_key,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &100,
+            &CurveType::Linear,
+            &is_soulbound,
+        )
+    };
+    let first = create(false);
+    let soulbound = create(true);
+    let second = create(false);
+
+    let moved = client.transfer_all_receipts(&old_key, &new_key);
+    assert_eq!(moved, vec![&env, first, second]);
+
+    assert_eq!(client.get_stream(&first).receiver, new_key);
+    assert_eq!(client.get_stream(&second).receiver, new_key);
+    assert_eq!(client.get_stream(&soulbound).receiver, old_key);
+    assert_eq!(client.get_receiver_streams(&old_key), vec![&env, soulbound]);
+    assert_eq!(
+        client.get_receiver_streams(&new_key),
+        vec![&env, first, second]
+    );
+}
+
+#[test]
+fn test_transfer_receipts_batch_skips_soulbound() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let estate = Address::generate(&env);
+    let heir = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    let create = |is_soulbound: bool| {
+        client.create_stream(
+            &sender,
+            &estate,
+            &token_id,
+            &1000,
+            &100,
+            &200,
+            &100,
+            &CurveType::Linear,
+            &is_soulbound,
+        )
+    };
+    let first = create(false);
+    let soulbound = create(true);
+    let second = create(false);
+    let kept = create(false);
+
+    let moved =
+        client.transfer_receipts_batch(&estate, &heir, &vec![&env, first, soulbound, second]);
+    assert_eq!(moved, vec![&env, first, second]);
+
+    assert_eq!(client.get_stream(&first).receiver, heir);
+    assert_eq!(client.get_stream(&second).receipt_owner, heir);
+    assert_eq!(client.get_stream(&soulbound).receiver, estate);
+    assert_eq!(
+        client.get_receiver_streams(&estate),
+        vec![&env, soulbound, kept]
+    );
+    assert_eq!(
+        client.get_receiver_streams(&heir),
+        vec![&env, first, second]
+    );
+}
+
+#[test]
+fn test_transfer_all_receipts_rejects_restricted_destination() {
+    let env = Env::default();
+    let (client, stream_id, _, receiver) = setup_stream(&env);
+
+    let admin = Address::generate(&env);
+    let officer = Address::generate(&env);
+    client.initialize(&admin);
+    client.grant_role(&admin, &officer, &Role::ComplianceOfficer);
+    let restricted = Address::generate(&env);
+    client.restrict_address(&officer, &restricted);
+
+    let result = client.try_transfer_all_receipts(&receiver, &restricted);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+    assert_eq!(client.get_stream(&stream_id).receiver, receiver);
+}
+
+#[test]
+fn test_fallback_claims_after_inactivity() {
+    let env = Env::default();
+    let (client, stream_id, _, receiver) = setup_stream(&env);
+    let fallback = Address::generate(&env);
+
+    client.set_fallback(&stream_id, &receiver, &fallback, &500);
+    assert_eq!(
+        client.get_fallback(&stream_id),
+        Some((fallback.clone(), 500, 0))
+    );
+
+    let result = client.try_claim_as_fallback(&stream_id, &fallback);
+    assert_eq!(result, Err(Ok(Error::TimelockNotElapsed)));
+
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    client.claim_as_fallback(&stream_id, &fallback);
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.receiver, fallback);
+    assert_eq!(stream.receipt_owner, fallback);
+    assert_eq!(client.get_fallback(&stream_id), None);
+    assert_eq!(client.withdraw(&stream_id, &fallback), 1000);
+}
+
+#[test]
+fn test_active_receiver_blocks_fallback() {
+    let env = Env::default();
+    let (client, stream_id, _, receiver) = setup_stream(&env);
+    let fallback = Address::generate(&env);
+
+    let outsider = Address::generate(&env);
+    let result = client.try_set_fallback(&stream_id, &outsider, &fallback, &500);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    client.set_fallback(&stream_id, &receiver, &fallback, &500);
+
+    // A withdrawal restarts the inactivity window
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&stream_id, &receiver);
+    env.ledger().with_mut(|li| li.timestamp = 600);
+    let result = client.try_claim_as_fallback(&stream_id, &fallback);
+    assert_eq!(result, Err(Ok(Error::TimelockNotElapsed)));
+
+    let result = client.try_claim_as_fallback(&stream_id, &outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.get_stream(&stream_id).receiver, receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 650);
+    client.claim_as_fallback(&stream_id, &fallback);
+    assert_eq!(client.get_stream(&stream_id).receiver, fallback);
+}
+
+#[test]
+fn test_redirect_on_failure_after_grace() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let fallback = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    sac.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token_admin = StellarAssetClient::new(&env, &sac.address());
+    token_admin.mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &sac.address(),
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    // The receiver's balance is deauthorized, so it rejects every transfer
+    token_admin.set_authorized(&receiver, &false);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    assert!(client.try_withdraw(&stream_id, &receiver).is_err());
+    assert!(!client.redirect_on_failure(&sender, &stream_id, &fallback));
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 150 + REDIRECT_GRACE - 1);
+    assert!(!client.redirect_on_failure(&sender, &stream_id, &fallback));
+    assert_eq!(client.get_stream(&stream_id).receiver, receiver);
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 150 + REDIRECT_GRACE);
+    assert!(client.redirect_on_failure(&sender, &stream_id, &fallback));
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.receiver, fallback);
+    assert_eq!(stream.total_amount, 1000);
+    assert_eq!(client.withdraw(&stream_id, &fallback), 1000);
+    assert_eq!(
+        TokenClient::new(&env, &sac.address()).balance(&fallback),
+        1000
+    );
+}
+
+#[test]
+fn test_redirect_on_failure_rejects_working_or_soulbound_receiver() {
+    let env = Env::default();
+    let (client, stream_id, sender, _) = setup_stream(&env);
+    let fallback = Address::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let result = client.try_redirect_on_failure(&sender, &stream_id, &fallback);
+    assert_eq!(result, Err(Ok(Error::StreamNotStuck)));
+
+    client.make_soulbound(&stream_id, &sender);
+    let result = client.try_redirect_on_failure(&sender, &stream_id, &fallback);
+    assert_eq!(result, Err(Ok(Error::StreamIsSoulbound)));
+}
+
+#[test]
+fn test_approved_spender_transfers_receipt() {
+    let env = Env::default();
+    let (client, stream_id, _, receiver) = setup_stream(&env);
+    let marketplace = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let result = client.try_transfer_receipt(&stream_id, &marketplace, &buyer);
+    assert_eq!(result, Err(Ok(Error::NotReceiptOwner)));
+
+    client.approve_receipt(&stream_id, &receiver, &marketplace);
+    assert_eq!(
+        client.get_receipt_approval(&stream_id),
+        Some(marketplace.clone())
+    );
+    client.transfer_receipt(&stream_id, &marketplace, &buyer);
+
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.receiver, buyer);
+    assert_eq!(stream.receipt_owner, buyer);
+
+    // The approval is spent with the transfer
+    assert_eq!(client.get_receipt_approval(&stream_id), None);
+    let result = client.try_transfer_receipt(&stream_id, &marketplace, &receiver);
+    assert_eq!(result, Err(Ok(Error::NotReceiptOwner)));
+}
+
+#[test]
+fn test_receipt_approval_revoked_or_soulbound() {
+    let env = Env::default();
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    let spender = Address::generate(&env);
+
+    client.approve_receipt(&stream_id, &receiver, &spender);
+    client.revoke_receipt_approval(&stream_id, &receiver);
+    assert_eq!(client.get_receipt_approval(&stream_id), None);
+
+    let result = client.try_approve_receipt(&stream_id, &spender, &spender);
+    assert_eq!(result, Err(Ok(Error::NotReceiptOwner)));
+
+    client.make_soulbound(&stream_id, &sender);
+    let result = client.try_approve_receipt(&stream_id, &receiver, &spender);
+    assert_eq!(result, Err(Ok(Error::StreamIsSoulbound)));
+}
+
+#[test]
+fn test_transfer_receipt_after_receiver_change() {
+    let env = Env::default();
+    let (client, stream_id, sender, receiver) = setup_stream(&env);
+    let new_receiver = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    client.transfer_receiver(&stream_id, &sender, &new_receiver);
+    assert_eq!(client.get_stream(&stream_id).receipt_owner, new_receiver);
+
+    // The previous receiver no longer owns the receipt
+    let result = client.try_transfer_receipt(&stream_id, &receiver, &buyer);
+    assert_eq!(result, Err(Ok(Error::NotReceiptOwner)));
+
+    client.transfer_receipt(&stream_id, &new_receiver, &buyer);
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.receiver, buyer);
+    assert_eq!(stream.receipt_owner, buyer);
+}
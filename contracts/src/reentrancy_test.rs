@@ -0,0 +1,118 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env, IntoVal, Symbol, Vec,
+};
+
+use crate::types::CurveType;
+
+// Vault that tries to re-enter withdraw on the stream contract before paying out
+#[contract]
+pub struct ReentrantVault;
+
+#[contractimpl]
+impl ReentrantVault {
+    pub fn init(env: Env, token: Address, target: Address, stream_id: u64, receiver: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("token"), &token);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("target"), &(target, stream_id, receiver));
+    }
+
+    pub fn deposit(_env: Env, _from: Address, amount: i128) -> i128 {
+        amount
+    }
+
+    pub fn withdraw(env: Env, to: Address, shares: i128) -> i128 {
+        let (target, stream_id, receiver): (Address, u64, Address) = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("target"))
+            .unwrap();
+        let reentered = env
+            .try_invoke_contract::<i128, crate::errors::Error>(
+                &target,
+                &Symbol::new(&env, "withdraw"),
+                (stream_id, receiver).into_val(&env),
+            )
+            .is_ok();
+        env.storage()
+            .instance()
+            .set(&symbol_short!("reenter"), &reentered);
+
+        let token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("token"))
+            .unwrap();
+        TokenClient::new(&env, &token).transfer(&env.current_contract_address(), &to, &shares);
+        shares
+    }
+
+    pub fn get_value(_env: Env, shares: i128) -> i128 {
+        shares
+    }
+
+    pub fn reentered(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("reenter"))
+            .unwrap_or(false)
+    }
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_reentrant_vault_cannot_double_withdraw() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let vault = env.register(ReentrantVault, ());
+    let vault_client = ReentrantVaultClient::new(&env, &vault);
+    client.approve_vault(&admin, &vault);
+
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+    vault_client.init(&token_id, &contract_id, &stream_id, &receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    assert_eq!(client.withdraw(&stream_id, &receiver), 500);
+
+    assert!(!vault_client.reentered());
+    assert_eq!(token.balance(&receiver), 500);
+    assert_eq!(client.get_stream(&stream_id).withdrawn_amount, 500);
+    assert_eq!(client.get_vault_deposited(&vault), 500);
+}
@@ -0,0 +1,48 @@
+use soroban_sdk::{contractclient, token, Address, Env};
+
+/// Swap interface expected from approved settlement routers
+#[allow(dead_code)]
+#[contractclient(name = "RouterClient")]
+pub trait RouterInterface {
+    /// Swap `amount_in` of `token_in`, already sent to the router, for at
+    /// least `min_out` of `token_out` paid to `to`. Returns the amount paid.
+    fn swap(
+        env: Env,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_out: i128,
+        to: Address,
+    ) -> i128;
+}
+
+/// Send `amount_in` to the router and swap it into `token_out` for this
+/// contract. Returns how much `token_out` actually arrived.
+pub fn swap_via_router(
+    env: &Env,
+    router: &Address,
+    token_in: &Address,
+    token_out: &Address,
+    amount_in: i128,
+    min_out: i128,
+) -> Result<i128, ()> {
+    let contract = env.current_contract_address();
+    let out_client = token::Client::new(env, token_out);
+    let balance_before = out_client.balance(&contract);
+
+    token::Client::new(env, token_in).transfer(&contract, router, &amount_in);
+
+    let router_client = RouterClient::new(env, router);
+    match router_client.try_swap(token_in, token_out, &amount_in, &min_out, &contract) {
+        Ok(Ok(_)) => {}
+        _ => return Err(()),
+    }
+
+    // Trust the balance change rather than the router's reported amount
+    let received = out_client.balance(&contract) - balance_before;
+    if received < min_out {
+        return Err(());
+    }
+
+    Ok(received)
+}
@@ -0,0 +1,166 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::storage::MAX_PAGE_SIZE;
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+fn setup_stream<'a>(env: &Env) -> (StellarStreamContractClient<'a>, u64, Address) {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    (client, stream_id, receiver)
+}
+
+#[test]
+fn test_vested_and_withdrawn_schedule() {
+    let env = Env::default();
+    let (client, stream_id, receiver) = setup_stream(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&stream_id, &receiver);
+
+    let schedule = client.get_vested_withdrawn_schedule(&stream_id, &5);
+    assert_eq!(schedule.len(), 5);
+
+    let expected = [(100, 0), (125, 250), (150, 500), (175, 750), (200, 1000)];
+    for (i, (timestamp, vested)) in expected.iter().enumerate() {
+        let (t, v, withdrawn) = schedule.get(i as u32).unwrap();
+        assert_eq!(t, *timestamp);
+        assert_eq!(v, *vested);
+        // Withdrawn is historical, so it is a flat line at the current amount
+        assert_eq!(withdrawn, 500);
+    }
+}
+
+#[test]
+fn test_schedule_requires_at_least_two_points() {
+    let env = Env::default();
+    let (client, stream_id, _) = setup_stream(&env);
+
+    let result = client.try_get_unlock_schedule(&stream_id, &1);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_schedule_points_capped_at_page_size() {
+    let env = Env::default();
+    let (client, stream_id, _) = setup_stream(&env);
+
+    assert_eq!(
+        client.get_unlock_schedule(&stream_id, &MAX_PAGE_SIZE).len(),
+        MAX_PAGE_SIZE
+    );
+    let result = client.try_get_unlock_schedule(&stream_id, &(MAX_PAGE_SIZE + 1));
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_schedule_for_very_long_stream() {
+    let env = Env::default();
+    let (client, existing_id, receiver) = setup_stream(&env);
+    let existing = client.get_stream(&existing_id);
+
+    // duration * points overflows u64 here
+    let end_time = u64::MAX / 2;
+    let stream_id = client.create_stream(
+        &existing.sender,
+        &receiver,
+        &existing.token,
+        &1000,
+        &0,
+        &end_time,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+
+    let schedule = client.get_unlock_schedule(&stream_id, &MAX_PAGE_SIZE);
+    assert_eq!(schedule.get(0).unwrap(), (0, 0));
+    assert_eq!(schedule.get(MAX_PAGE_SIZE - 1).unwrap(), (end_time, 1000));
+}
+
+#[test]
+fn test_next_claimable_time_for_linear_stream() {
+    let env = Env::default();
+    let (client, stream_id, receiver) = setup_stream(&env);
+
+    // 10 tokens vest per second; 250 are withdrawn at t=125
+    env.ledger().with_mut(|li| li.timestamp = 125);
+    client.withdraw(&stream_id, &receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 130);
+    assert_eq!(client.next_claimable_time(&stream_id, &100), 135);
+    assert_eq!(client.next_claimable_time(&stream_id, &50), 130);
+    assert_eq!(client.next_claimable_time(&stream_id, &750), 200);
+
+    let result = client.try_next_claimable_time(&stream_id, &751);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+}
+
+#[test]
+fn test_accrued_since_matches_unlocked() {
+    let env = Env::default();
+    let (client, stream_id, _) = setup_stream(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let stream = client.get_stream(&stream_id);
+    let unlocked = StellarStreamContract::calculate_unlocked(&stream, 150);
+    assert_eq!(
+        client.accrued_since(&stream_id, &stream.start_time),
+        unlocked
+    );
+    assert_eq!(client.accrued_since(&stream_id, &0), 500);
+    assert_eq!(client.accrued_since(&stream_id, &125), 250);
+    assert_eq!(client.accrued_since(&stream_id, &150), 0);
+
+    let result = client.try_accrued_since(&stream_id, &151);
+    assert_eq!(result, Err(Ok(Error::InvalidTimeRange)));
+}
+
+#[test]
+fn test_accrued_since_excludes_paused_time() {
+    let env = Env::default();
+    let (client, stream_id, _) = setup_stream(&env);
+    let sender = client.get_stream(&stream_id).sender;
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.pause_stream(&stream_id, &sender);
+
+    env.ledger().with_mut(|li| li.timestamp = 180);
+    assert_eq!(client.accrued_since(&stream_id, &150), 0);
+    assert_eq!(client.accrued_since(&stream_id, &100), 500);
+}
@@ -0,0 +1,210 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::types::CurveType;
+
+// Router paying a fixed rate out of its own balance, ignoring min_out so the
+// stream contract's own check is exercised
+#[contract]
+pub struct FixedRateRouter;
+
+#[contractimpl]
+impl FixedRateRouter {
+    pub fn init(env: Env, rate_bps: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("rate"), &rate_bps);
+    }
+
+    pub fn swap(
+        env: Env,
+        _token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        _min_out: i128,
+        to: Address,
+    ) -> i128 {
+        let rate_bps: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("rate"))
+            .unwrap();
+        let amount_out = amount_in * rate_bps / 10_000;
+        TokenClient::new(&env, &token_out).transfer(
+            &env.current_contract_address(),
+            &to,
+            &amount_out,
+        );
+        amount_out
+    }
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct SettlementContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    admin: Address,
+    token_a: TokenClient<'a>,
+    token_b: TokenClient<'a>,
+    token_b_id: Address,
+    router: Address,
+    receiver: Address,
+    stream_id: u64,
+}
+
+fn setup(env: &Env) -> SettlementContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let (token_a_id, token_a) = create_token_contract(env, &admin);
+    let (token_b_id, token_b) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_a_id).mint(&sender, &1000);
+
+    // Router swaps 1 A for 2 B
+    let router = env.register(FixedRateRouter, ());
+    FixedRateRouterClient::new(env, &router).init(&20_000);
+    StellarAssetClient::new(env, &token_b_id).mint(&router, &10_000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_a_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+
+    SettlementContext {
+        client,
+        admin,
+        token_a,
+        token_b,
+        token_b_id,
+        router,
+        receiver,
+        stream_id,
+    }
+}
+
+#[test]
+fn test_withdraw_settles_in_other_token() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    // At least 1.9 B per A (7 decimals)
+    ctx.client.approve_router(&ctx.admin, &ctx.router);
+    ctx.client.set_settlement(
+        &ctx.stream_id,
+        &ctx.receiver,
+        &ctx.token_b_id,
+        &ctx.router,
+        &19_000_000,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(ctx.client.withdraw(&ctx.stream_id, &ctx.receiver), 500);
+
+    assert_eq!(ctx.token_a.balance(&ctx.receiver), 0);
+    assert_eq!(ctx.token_a.balance(&ctx.router), 500);
+    assert_eq!(ctx.token_b.balance(&ctx.receiver), 1000);
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).withdrawn_amount, 500);
+}
+
+#[test]
+fn test_settlement_requires_approved_router() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let result = ctx.client.try_set_settlement(
+        &ctx.stream_id,
+        &ctx.receiver,
+        &ctx.token_b_id,
+        &ctx.router,
+        &20_000_000,
+    );
+    assert_eq!(result, Err(Ok(Error::RouterNotApproved)));
+
+    // Revoking after the fact blocks withdrawals until settlement is cleared
+    ctx.client.approve_router(&ctx.admin, &ctx.router);
+    ctx.client.set_settlement(
+        &ctx.stream_id,
+        &ctx.receiver,
+        &ctx.token_b_id,
+        &ctx.router,
+        &20_000_000,
+    );
+    ctx.client.revoke_router(&ctx.admin, &ctx.router);
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    let result = ctx.client.try_withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::RouterNotApproved)));
+
+    ctx.client.clear_settlement(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(ctx.client.withdraw(&ctx.stream_id, &ctx.receiver), 500);
+    assert_eq!(ctx.token_a.balance(&ctx.receiver), 500);
+}
+
+#[test]
+fn test_swap_below_min_out_reverts_withdrawal() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client.approve_router(&ctx.admin, &ctx.router);
+    ctx.client.set_settlement(
+        &ctx.stream_id,
+        &ctx.receiver,
+        &ctx.token_b_id,
+        &ctx.router,
+        &30_000_000,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    let result = ctx.client.try_withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::SwapFailed)));
+
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).withdrawn_amount, 0);
+    assert_eq!(ctx.token_a.balance(&ctx.router), 0);
+    assert_eq!(ctx.token_b.balance(&ctx.receiver), 0);
+}
+
+#[test]
+fn test_settlement_requires_positive_min_rate() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client.approve_router(&ctx.admin, &ctx.router);
+    for min_rate in [0, -1] {
+        let result = ctx.client.try_set_settlement(
+            &ctx.stream_id,
+            &ctx.receiver,
+            &ctx.token_b_id,
+            &ctx.router,
+            &min_rate,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    }
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).router, None);
+}
@@ -50,6 +50,7 @@ fn test_create_soulbound_stream() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -99,6 +100,7 @@ fn test_create_normal_stream_not_soulbound() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &false, // is_soulbound
     );
@@ -147,6 +149,7 @@ fn test_transfer_receiver_blocked_on_soulbound() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -199,6 +202,7 @@ fn test_transfer_receiver_allowed_on_normal_stream() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &false, // is_soulbound
     );
@@ -244,6 +248,7 @@ fn test_soulbound_flag_immutable_after_creation() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -297,6 +302,7 @@ fn test_soulbound_stream_still_withdrawable() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -353,6 +359,7 @@ fn test_soulbound_stream_cancellable_by_sender() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -412,6 +419,7 @@ fn test_get_soulbound_streams_index() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -423,6 +431,7 @@ fn test_get_soulbound_streams_index() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &true, // is_soulbound
     );
@@ -435,6 +444,7 @@ fn test_get_soulbound_streams_index() {
         &PRINCIPAL,
         &0,
         &DURATION,
+        &0,
         &CurveType::Linear,
         &false, // is_soulbound
     );
@@ -463,3 +473,102 @@ fn test_get_soulbound_streams_index() {
         "Second soulbound ID should match"
     );
 }
+
+/// # Purpose
+/// Verify that the receipt owner can latch a stream soulbound after creation
+/// # Setup
+/// Create a normal stream, then call make_soulbound as the receiver
+/// # Assertion
+/// Stream is soulbound, indexed, and transfer_receiver is rejected
+#[test]
+fn test_make_soulbound_latches_stream() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let new_receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+
+    let token_admin_client = StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&sender, &(PRINCIPAL * 10));
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &PRINCIPAL,
+        &0,
+        &DURATION,
+        &0,
+        &CurveType::Linear,
+        &false, // is_soulbound
+    );
+
+    // Only the sender or receipt owner can latch
+    let stranger = Address::generate(&env);
+    let result = client.try_make_soulbound(&stream_id, &stranger);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    client.make_soulbound(&stream_id, &receiver);
+
+    let stream = client.get_stream(&stream_id);
+    assert!(stream.is_soulbound, "Stream should now be soulbound");
+    assert_eq!(client.get_soulbound_streams().len(), 1);
+
+    let result = client.try_transfer_receiver(&stream_id, &sender, &new_receiver);
+    assert_eq!(result, Err(Ok(Error::StreamIsSoulbound)));
+}
+
+/// # Purpose
+/// Verify that the sender can make a grant non-transferable, irreversibly
+/// # Setup
+/// Create a normal stream, then call make_soulbound as the sender
+/// # Assertion
+/// Transfers fail afterwards and the latch cannot be applied or undone again
+#[test]
+fn test_sender_make_soulbound_is_one_way() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let new_receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+
+    let token_admin_client = StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&sender, &(PRINCIPAL * 10));
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &PRINCIPAL,
+        &0,
+        &DURATION,
+        &0,
+        &CurveType::Linear,
+        &false, // is_soulbound
+    );
+
+    client.make_soulbound(&stream_id, &sender);
+
+    let result = client.try_transfer_receiver(&stream_id, &sender, &new_receiver);
+    assert_eq!(result, Err(Ok(Error::StreamIsSoulbound)));
+
+    // Latching again is rejected and the flag stays set
+    let result = client.try_make_soulbound(&stream_id, &sender);
+    assert_eq!(result, Err(Ok(Error::StreamIsSoulbound)));
+    assert!(client.get_stream(&stream_id).is_soulbound);
+    assert_eq!(client.get_soulbound_streams().len(), 1);
+}
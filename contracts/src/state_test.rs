@@ -0,0 +1,128 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct StateContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    sender: Address,
+    receiver: Address,
+    arbiter: Address,
+    stream_id: u64,
+}
+
+fn setup(env: &Env) -> StateContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let arbiter = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &10000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &0,
+        &1000,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+    client.set_arbiter(&stream_id, &sender, &arbiter);
+    env.ledger().with_mut(|li| li.timestamp = 500);
+
+    StateContext {
+        client,
+        sender,
+        receiver,
+        arbiter,
+        stream_id,
+    }
+}
+
+/// Result of every mutating operation on the context's stream, in a fixed order
+fn attempt_all(env: &Env, ctx: &StateContext) -> [Result<(), Error>; 5] {
+    let new_receiver = Address::generate(env);
+    [
+        ctx.client
+            .try_withdraw(&ctx.stream_id, &ctx.receiver)
+            .map(|_| ())
+            .map_err(|e| e.unwrap()),
+        ctx.client
+            .try_top_up_stream(&ctx.stream_id, &ctx.sender, &100)
+            .map(|_| ())
+            .map_err(|e| e.unwrap()),
+        ctx.client
+            .try_pause_stream(&ctx.stream_id, &ctx.sender)
+            .map(|_| ())
+            .map_err(|e| e.unwrap()),
+        ctx.client
+            .try_transfer_receiver(&ctx.stream_id, &ctx.sender, &new_receiver)
+            .map(|_| ())
+            .map_err(|e| e.unwrap()),
+        ctx.client
+            .try_cancel(&ctx.stream_id, &ctx.sender)
+            .map(|_| ())
+            .map_err(|e| e.unwrap()),
+    ]
+}
+
+#[test]
+fn test_frozen_stream_rejects_every_mutation() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client.freeze_stream(&ctx.stream_id, &ctx.arbiter);
+
+    for result in attempt_all(&env, &ctx) {
+        assert_eq!(result, Err(Error::StreamFrozen));
+    }
+}
+
+#[test]
+fn test_cancelled_stream_rejects_every_mutation() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client.cancel(&ctx.stream_id, &ctx.sender);
+
+    for result in attempt_all(&env, &ctx) {
+        assert_eq!(result, Err(Error::AlreadyCancelled));
+    }
+}
+
+#[test]
+fn test_paused_stream_only_blocks_withdrawal() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.client.pause_stream(&ctx.stream_id, &ctx.sender);
+
+    let [withdraw, top_up, pause, transfer, cancel] = attempt_all(&env, &ctx);
+    assert_eq!(withdraw, Err(Error::StreamPaused));
+    assert_eq!(top_up, Ok(()));
+    assert_eq!(pause, Ok(()));
+    assert_eq!(transfer, Ok(()));
+    assert_eq!(cancel, Ok(()));
+}
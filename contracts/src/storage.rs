@@ -4,6 +4,57 @@ pub const STREAM_COUNT: Symbol = symbol_short!("STR_CNT");
 pub const PROPOSAL_COUNT: Symbol = symbol_short!("PROP_CNT");
 pub const RECEIPT: Symbol = symbol_short!("RECEIPT");
 pub const RESTRICTED_ADDRESSES: Symbol = symbol_short!("RESTRICT");
+/// (FALLBACK, stream_id) -> (fallback, inactivity seconds, set at)
+pub const FALLBACK: Symbol = symbol_short!("FALLBACK");
+/// (RCV_HIST, receiver) -> IDs of every unpurged stream paying receiver,
+/// cancelled ones included
+pub const RECEIVER_HISTORY: Symbol = symbol_short!("RCV_HIST");
+/// BACKDATE -> (max seconds start_time may precede creation, backdating unrestricted)
+pub const BACKDATE: Symbol = symbol_short!("BACKDATE");
+/// (WD_FAIL, stream_id) -> (first failed transfer probe at, withdrawn amount then)
+pub const WD_FAIL: Symbol = symbol_short!("WD_FAIL");
+/// (RCPT_APR, stream_id) -> spender the receipt owner approved to transfer it
+pub const RECEIPT_APPROVAL: Symbol = symbol_short!("RCPT_APR");
+/// (DISPUTED, stream_id) -> party that raised the dispute awaiting a ruling
+pub const DISPUTE_RAISED: Symbol = symbol_short!("DISPUTED");
+/// DRN_SKIP -> IDs of streams the emergency drain could not settle
+pub const DRAIN_SKIPPED: Symbol = symbol_short!("DRN_SKIP");
+/// (WQ_CURSOR, token) -> next stream ID process_withdraw_queue scans
+pub const WITHDRAW_QUEUE_CURSOR: Symbol = symbol_short!("WQ_CURSOR");
+/// Upper bound on distinct tokens tracked for outstanding obligations
+pub const MAX_TRACKED_TOKENS: u32 = 50;
+/// Upper bound on approvers recorded for a single proposal
+pub const MAX_PROPOSAL_APPROVERS: u32 = 20;
+/// Delay between scheduling an emergency drain and being able to run it
+pub const EMERGENCY_DRAIN_DELAY: u64 = 172_800;
+/// Upper bound on items processed by a single batch call
+pub const MAX_BATCH: u32 = 25;
+/// Upper bound on streams returned by one get_streams_page call
+pub const MAX_PAGE_SIZE: u32 = 50;
+/// Upper bound on withdrawal prices kept per USD-pegged stream
+pub const MAX_WITHDRAW_PRICES: u32 = 50;
+/// Default time a cancelled stream stays queryable before it can be purged
+pub const DEFAULT_PURGE_RETENTION: u64 = 2_592_000;
+/// Time after end_time before a sender may reclaim a stream its oracle stranded
+pub const STUCK_RECLAIM_GRACE: u64 = 7_776_000;
+/// Time a receiver must keep rejecting transfers before the sender may redirect
+pub const REDIRECT_GRACE: u64 = 604_800;
+/// Default for how far before creation a stream's start_time may be
+pub const DEFAULT_MAX_BACKDATING: u64 = 2_592_000;
+/// Length of a month for monthly streams: 30 days, whatever the calendar says
+pub const SECONDS_PER_MONTH: u64 = 2_592_000;
+/// Most a proposal's deadline can be pushed past the one it was created with
+pub const MAX_PROPOSAL_EXTENSION: u64 = 604_800;
+/// Minimum admin approvals an upgrade proposal must require
+pub const MIN_UPGRADE_APPROVALS: u32 = 2;
+/// Period the loyalty reward rate is quoted over
+pub const LOYALTY_PERIOD: u64 = 2_592_000;
+/// Shortest gap between withdrawals that earns a loyalty bonus
+pub const LOYALTY_MIN_IDLE: u64 = 86_400;
+/// Upper bound on the loyalty reward rate, in bps per LOYALTY_PERIOD
+pub const MAX_LOYALTY_RATE_BPS: u32 = 100;
+/// Upper bound on the protocol fee taken from new streams (10%)
+pub const MAX_FEE_BPS: u32 = 1_000;
 #[allow(dead_code)]
 pub const FLASH_LOAN_LOCK: Symbol = symbol_short!("FL_LOCK");
 #[allow(dead_code)]
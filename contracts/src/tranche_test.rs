@@ -0,0 +1,58 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env,
+};
+
+use crate::errors::Error;
+use crate::types::CurveType;
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_tranches_vest_on_their_own_schedules() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &3000);
+
+    let tranches = vec![
+        &env,
+        (1000_i128, 100_u64, 200_u64, CurveType::Linear),
+        (2000_i128, 200_u64, 400_u64, CurveType::Linear),
+    ];
+    let group_id = client.create_tranched_stream(&sender, &receiver, &token_id, &tranches);
+
+    let group = client.get_tranche_group(&group_id);
+    assert_eq!(group.len(), 2);
+    let first = group.get(0).unwrap();
+    let second = group.get(1).unwrap();
+    assert_eq!(token.balance(&contract_id), 3000);
+
+    // First tranche is half vested, second has not started
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    assert_eq!(client.withdraw(&first, &receiver), 500);
+    let result = client.try_withdraw(&second, &receiver);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    // First tranche is done, second is a quarter vested
+    env.ledger().with_mut(|li| li.timestamp = 250);
+    assert_eq!(client.withdraw(&first, &receiver), 500);
+    assert_eq!(client.withdraw(&second, &receiver), 500);
+    assert_eq!(token.balance(&receiver), 1500);
+}
@@ -7,9 +7,7 @@ use soroban_sdk::{token, Address, Env};
 /// Test context for TTL stress testing
 struct TtlTestContext {
     env: Env,
-    contract_id: Address,
     client: StellarStreamContractClient<'static>,
-    token_admin: Address,
     token: token::StellarAssetClient<'static>,
     token_id: Address,
 }
@@ -40,9 +38,7 @@ fn setup_ttl_test() -> TtlTestContext {
 
     TtlTestContext {
         env,
-        contract_id,
         client,
-        token_admin,
         token,
         token_id,
     }
@@ -93,6 +89,7 @@ fn test_four_year_stream_ttl_survival() {
         &amount,
         &start_time,
         &end_time,
+        &start_time,
         &CurveType::Linear,
         &false, // Not soulbound
     );
@@ -183,7 +180,7 @@ fn test_multiple_streams_ttl_survival() {
     // Create three streams
     let stream_id_1 = ctx.client.create_stream(
         &sender, &receiver1, &ctx.token_id, &amount_per_stream,
-        &start_time, &end_time, &CurveType::Linear, &false,
+        &start_time, &end_time, &start_time, &CurveType::Linear, &false,
     );
 
     // Create second stream 6 months later
@@ -191,7 +188,7 @@ fn test_multiple_streams_ttl_survival() {
     let stream_id_2 = ctx.client.create_stream(
         &sender, &receiver2, &ctx.token_id, &amount_per_stream,
         &(start_time + SECONDS_PER_YEAR / 2), 
-        &(end_time + SECONDS_PER_YEAR / 2), 
+        &(end_time + SECONDS_PER_YEAR / 2), &(start_time + SECONDS_PER_YEAR / 2), 
         &CurveType::Linear, &false,
     );
 
@@ -200,7 +197,7 @@ fn test_multiple_streams_ttl_survival() {
     let stream_id_3 = ctx.client.create_stream(
         &sender, &receiver3, &ctx.token_id, &amount_per_stream,
         &(start_time + SECONDS_PER_YEAR), 
-        &(end_time + SECONDS_PER_YEAR), 
+        &(end_time + SECONDS_PER_YEAR), &(start_time + SECONDS_PER_YEAR), 
         &CurveType::Linear, &false,
     );
 
@@ -259,7 +256,7 @@ fn test_paused_stream_ttl_survival() {
 
     let stream_id = ctx.client.create_stream(
         &sender, &receiver, &ctx.token_id, &amount,
-        &start_time, &end_time, &CurveType::Linear, &false,
+        &start_time, &end_time, &start_time, &CurveType::Linear, &false,
     );
 
     // Pause the stream after 6 months
@@ -313,7 +310,7 @@ fn test_cancelled_stream_ttl_and_recreation() {
     // Create initial stream
     let stream_id_1 = ctx.client.create_stream(
         &sender, &receiver, &ctx.token_id, &amount,
-        &start_time, &end_time, &CurveType::Linear, &false,
+        &start_time, &end_time, &start_time, &CurveType::Linear, &false,
     );
 
     // Jump forward 1 year and partially withdraw
@@ -340,7 +337,7 @@ fn test_cancelled_stream_ttl_and_recreation() {
     
     let stream_id_2 = ctx.client.create_stream(
         &sender, &receiver, &ctx.token_id, &amount,
-        &new_start_time, &new_end_time, &CurveType::Linear, &false,
+        &new_start_time, &new_end_time, &new_start_time, &CurveType::Linear, &false,
     );
 
     // Jump forward and complete the new stream
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, BytesN, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, Symbol, Vec};
 
 // Interest distribution strategies
 // Bits can be combined: e.g., 0b011 = 50% sender, 50% receiver
@@ -21,6 +21,24 @@ pub const INTEREST_SPLIT_ALL: u32 = 0b111; // 7: 33/33/33 split
 pub enum CurveType {
     Linear = 0,
     Exponential = 1,
+    /// Linear between consecutive milestones, with (start, 0%) and (end, 100%) as endpoints
+    PiecewiseLinear = 2,
+    /// Front-loaded: unlocked = total * sqrt(elapsed / duration)
+    Logarithmic = 3,
+    /// Equal tranches released every interval, per the stream's StepConfig
+    Step = 4,
+}
+
+// Which part of a stream's balance counts as voting power
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VotingPowerMode {
+    /// Unlocked but not yet withdrawn balance
+    Unlocked = 0,
+    /// Balance still locked in the stream (commitment)
+    Locked = 1,
+    /// Everything not yet withdrawn, locked or unlocked
+    Total = 2,
 }
 
 // Role definitions for RBAC
@@ -31,6 +49,17 @@ pub enum Role {
     Pauser,            // Can pause/unpause contract
     TreasuryManager,   // Can update fees and treasury address
     ComplianceOfficer, // Can execute regulatory clawbacks
+    Guardian,          // Can drain and disable the contract in an emergency
+}
+
+/// Roles, restriction and stream counts for a single address
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddressStatus {
+    pub roles: Vec<Role>,
+    pub restricted: bool,
+    pub sending_streams: u32,   // Active streams the address funds
+    pub receiving_streams: u32, // Active streams paying the address
 }
 
 #[contracttype]
@@ -56,6 +85,46 @@ pub struct Milestone {
     pub percentage: u32,
 }
 
+/// Schedule for `create_milestone_stream`, bundled so a milestone stream can
+/// carry a cliff and a vault without exceeding the parameter cap
+#[contracttype]
+#[derive(Clone)]
+pub struct MilestoneStreamParams {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub cliff_time: u64,
+    pub milestones: Vec<Milestone>,
+    pub curve_type: CurveType,
+    pub is_soulbound: bool,
+    pub vault_address: Option<Address>,
+}
+
+/// Release schedule for Step curves: `tranches` equal parts, one per `interval`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StepConfig {
+    pub interval: u64,
+    pub tranches: u32,
+}
+
+/// Everything `calculate_unlocked` reads from a stream, so clients can
+/// reproduce the unlocked amount at any time off-chain. Streams have no
+/// upfront unlock: nothing vests before `start_time` or `cliff_time`.
+#[contracttype]
+#[derive(Clone)]
+pub struct CurveParams {
+    pub curve_type: CurveType,
+    pub total_amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub cliff_time: u64,
+    pub milestones: Vec<Milestone>,
+    pub step_config: StepConfig,
+    pub total_paused_duration: u64, // Completed pauses, already counted
+    pub paused_since: Option<u64>,  // Start of the ongoing pause, if paused
+    pub pause_budget: u64,          // 0 = unlimited
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct Stream {
@@ -65,6 +134,8 @@ pub struct Stream {
     pub total_amount: i128,
     pub start_time: u64,
     pub end_time: u64,
+    /// Nothing unlocks before this time; equal to start_time when there is no cliff
+    pub cliff_time: u64,
     pub withdrawn: i128,
     pub withdrawn_amount: i128,
     pub cancelled: bool,
@@ -97,23 +168,70 @@ pub struct Stream {
     pub arbiter: Option<Address>,
     /// If true, stream is frozen pending dispute resolution
     pub is_frozen: bool,
+    /// Maximum cumulative pause time in seconds (0 = unlimited)
+    pub pause_budget: u64,
+    /// If true, transfer_receiver needs the current receiver's approval
+    pub require_receiver_consent: bool,
+    /// Timestamp of the last create, withdraw, pause, unpause or top-up
+    pub last_activity: u64,
+    /// Ledger sequence the stream was created in
+    pub created_ledger: u32,
+    /// Ledger timestamp the stream was created at
+    pub created_at: u64,
+    /// Token vested funds are swapped into on withdrawal, if any
+    pub settlement_token: Option<Address>,
+    /// Approved router performing the settlement swap
+    pub router: Option<Address>,
+    /// Minimum settlement token units received per whole stream token swapped
+    pub settlement_min_rate: i128,
+    /// Tranche schedule for Step curves; zero tranches on every other stream
+    pub step_config: StepConfig,
+    /// Receiver transfers are rejected before this timestamp (0 = no lock).
+    /// Unlike soulbound, the lock lifts on its own.
+    pub transfer_locked_until: u64,
 }
 
 // Legacy Stream struct (v1) - for migration example
 // This represents an older version without cliff_time
+/// Stream that a CreateStream proposal opens on execution
 #[contracttype]
-#[derive(Clone)]
-pub struct StreamProposal {
-    pub sender: Address,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposedStream {
     pub receiver: Address,
     pub token: Address,
     pub total_amount: i128,
     pub start_time: u64,
     pub end_time: u64,
+}
+
+/// Action a proposal performs once it reaches its approval threshold
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProposalAction {
+    CreateStream(ProposedStream),
+    /// Fee in basis points and the treasury receiving it
+    SetFee(u32, Address),
+    RestrictAddress(Address),
+    /// Hash of the uploaded WASM to switch the contract to
+    Upgrade(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Proposal {
+    pub sender: Address,
+    pub action: ProposalAction,
     pub approvers: Vec<Address>,
+    /// Addresses allowed to approve; empty means anyone may
+    pub authorized_approvers: Vec<Address>,
     pub required_approvals: u32,
     pub deadline: u64,
+    /// Deadline the proposal was created with; extensions are capped from it
+    pub original_deadline: u64,
     pub executed: bool,
+    /// If true, funds were pulled into the contract at proposal creation
+    pub escrowed: bool,
+    pub cancelled: bool,
 }
 
 #[contracttype]
@@ -155,6 +273,47 @@ pub enum DataKey {
     ApprovedVaults,         // Vec<Address> of approved lending vaults
     VaultShares(u64),       // Vault shares for stream_id
     VotingDelegate(u64),    // Voting delegate for stream_id
+    PendingReceiver(u64),   // Receiver change awaiting consent for stream_id
+    FeeConfig,              // Option<FeeConfig>: protocol fee settings
+    Outstanding(Address),   // Running total still owed to streams in a token
+    OutstandingTokens,      // Vec<Address> of tokens with an outstanding counter
+    VaultCap(Address),      // Max principal a vault may hold across streams
+    VaultTotal(Address),    // Principal currently deposited into a vault
+    ReceiverIndex(Address), // Vec<u64> of active stream IDs for a receiver
+    SenderIndex(Address),   // Vec<u64> of active stream IDs for a sender
+    MaxStreamsPerReceiver,  // u32 cap on active streams per receiver (0 = unlimited)
+    DrainScheduled,         // u64 timestamp after which emergency_drain may run
+    DrainCursor,            // Next stream ID to settle in an emergency drain
+    Disabled,               // bool set once an emergency drain has completed
+    DefaultArbiter,         // Arbiter applied to streams created without one
+    WithdrawPrices(u64),    // Vec<(u64, i128)> oracle prices used by withdrawals
+    PurgeRetention,         // u64 seconds a cancelled stream is kept before purge
+    VotingPowerMode,        // VotingPowerMode used by get_voting_power
+    Delegations(Address),   // Vec<u64> of streams delegated to an address
+    TrancheGroupCount,      // Next tranche group ID
+    TrancheGroup(u64),      // Vec<u64> of stream IDs in a tranche group
+    AllowlistEnabled,       // bool: only allowlisted tokens may be streamed
+    AllowedTokens,          // Vec<Address> of allowlisted tokens
+    BlockedTokens,          // Vec<Address> of tokens that may never be streamed
+    TokenPaused(Address),   // bool: withdrawals and creation halted for a token
+    UsdWithdrawn(u64),      // USD value already paid out by a USD-pegged stream
+    Activated(u64),         // bool set on a stream's first successful withdrawal
+    LoyaltyRate,            // u32 bps per LOYALTY_PERIOD paid on idle balances
+    LoyaltyPool(Address),   // Reward balance available for loyalty bonuses
+    LoyaltyAccrual(u64),    // (checkpoint, total bonus paid) for stream_id
+    ApprovedRouters,        // Vec<Address> of routers allowed for settlement
+    SoulboundRebuild,       // (next stream ID, Vec<u64> found) while rebuilding
+    WithdrawOnly,           // bool: only withdrawals and cancellations allowed
+    // Address withdrawals from stream_id are sent to instead of its receiver
+    WithdrawDestination(u64),
+    // (grace seconds, refund to sender) once stream_id opts into auto-settle
+    AutoCancel(u64),
+    // u32: process_withdraw_queue serves higher values first (default 0)
+    StreamPriority(u64),
+    // bool: Guardian emergency stop on stream operations
+    GlobalPaused,
+    // i128: lowest total the receiver lets stream_id be reduced to
+    ReductionFloor(u64),
 }
 
 #[contracttype]
@@ -183,7 +342,17 @@ pub struct StreamCreatedEvent {
 pub struct StreamClaimEvent {
     pub stream_id: u64,
     pub claimer: Address,
+    /// Address the tokens were sent to (the receiver unless withdrawn elsewhere)
+    pub destination: Address,
+    /// Informational tag, e.g. an exchange deposit memo
+    pub memo: Option<BytesN<32>>,
+    /// Total transferred: principal_amount + interest_amount + loyalty_bonus
     pub amount: i128,
+    pub principal_amount: i128,
+    /// Vault yield realized by this claim (0 for non-vault streams)
+    pub interest_amount: i128,
+    /// Reward pool bonus for funds left streaming (0 when disabled)
+    pub loyalty_bonus: i128,
     pub total_claimed: i128,
     pub timestamp: u64,
 }
@@ -217,6 +386,15 @@ pub struct StreamFrozenEvent {
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct DisputeRaisedEvent {
+    pub stream_id: u64,
+    pub raised_by: Address,
+    pub arbiter: Address,
+    pub timestamp: u64,
+}
+
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct DisputeResolvedEvent {
@@ -238,6 +416,147 @@ pub struct StreamToppedUpEvent {
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamAmendedEvent {
+    pub stream_id: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub curve_type: CurveType,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ReceiverChangeRequestedEvent {
+    pub stream_id: u64,
+    pub current_receiver: Address,
+    pub new_receiver: Address,
+    pub timestamp: u64,
+}
+
+/// Protocol fee settings. Absent from storage until a TreasuryManager sets it,
+/// so "not configured" stays distinct from an explicit 0 bps fee.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    pub fee_bps: u32,
+    pub treasury: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct FeeCollectedEvent {
+    pub stream_id: u64,
+    pub token: Address,
+    pub treasury: Address,
+    pub fee_bps: u32,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamActivatedEvent {
+    pub stream_id: u64,
+    pub receiver: Address,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamReducedEvent {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub reduced_by: i128,
+    pub new_total: i128,
+    pub new_end_time: u64,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamRescheduledEvent {
+    pub stream_id: u64,
+    pub sender: Address,
+    pub old_end_time: u64,
+    pub new_end_time: u64,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamMetadataUpdatedEvent {
+    pub stream_id: u64,
+    pub old_metadata: Option<BytesN<32>>,
+    pub new_metadata: Option<BytesN<32>>,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct BatchOperationEvent {
+    pub operation: Symbol,
+    pub count: u32,
+    pub total_amount: i128,
+    pub stream_ids_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SettlementEvent {
+    pub stream_id: u64,
+    pub router: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamPurgedEvent {
+    pub stream_id: u64,
+    pub admin: Address,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct EmergencyDrainEvent {
+    pub guardian: Address,
+    pub settled_up_to: u64,
+    pub completed: bool,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct WithdrawOnlyEvent {
+    pub guardian: Address,
+    pub enabled: bool,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct GlobalPauseEvent {
+    pub guardian: Address,
+    pub paused: bool,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamAutoSettledEvent {
+    pub stream_id: u64,
+    pub recipient: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct ReceiptTransferredEvent {
@@ -289,6 +608,26 @@ pub struct ProposalCreatedEvent {
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ActionProposalCreatedEvent {
+    pub proposal_id: u64,
+    pub sender: Address,
+    pub action: ProposalAction,
+    pub required_approvals: u32,
+    pub deadline: u64,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ProposalCancelledEvent {
+    pub proposal_id: u64,
+    pub sender: Address,
+    pub refunded: i128,
+    pub timestamp: u64,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct ReceiptMetadata {
@@ -0,0 +1,387 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env,
+};
+
+use crate::errors::Error;
+use crate::storage::STUCK_RECLAIM_GRACE;
+
+// Mock price oracle returning a configurable (price, timestamp) pair
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &(price, timestamp));
+    }
+
+    pub fn price(env: Env) -> (i128, u64) {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap_or((0, 0))
+    }
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct UsdPegContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    oracle: MockOracleClient<'a>,
+    sender: Address,
+    receiver: Address,
+    token_id: Address,
+}
+
+fn setup(env: &Env) -> UsdPegContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let oracle_id = env.register(MockOracle, ());
+    let oracle = MockOracleClient::new(env, &oracle_id);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token_id, _) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &1_000_000_000_000);
+
+    UsdPegContext {
+        client,
+        oracle,
+        sender,
+        receiver,
+        token_id,
+    }
+}
+
+#[test]
+fn test_create_usd_pegged_stream() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    // $0.50 per token
+    ctx.oracle.set_price(&5_000_000, &1_000);
+
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000, // $500
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    let stream = ctx.client.get_stream(&stream_id);
+    assert!(stream.is_usd_pegged);
+    assert_eq!(stream.usd_amount, 5_000_000_000);
+    assert_eq!(stream.total_amount, 10_000_000_000);
+}
+
+#[test]
+fn test_usd_pegged_stream_pays_fee_on_both_amounts() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    ctx.client.initialize(&admin);
+    ctx.client.set_fee_config(&admin, &100, &treasury);
+
+    // $0.50 per token
+    ctx.oracle.set_price(&5_000_000, &1_000);
+
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000, // $500
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    // 1% of both the tokens and the USD value goes to the treasury
+    let stream = ctx.client.get_stream(&stream_id);
+    assert_eq!(stream.total_amount, 9_900_000_000);
+    assert_eq!(stream.usd_amount, 4_950_000_000);
+    let token = TokenClient::new(&env, &ctx.token_id);
+    assert_eq!(token.balance(&treasury), 100_000_000);
+}
+
+#[test]
+fn test_usd_pegged_stream_rejects_zero_price() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.oracle.set_price(&0, &1_000);
+
+    let result = ctx.client.try_create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    assert_eq!(result, Err(Ok(Error::OracleFailed)));
+}
+
+#[test]
+fn test_usd_pegged_stream_rejects_absurd_price() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    // Far outside the configured bounds
+    ctx.oracle.set_price(&i128::MAX, &1_000);
+
+    let result = ctx.client.try_create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    assert_eq!(result, Err(Ok(Error::PriceOutOfBounds)));
+}
+
+#[test]
+fn test_usd_pegged_stream_rejects_zero_token_amount() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    // Price in bounds, but $0.0000001 buys less than one token unit
+    ctx.oracle.set_price(&100_000_000, &1_000);
+
+    let result = ctx.client.try_create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &1_000_000_000,
+    );
+
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_usd_pegged_withdrawals_record_oracle_price() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.oracle.set_price(&5_000_000, &1_000);
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    ctx.oracle.set_price(&4_000_000, &1_500);
+    ctx.client.withdraw(&stream_id, &ctx.receiver);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_800);
+    ctx.oracle.set_price(&6_000_000, &1_800);
+    ctx.client.withdraw(&stream_id, &ctx.receiver);
+
+    let prices = ctx.client.get_withdraw_prices(&stream_id);
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices.get(0).unwrap(), (1_500, 4_000_000));
+    assert_eq!(prices.get(1).unwrap(), (1_800, 6_000_000));
+}
+
+#[test]
+fn test_usd_pegged_final_withdrawal_capped_at_deposit() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let token = TokenClient::new(&env, &ctx.token_id);
+
+    // $500 at $0.50 deposits 1_000 tokens (7 decimals)
+    ctx.oracle.set_price(&5_000_000, &1_000);
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    // Half vested at the creation price: $250 is 500 tokens
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    ctx.oracle.set_price(&5_000_000, &1_500);
+    assert_eq!(
+        ctx.client.withdraw(&stream_id, &ctx.receiver),
+        5_000_000_000
+    );
+
+    // The dollar has appreciated against the token: the remaining $250 would
+    // be 1_000 tokens at $0.25, but only 500 are left
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    ctx.oracle.set_price(&2_500_000, &2_000);
+    assert_eq!(
+        ctx.client.withdraw(&stream_id, &ctx.receiver),
+        5_000_000_000
+    );
+
+    let stream = ctx.client.get_stream(&stream_id);
+    assert_eq!(stream.withdrawn_amount, stream.total_amount);
+    assert_eq!(token.balance(&ctx.receiver), 10_000_000_000);
+    assert_eq!(token.balance(&ctx.client.address), 0);
+}
+
+#[test]
+fn test_sender_reclaims_stream_stranded_by_dead_oracle() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let token = TokenClient::new(&env, &ctx.token_id);
+
+    let admin = Address::generate(&env);
+    ctx.client.initialize(&admin);
+
+    ctx.oracle.set_price(&5_000_000, &1_000);
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+    let sender_before = token.balance(&ctx.sender);
+
+    // Oracle never updates again, so every withdrawal sees a stale price
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+    let result = ctx.client.try_withdraw(&stream_id, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::OracleFailed)));
+
+    let result = ctx
+        .client
+        .try_sender_reclaim_stuck(&ctx.sender, &admin, &stream_id);
+    assert_eq!(result, Err(Ok(Error::TimelockNotElapsed)));
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 2_000 + STUCK_RECLAIM_GRACE);
+    let refund = ctx
+        .client
+        .sender_reclaim_stuck(&ctx.sender, &admin, &stream_id);
+
+    assert_eq!(refund, 10_000_000_000);
+    assert_eq!(token.balance(&ctx.sender), sender_before + refund);
+    assert!(ctx.client.get_stream(&stream_id).cancelled);
+}
+
+#[test]
+fn test_get_withdrawable_quotes_usd_pegged_stream() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.oracle.set_price(&5_000_000, &1_000);
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    // $250 vested at $0.40 is 625 tokens; quoting records no price
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    ctx.oracle.set_price(&4_000_000, &1_500);
+    assert_eq!(ctx.client.get_withdrawable(&stream_id), 6_250_000_000);
+    assert_eq!(ctx.client.get_withdraw_prices(&stream_id).len(), 0);
+
+    assert_eq!(
+        ctx.client.withdraw(&stream_id, &ctx.receiver),
+        6_250_000_000
+    );
+    assert_eq!(ctx.client.get_withdrawable(&stream_id), 0);
+}
+
+#[test]
+fn test_usd_pegged_partial_withdrawal_keeps_the_rest_owed() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    ctx.oracle.set_price(&5_000_000, &1_000);
+    let stream_id = ctx.client.create_usd_pegged_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &5_000_000_000,
+        &1_000,
+        &2_000,
+        &ctx.oracle.address,
+        &3600,
+        &100_000,
+        &10_000_000,
+    );
+
+    // $250 vested is 500 tokens; taking 200 leaves $150 owed
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    ctx.oracle.set_price(&5_000_000, &1_500);
+    assert_eq!(
+        ctx.client
+            .withdraw_amount(&stream_id, &ctx.receiver, &2_000_000_000),
+        2_000_000_000
+    );
+    assert_eq!(ctx.client.get_withdrawable(&stream_id), 3_000_000_000);
+}
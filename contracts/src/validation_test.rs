@@ -0,0 +1,69 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
+
+use crate::errors::Error;
+use crate::types::{CurveType, Role};
+
+#[test]
+fn test_validate_stream_params_reports_every_violation() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let officer = Address::generate(&env);
+    client.initialize(&admin);
+    client.grant_role(&admin, &officer, &Role::ComplianceOfficer);
+
+    let receiver = Address::generate(&env);
+    let token = Address::generate(&env);
+    client.restrict_address(&officer, &receiver);
+
+    // Restricted receiver, inverted time range and zero amount
+    let errors = client.validate_stream_params(
+        &receiver,
+        &token,
+        &0,
+        &200,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &None,
+    );
+
+    assert_eq!(
+        errors,
+        vec![
+            &env,
+            Error::AddressRestricted,
+            Error::InvalidTimeRange,
+            Error::InvalidAmount,
+        ]
+    );
+}
+
+#[test]
+fn test_validate_stream_params_accepts_valid_input() {
+    let env = Env::default();
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let errors = client.validate_stream_params(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &1000,
+        &100,
+        &200,
+        &100,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &None,
+    );
+
+    assert!(errors.is_empty());
+}
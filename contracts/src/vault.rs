@@ -47,8 +47,13 @@ pub fn withdraw_from_vault(env: &Env, vault: &Address, shares: i128) -> Result<i
         return Err(());
     }
 
+    // A vault that traps is reported as a failure instead of aborting the
+    // caller, which may want to skip the stream
     let vault_client = VaultClient::new(env, vault);
-    let amount = vault_client.withdraw(&env.current_contract_address(), &shares);
+    let amount = match vault_client.try_withdraw(&env.current_contract_address(), &shares) {
+        Ok(Ok(amount)) => amount,
+        _ => return Err(()),
+    };
 
     if amount <= 0 {
         return Err(());
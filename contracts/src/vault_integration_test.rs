@@ -112,6 +112,7 @@ fn test_stream_survives_vault_pause() {
         &10000,
         &100,
         &300,
+        &100,
         &CurveType::Linear,
         &false,
     );
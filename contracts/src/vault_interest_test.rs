@@ -0,0 +1,380 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, Symbol, TryFromVal, Vec,
+};
+
+use crate::errors::Error;
+use crate::types::{CurveType, StreamClaimEvent, INTEREST_TO_RECEIVER, INTEREST_TO_SENDER};
+
+// Vault whose share price tracks its token balance, so minting to it simulates yield.
+// `set_failing` makes withdrawals pay nothing, like a vault that can't redeem.
+#[contract]
+pub struct AppreciatingVault;
+
+#[contractimpl]
+impl AppreciatingVault {
+    pub fn init(env: Env, token: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("token"), &token);
+    }
+
+    pub fn deposit(env: Env, _from: Address, amount: i128) -> i128 {
+        let shares: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("shares"))
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("shares"), &(shares + amount));
+        amount
+    }
+
+    pub fn set_failing(env: Env, failing: bool) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("failing"), &failing);
+    }
+
+    pub fn withdraw(env: Env, to: Address, shares: i128) -> i128 {
+        if env
+            .storage()
+            .instance()
+            .get(&symbol_short!("failing"))
+            .unwrap_or(false)
+        {
+            return 0;
+        }
+        let amount = Self::get_value(env.clone(), shares);
+        let total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("shares"))
+            .unwrap();
+        env.storage()
+            .instance()
+            .set(&symbol_short!("shares"), &(total_shares - shares));
+
+        let token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("token"))
+            .unwrap();
+        TokenClient::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+        amount
+    }
+
+    pub fn get_value(env: Env, shares: i128) -> i128 {
+        let token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("token"))
+            .unwrap();
+        let balance = TokenClient::new(&env, &token).balance(&env.current_contract_address());
+        let total_shares: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("shares"))
+            .unwrap();
+        shares * balance / total_shares
+    }
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+#[test]
+fn test_claim_event_splits_principal_and_interest() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&sender, &1000);
+
+    let vault = env.register(AppreciatingVault, ());
+    AppreciatingVaultClient::new(&env, &vault).init(&token_id);
+    client.approve_vault(&admin, &vault);
+
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+
+    // Vault earns 10% yield
+    token_admin_client.mint(&vault, &100);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let claimed = client.withdraw(&stream_id, &receiver);
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("claim"))
+        })
+        .unwrap();
+    let event = StreamClaimEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.principal_amount, 500);
+    assert_eq!(event.interest_amount, 50);
+    assert_eq!(event.principal_amount + event.interest_amount, event.amount);
+    assert_eq!(event.total_claimed, 500);
+
+    assert_eq!(claimed, 550);
+    assert_eq!(token.balance(&receiver), 550);
+}
+
+#[test]
+fn test_update_interest_strategy_before_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &1000);
+
+    let vault = env.register(AppreciatingVault, ());
+    AppreciatingVaultClient::new(&env, &vault).init(&token_id);
+    client.approve_vault(&admin, &vault);
+
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+
+    // Only the strategy bits are accepted
+    let result = client.try_update_interest_strategy(&stream_id, &sender, &receiver, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    let result = client.try_update_interest_strategy(&stream_id, &sender, &receiver, &8);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+    // 100% to receiver, renegotiated to a 50/50 split
+    client.update_interest_strategy(&stream_id, &sender, &receiver, &INTEREST_TO_RECEIVER);
+    let split = INTEREST_TO_SENDER | INTEREST_TO_RECEIVER;
+    client.update_interest_strategy(&stream_id, &sender, &receiver, &split);
+
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == sender));
+    assert!(auths.iter().any(|(addr, _)| *addr == receiver));
+    assert_eq!(client.get_stream(&stream_id).interest_strategy, split);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.withdraw(&stream_id, &receiver);
+    let result =
+        client.try_update_interest_strategy(&stream_id, &sender, &receiver, &INTEREST_TO_RECEIVER);
+    assert_eq!(result, Err(Ok(Error::StreamAlreadyClaimed)));
+}
+
+#[test]
+fn test_withdraw_batch_reverts_when_vault_fails_to_redeem() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &2000);
+
+    let vault = env.register(AppreciatingVault, ());
+    let vault_client = AppreciatingVaultClient::new(&env, &vault);
+    vault_client.init(&token_id);
+    vault_client.set_failing(&true);
+    client.approve_vault(&admin, &vault);
+
+    let plain_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+    let vault_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault),
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let stream_ids = vec![&env, plain_id, vault_id];
+    let result = client.try_withdraw_batch(&stream_ids, &receiver, &false);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    // Nothing is marked as paid and the vault keeps the shares
+    assert_eq!(client.get_stream(&plain_id).withdrawn_amount, 0);
+    assert_eq!(client.get_stream(&vault_id).withdrawn_amount, 0);
+    assert_eq!(client.get_withdrawable(&vault_id), 500);
+    assert_eq!(token.balance(&receiver), 0);
+}
+
+#[test]
+fn test_cancel_redeems_vault_shares() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&sender, &1000);
+
+    let vault = env.register(AppreciatingVault, ());
+    AppreciatingVaultClient::new(&env, &vault).init(&token_id);
+    client.approve_vault(&admin, &vault);
+
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+
+    // Vault earns 10% yield, shared pro rata by the split
+    token_admin_client.mint(&vault, &100);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    client.cancel(&stream_id, &sender);
+
+    assert_eq!(token.balance(&receiver), 550);
+    assert_eq!(token.balance(&sender), 550);
+    assert_eq!(token.balance(&vault), 0);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(client.get_vault_deposited(&vault), 0);
+}
+
+#[test]
+fn test_cancel_partial_redeems_vault_shares() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&sender, &2000);
+
+    let vault = env.register(AppreciatingVault, ());
+    AppreciatingVaultClient::new(&env, &vault).init(&token_id);
+    client.approve_vault(&admin, &vault);
+
+    let stream_id = client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+    // A plain stream whose tokens sit in the contract
+    client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    // Vault earns 10% yield; the refunded half carries its share
+    token_admin_client.mint(&vault, &100);
+
+    client.cancel_partial(&stream_id, &sender, &500);
+
+    assert_eq!(token.balance(&sender), 550);
+    assert_eq!(token.balance(&vault), 550);
+    assert_eq!(token.balance(&contract_id), 1000);
+    assert_eq!(client.get_vault_deposited(&vault), 500);
+
+    // The rest is still fully redeemable
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.withdraw(&stream_id, &receiver);
+    assert_eq!(token.balance(&vault), 0);
+    assert_eq!(client.get_vault_deposited(&vault), 0);
+}
@@ -0,0 +1,128 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Address, Env, Vec,
+};
+
+use crate::errors::Error;
+use crate::types::CurveType;
+
+// Minimal vault returning 1:1 shares
+#[contract]
+pub struct MockVault;
+
+#[contractimpl]
+impl MockVault {
+    pub fn deposit(_env: Env, _from: Address, amount: i128) -> i128 {
+        amount
+    }
+
+    pub fn withdraw(_env: Env, _to: Address, shares: i128) -> i128 {
+        shares
+    }
+
+    pub fn get_value(_env: Env, shares: i128) -> i128 {
+        shares
+    }
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+fn setup(env: &Env) -> (StellarStreamContractClient<'_>, Address) {
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    (client, admin)
+}
+
+#[test]
+fn test_get_approved_vaults_after_revoke() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    let vault_a = Address::generate(&env);
+    let vault_b = Address::generate(&env);
+    assert_eq!(client.get_approved_vaults().len(), 0);
+
+    client.approve_vault(&admin, &vault_a);
+    client.approve_vault(&admin, &vault_b);
+    assert_eq!(client.get_approved_vaults().len(), 2);
+
+    client.revoke_vault(&admin, &vault_a);
+
+    let vaults = client.get_approved_vaults();
+    assert_eq!(vaults.len(), 1);
+    assert_eq!(vaults.get(0).unwrap(), vault_b);
+    assert!(!client.is_vault_approved(&vault_a));
+}
+
+#[test]
+fn test_non_admin_cannot_approve_vault() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let non_admin = Address::generate(&env);
+    let vault = Address::generate(&env);
+
+    let result = client.try_approve_vault(&non_admin, &vault);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_vault_cap_rejects_excess_principal() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let vault = env.register(MockVault, ());
+    client.approve_vault(&admin, &vault);
+    client.set_vault_cap(&admin, &vault, &1500);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, _) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    client.create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+    assert_eq!(client.get_vault_deposited(&vault), 1000);
+
+    // A second 1000 would take the vault to 2000, over its 1500 cap
+    let result = client.try_create_stream_with_milestones(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &Vec::new(&env),
+        &CurveType::Linear,
+        &false,
+        &Some(vault.clone()),
+    );
+    assert_eq!(result, Err(Ok(Error::VaultCapExceeded)));
+    assert_eq!(client.get_vault_deposited(&vault), 1000);
+}
@@ -1,5 +1,7 @@
 use soroban_sdk::{Address, Env};
 
+use crate::types::VotingPowerMode;
+
 /// Get voting power for a stream
 /// Returns the unlocked balance that can be used for voting
 pub fn get_voting_power(_env: &Env, stream: &crate::types::Stream, current_time: u64) -> i128 {
@@ -7,19 +9,36 @@ pub fn get_voting_power(_env: &Env, stream: &crate::types::Stream, current_time:
         return 0;
     }
 
-    // Calculate unlocked amount
-    let unlocked = if current_time < stream.start_time {
-        0
-    } else if current_time >= stream.end_time {
-        stream.total_amount
-    } else {
-        let elapsed = (current_time - stream.start_time) as i128;
-        let duration = (stream.end_time - stream.start_time) as i128;
-        (stream.total_amount * elapsed) / duration
-    };
-
     // Return unlocked minus already withdrawn
-    unlocked - stream.withdrawn_amount
+    unlocked_amount(stream, current_time) - stream.withdrawn_amount
+}
+
+/// Get the balance still locked in a stream (not yet unlocked)
+pub fn get_locked_balance(stream: &crate::types::Stream, current_time: u64) -> i128 {
+    if stream.cancelled {
+        return 0;
+    }
+    stream.total_amount - unlocked_amount(stream, current_time)
+}
+
+/// Voting power of a stream under the given mode
+pub fn get_voting_power_for_mode(
+    env: &Env,
+    stream: &crate::types::Stream,
+    current_time: u64,
+    mode: VotingPowerMode,
+) -> i128 {
+    match mode {
+        VotingPowerMode::Unlocked => get_voting_power(env, stream, current_time),
+        VotingPowerMode::Locked => get_locked_balance(stream, current_time),
+        VotingPowerMode::Total => get_total_balance(stream),
+    }
+}
+
+/// Same vesting the contract pays out on, so pauses, cliffs and curves count
+/// identically for withdrawals and votes
+fn unlocked_amount(stream: &crate::types::Stream, current_time: u64) -> i128 {
+    crate::StellarStreamContract::calculate_unlocked(stream, current_time)
 }
 
 /// Get total stream balance (locked + unlocked)
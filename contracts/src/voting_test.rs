@@ -6,6 +6,8 @@ use soroban_sdk::{
     Address, Env,
 };
 
+use crate::types::VotingPowerMode;
+
 fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
     let contract_id = env
         .register_stellar_asset_contract_v2(admin.clone())
@@ -38,6 +40,7 @@ fn test_get_voting_power() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -110,6 +113,7 @@ fn test_delegate_voting_power() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -149,6 +153,7 @@ fn test_get_delegated_voting_power() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -160,6 +165,7 @@ fn test_get_delegated_voting_power() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -209,6 +215,7 @@ fn test_voting_power_after_withdrawal() {
         &1000,
         &100,
         &200,
+        &100,
         &crate::types::CurveType::Linear,
         &false,
     );
@@ -236,3 +243,102 @@ fn test_voting_power_after_withdrawal() {
     let power_after = client.get_voting_power(&stream_id);
     assert_eq!(power_after, 0);
 }
+
+#[test]
+fn test_voting_power_modes_at_mid_vesting() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+
+    let (token_address, _token_client) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_address);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    client.initialize(&admin);
+    token_admin_client.mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+
+    // Withdraw 200 at 20%, then move to 50%
+    env.ledger().with_mut(|li| li.timestamp = 120);
+    client.withdraw(&stream_id, &receiver);
+    env.ledger().with_mut(|li| li.timestamp = 150);
+
+    assert_eq!(client.get_voting_power_mode(), VotingPowerMode::Unlocked);
+    assert_eq!(client.get_voting_power(&stream_id), 300);
+    assert_eq!(
+        client.get_voting_power_with_mode(&stream_id, &VotingPowerMode::Locked),
+        500
+    );
+    assert_eq!(
+        client.get_voting_power_with_mode(&stream_id, &VotingPowerMode::Total),
+        800
+    );
+
+    client.set_voting_power_mode(&admin, &VotingPowerMode::Locked);
+    assert_eq!(client.get_voting_power(&stream_id), 500);
+
+    client.set_voting_power_mode(&admin, &VotingPowerMode::Total);
+    assert_eq!(client.get_voting_power(&stream_id), 800);
+}
+
+#[test]
+fn test_paused_stream_voting_power_frozen_at_pause() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+
+    let (token_address, _token_client) = create_token_contract(&env, &admin);
+    let token_admin_client = StellarAssetClient::new(&env, &token_address);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    token_admin_client.mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_address,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &crate::types::CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 140);
+    client.pause_stream(&stream_id, &sender);
+
+    // Vesting stops at the pause, and so does voting power
+    env.ledger().with_mut(|li| li.timestamp = 180);
+    assert_eq!(client.get_voting_power(&stream_id), 400);
+    assert_eq!(
+        client.get_voting_power_with_mode(&stream_id, &VotingPowerMode::Locked),
+        600
+    );
+
+    // After resuming, the paused time is skipped rather than caught up
+    client.unpause_stream(&stream_id, &sender);
+    env.ledger().with_mut(|li| li.timestamp = 190);
+    assert_eq!(client.get_voting_power(&stream_id), 500);
+}
@@ -0,0 +1,653 @@
+#![cfg(test)]
+use crate::{StellarStreamContract, StellarStreamContractClient};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events, IssuerFlags, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec,
+    xdr::ToXdr,
+    Address, BytesN, Env, Symbol, TryFromVal, Vec,
+};
+
+use crate::errors::Error;
+use crate::storage::MAX_BATCH;
+use crate::types::{BatchOperationEvent, CurveType, Role, StreamActivatedEvent, StreamClaimEvent};
+
+fn activation_events(env: &Env) -> Vec<StreamActivatedEvent> {
+    let mut events = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        if Symbol::try_from_val(env, &topics.get(0).unwrap()) == Ok(symbol_short!("activate")) {
+            events.push_back(StreamActivatedEvent::try_from_val(env, &data).unwrap());
+        }
+    }
+    events
+}
+
+fn create_token_contract<'a>(env: &Env, admin: &Address) -> (Address, TokenClient<'a>) {
+    let contract_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    (contract_id.clone(), TokenClient::new(env, &contract_id))
+}
+
+struct WithdrawContext<'a> {
+    client: StellarStreamContractClient<'a>,
+    token: TokenClient<'a>,
+    token_id: Address,
+    admin: Address,
+    sender: Address,
+    receiver: Address,
+    stream_id: u64,
+}
+
+fn setup(env: &Env) -> WithdrawContext<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(env);
+    let receiver = Address::generate(env);
+    let (token_id, token) = create_token_contract(env, &admin);
+    StellarAssetClient::new(env, &token_id).mint(&sender, &1000);
+
+    let stream_id = client.create_stream(
+        &sender,
+        &receiver,
+        &token_id,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    WithdrawContext {
+        client,
+        token,
+        token_id,
+        admin,
+        sender,
+        receiver,
+        stream_id,
+    }
+}
+
+#[test]
+fn test_withdraw_to_with_memo_emits_memo() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let exchange = Address::generate(&env);
+    let memo = BytesN::from_array(&env, &[7; 32]);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let amount = ctx
+        .client
+        .withdraw_to_with_memo(&ctx.stream_id, &ctx.receiver, &exchange, &memo);
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("claim"))
+        })
+        .unwrap();
+    let event = StreamClaimEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.memo, Some(memo));
+    assert_eq!(event.destination, exchange);
+    assert_eq!(event.claimer, ctx.receiver);
+
+    assert_eq!(amount, 500);
+    assert_eq!(ctx.token.balance(&exchange), 500);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 0);
+}
+
+#[test]
+fn test_withdraw_to_restricted_destination_rejected() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let officer = Address::generate(&env);
+    ctx.client
+        .grant_role(&ctx.admin, &officer, &Role::ComplianceOfficer);
+    let destination = Address::generate(&env);
+    ctx.client.restrict_address(&officer, &destination);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let memo = BytesN::from_array(&env, &[0; 32]);
+    let result =
+        ctx.client
+            .try_withdraw_to_with_memo(&ctx.stream_id, &ctx.receiver, &destination, &memo);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+}
+
+#[test]
+fn test_withdraw_to_custody_address() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let custody = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let amount = ctx
+        .client
+        .withdraw_to(&ctx.stream_id, &ctx.receiver, &custody);
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("claim"))
+        })
+        .unwrap();
+    let event = StreamClaimEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.destination, custody);
+    assert_eq!(event.memo, None);
+
+    assert_eq!(amount, 500);
+    assert_eq!(ctx.token.balance(&custody), 500);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 0);
+    // The stream itself still belongs to the receiver
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).receiver, ctx.receiver);
+
+    let outsider = Address::generate(&env);
+    let result = ctx
+        .client
+        .try_withdraw_to(&ctx.stream_id, &outsider, &outsider);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_activation_event_fires_on_first_withdrawal_only() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    ctx.client.withdraw(&ctx.stream_id, &ctx.receiver);
+    let events = activation_events(&env);
+    assert_eq!(events.len(), 1);
+    let event = events.get(0).unwrap();
+    assert_eq!(event.stream_id, ctx.stream_id);
+    assert_eq!(event.receiver, ctx.receiver);
+    assert_eq!(event.timestamp, 150);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    ctx.client.withdraw(&ctx.stream_id, &ctx.receiver);
+    assert!(activation_events(&env).is_empty());
+}
+
+#[test]
+fn test_withdraw_batch_skips_streams_it_cannot_pay() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    StellarAssetClient::new(&env, &ctx.token_id).mint(&ctx.sender, &4000);
+
+    let create = |receiver: &Address, start: u64| {
+        ctx.client.create_stream(
+            &ctx.sender,
+            receiver,
+            &ctx.token_id,
+            &1000,
+            &start,
+            &(start + 100),
+            &start,
+            &CurveType::Linear,
+            &false,
+        )
+    };
+    let paused = create(&ctx.receiver, 100);
+    let not_started = create(&ctx.receiver, 500);
+    let cancelled = create(&ctx.receiver, 100);
+    let someone_else = create(&Address::generate(&env), 100);
+
+    let (other_token, _) = create_token_contract(&env, &ctx.admin);
+    StellarAssetClient::new(&env, &other_token).mint(&ctx.sender, &1000);
+    let token_paused = ctx.client.create_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &other_token,
+        &1000,
+        &100,
+        &200,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    ctx.client.pause_stream(&paused, &ctx.sender);
+    ctx.client.cancel(&cancelled, &ctx.sender);
+    ctx.client.pause_token(&ctx.admin, &other_token);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let stream_ids = vec![
+        &env,
+        ctx.stream_id,
+        paused,
+        token_paused,
+        not_started,
+        cancelled,
+        someone_else,
+        99,
+    ];
+    let amounts = ctx
+        .client
+        .withdraw_batch(&stream_ids, &ctx.receiver, &false);
+
+    let claims = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| {
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()) == Ok(symbol_short!("claim"))
+        })
+        .count();
+    assert_eq!(claims, 1);
+    assert_eq!(amounts, vec![&env, 500, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 500);
+}
+
+#[test]
+fn test_get_withdrawable_matches_withdraw_without_auth() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    // No authorizations at all: the view must not ask for any
+    env.set_auths(&[]);
+    assert_eq!(ctx.client.get_withdrawable(&ctx.stream_id), 500);
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).withdrawn_amount, 0);
+
+    env.mock_all_auths_allowing_non_root_auth();
+    assert_eq!(ctx.client.withdraw(&ctx.stream_id, &ctx.receiver), 500);
+    assert_eq!(ctx.client.get_withdrawable(&ctx.stream_id), 0);
+}
+
+#[test]
+fn test_get_withdrawable_reports_why_withdraw_would_fail() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    ctx.client.pause_stream(&ctx.stream_id, &ctx.sender);
+    let result = ctx.client.try_get_withdrawable(&ctx.stream_id);
+    assert_eq!(result, Err(Ok(Error::StreamPaused)));
+
+    ctx.client.cancel(&ctx.stream_id, &ctx.sender);
+    let result = ctx.client.try_get_withdrawable(&ctx.stream_id);
+    assert_eq!(result, Err(Ok(Error::AlreadyCancelled)));
+
+    let result = ctx.client.try_get_withdrawable(&99);
+    assert_eq!(result, Err(Ok(Error::StreamNotFound)));
+}
+
+#[test]
+fn test_withdraw_amount_partial_withdrawals_sum_to_unlocked() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 160);
+    assert_eq!(
+        ctx.client
+            .withdraw_amount(&ctx.stream_id, &ctx.receiver, &250),
+        250
+    );
+    assert_eq!(
+        ctx.client
+            .withdraw_amount(&ctx.stream_id, &ctx.receiver, &250),
+        250
+    );
+    // Only 100 of the 600 unlocked is left, so the request is clamped
+    assert_eq!(
+        ctx.client
+            .withdraw_amount(&ctx.stream_id, &ctx.receiver, &250),
+        100
+    );
+
+    assert_eq!(ctx.client.get_stream(&ctx.stream_id).withdrawn_amount, 600);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 600);
+
+    let result = ctx
+        .client
+        .try_withdraw_amount(&ctx.stream_id, &ctx.receiver, &250);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    let result = ctx
+        .client
+        .try_withdraw_amount(&ctx.stream_id, &ctx.receiver, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_withdraw_batch_rejects_empty_and_oversized_batches() {
+    let env = Env::default();
+    let ctx = setup(&env);
+
+    let result = ctx
+        .client
+        .try_withdraw_batch(&Vec::new(&env), &ctx.receiver, &false);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+    let mut stream_ids = Vec::new(&env);
+    for _ in 0..=MAX_BATCH {
+        stream_ids.push_back(ctx.stream_id);
+    }
+    let result = ctx
+        .client
+        .try_withdraw_batch(&stream_ids, &ctx.receiver, &false);
+    assert_eq!(result, Err(Ok(Error::BatchTooLarge)));
+}
+
+#[test]
+fn test_withdraw_batch_summary_replaces_claim_events() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    StellarAssetClient::new(&env, &ctx.token_id).mint(&ctx.sender, &1000);
+
+    let second = ctx.client.create_stream(
+        &ctx.sender,
+        &ctx.receiver,
+        &ctx.token_id,
+        &1000,
+        &100,
+        &300,
+        &100,
+        &CurveType::Linear,
+        &false,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let stream_ids = vec![&env, ctx.stream_id, second, 99];
+    let amounts = ctx.client.withdraw_batch(&stream_ids, &ctx.receiver, &true);
+    assert_eq!(amounts, vec![&env, 500, 250, 0]);
+
+    let mut claims = 0;
+    let mut summaries = Vec::new(&env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic = Symbol::try_from_val(&env, &topics.get(0).unwrap());
+        if topic == Ok(symbol_short!("claim")) {
+            claims += 1;
+        } else if topic == Ok(symbol_short!("batch")) {
+            summaries.push_back(BatchOperationEvent::try_from_val(&env, &data).unwrap());
+        }
+    }
+    assert_eq!(claims, 0);
+    assert_eq!(summaries.len(), 1);
+
+    let summary = summaries.get(0).unwrap();
+    assert_eq!(summary.operation, symbol_short!("withdraw"));
+    assert_eq!(summary.count, 2);
+    assert_eq!(summary.total_amount, 750);
+    let expected_hash: BytesN<32> = env.crypto().sha256(&stream_ids.clone().to_xdr(&env)).into();
+    assert_eq!(summary.stream_ids_hash, expected_hash);
+}
+
+#[test]
+fn test_withdraw_destination_routes_withdrawals() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let cold_wallet = Address::generate(&env);
+
+    let result = ctx
+        .client
+        .try_set_withdraw_destination(&ctx.stream_id, &ctx.sender, &cold_wallet);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    ctx.client
+        .set_withdraw_destination(&ctx.stream_id, &ctx.receiver, &cold_wallet);
+    assert_eq!(
+        ctx.client.get_withdraw_destination(&ctx.stream_id),
+        Some(cold_wallet.clone())
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    assert_eq!(ctx.client.withdraw(&ctx.stream_id, &ctx.receiver), 500);
+    assert_eq!(ctx.token.balance(&cold_wallet), 500);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 0);
+
+    ctx.client
+        .clear_withdraw_destination(&ctx.stream_id, &ctx.receiver);
+    env.ledger().with_mut(|li| li.timestamp = 160);
+    ctx.client.withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(ctx.token.balance(&ctx.receiver), 100);
+}
+
+#[test]
+fn test_withdraw_destination_checked_against_restrictions() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let cold_wallet = Address::generate(&env);
+
+    ctx.client
+        .set_withdraw_destination(&ctx.stream_id, &ctx.receiver, &cold_wallet);
+
+    // Restricted after being set: withdrawals stop until it is cleared
+    let officer = Address::generate(&env);
+    ctx.client
+        .grant_role(&ctx.admin, &officer, &Role::ComplianceOfficer);
+    ctx.client.restrict_address(&officer, &cold_wallet);
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let result = ctx.client.try_withdraw(&ctx.stream_id, &ctx.receiver);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+
+    let restricted = Address::generate(&env);
+    ctx.client.restrict_address(&officer, &restricted);
+    let result =
+        ctx.client
+            .try_set_withdraw_destination(&ctx.stream_id, &ctx.receiver, &restricted);
+    assert_eq!(result, Err(Ok(Error::AddressRestricted)));
+}
+
+#[test]
+fn test_withdraw_destination_cleared_on_receiver_change() {
+    let env = Env::default();
+    let ctx = setup(&env);
+    let cold_wallet = Address::generate(&env);
+    let new_receiver = Address::generate(&env);
+
+    ctx.client
+        .set_withdraw_destination(&ctx.stream_id, &ctx.receiver, &cold_wallet);
+    ctx.client
+        .transfer_receiver(&ctx.stream_id, &ctx.sender, &new_receiver);
+    assert_eq!(ctx.client.get_withdraw_destination(&ctx.stream_id), None);
+
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    ctx.client.withdraw(&ctx.stream_id, &new_receiver);
+    assert_eq!(ctx.token.balance(&new_receiver), 500);
+    assert_eq!(ctx.token.balance(&cold_wallet), 0);
+}
+
+#[test]
+fn test_withdraw_queue_serves_higher_priority_first() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let low_receiver = Address::generate(&env);
+    let high_receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &2000);
+
+    let low = client.create_stream(
+        &sender,
+        &low_receiver,
+        &token_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+    let high = client.create_stream(
+        &sender,
+        &high_receiver,
+        &token_id,
+        &1000,
+        &0,
+        &100,
+        &0,
+        &CurveType::Linear,
+        &false,
+    );
+    client.set_stream_priority(&admin, &high, &10);
+    assert_eq!(client.get_stream_priority(&high), 10);
+    assert_eq!(client.get_stream_priority(&low), 0);
+
+    let outsider = Address::generate(&env);
+    let result = client.try_set_stream_priority(&outsider, &low, &20);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+
+    // Liquidity runs short: only 700 of the 1000 owed is on hand
+    token.burn(&contract_id, &1300);
+    env.ledger().with_mut(|li| li.timestamp = 50);
+
+    assert_eq!(
+        client.process_withdraw_queue(&admin, &token_id),
+        (700, true)
+    );
+    assert_eq!(token.balance(&high_receiver), 500);
+    assert_eq!(token.balance(&low_receiver), 200);
+    assert_eq!(client.get_stream(&high).withdrawn_amount, 500);
+    assert_eq!(client.get_stream(&low).withdrawn_amount, 200);
+
+    let result = client.try_process_withdraw_queue(&outsider, &token_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_withdraw_queue_skips_restricted_destinations_and_failed_transfers() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let officer = Address::generate(&env);
+    client.initialize(&admin);
+    client.grant_role(&admin, &officer, &Role::ComplianceOfficer);
+
+    let sender = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    sac.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token_id = sac.address();
+    let token = TokenClient::new(&env, &token_id);
+    let token_admin_client = StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&sender, &300);
+
+    let mut streams = Vec::new(&env);
+    let mut receivers = Vec::new(&env);
+    for _ in 0..3 {
+        let receiver = Address::generate(&env);
+        streams.push_back(client.create_stream(
+            &sender,
+            &receiver,
+            &token_id,
+            &100,
+            &0,
+            &100,
+            &0,
+            &CurveType::Linear,
+            &false,
+        ));
+        receivers.push_back(receiver);
+    }
+
+    // Stream 0 pays a destination restricted after it was set
+    let destination = Address::generate(&env);
+    client.set_withdraw_destination(
+        &streams.get(0).unwrap(),
+        &receivers.get(0).unwrap(),
+        &destination,
+    );
+    client.restrict_address(&officer, &destination);
+    // Stream 1's receiver can no longer hold the token, so its transfer fails
+    token_admin_client.set_authorized(&receivers.get(1).unwrap(), &false);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(
+        client.process_withdraw_queue(&admin, &token_id),
+        (100, true)
+    );
+    assert_eq!(token.balance(&destination), 0);
+    assert_eq!(
+        client.get_stream(&streams.get(0).unwrap()).withdrawn_amount,
+        0
+    );
+    assert_eq!(token.balance(&receivers.get(1).unwrap()), 0);
+    assert_eq!(
+        client.get_stream(&streams.get(1).unwrap()).withdrawn_amount,
+        0
+    );
+    assert_eq!(token.balance(&receivers.get(2).unwrap()), 100);
+    assert_eq!(
+        client.get_stream(&streams.get(2).unwrap()).withdrawn_amount,
+        100
+    );
+}
+
+#[test]
+fn test_withdraw_queue_pages_and_skips_router_settled_streams() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let contract_id = env.register(StellarStreamContract, ());
+    let client = StellarStreamContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (token_id, token) = create_token_contract(&env, &admin);
+    StellarAssetClient::new(&env, &token_id).mint(&sender, &10000);
+
+    for _ in 0..=MAX_BATCH {
+        client.create_stream(
+            &sender,
+            &receiver,
+            &token_id,
+            &100,
+            &0,
+            &100,
+            &0,
+            &CurveType::Linear,
+            &false,
+        );
+    }
+
+    // Stream 0 settles through a router, so the queue leaves it alone
+    let router = Address::generate(&env);
+    let settlement_token = Address::generate(&env);
+    client.approve_router(&admin, &router);
+    client.set_settlement(&0, &receiver, &settlement_token, &router, &10_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let first_page = (100 * (MAX_BATCH as i128 - 1), false);
+    assert_eq!(client.process_withdraw_queue(&admin, &token_id), first_page);
+    assert_eq!(
+        client.process_withdraw_queue(&admin, &token_id),
+        (100, true)
+    );
+    assert_eq!(token.balance(&receiver), 100 * MAX_BATCH as i128);
+    assert_eq!(client.get_stream(&0).withdrawn_amount, 0);
+
+    // A finished scan starts over from the first stream
+    assert_eq!(client.process_withdraw_queue(&admin, &token_id), (0, false));
+}